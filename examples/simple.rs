@@ -6,23 +6,23 @@ fn main() {
     let env = EnvBuilder::new().open("test-lmdb", 0o777).unwrap();
 
     let db_handle = env.get_default_db(DbFlags::empty()).unwrap();
-    let txn = env.new_transaction().unwrap();
-    {
+
+    // Transaction is committed if closure succeeds and aborted
+    // otherwise, commit error is returned as well
+    let res = env.with_write_txn(|txn| {
         let db = txn.bind(&db_handle); // get a database bound to this transaction
 
-        let pairs = vec![("Albert", "Einstein",),
+        let pairs = [("Albert", "Einstein",),
                          ("Joe", "Smith",),
                          ("Jack", "Daniels")];
 
         for &(name, surname) in pairs.iter() {
-            db.set(&surname, &name).unwrap();
+            try!(db.set(&surname, &name));
         }
-    }
+        Ok(())
+    });
 
-    // Note: `commit` is choosen to be explicit as
-    // in case of failure it is responsibility of
-    // the client to handle the error
-    match txn.commit() {
+    match res {
         Err(_) => panic!("failed to commit!"),
         Ok(_) => ()
     }
@@ -0,0 +1,58 @@
+//! Measures `Cursor::skip` against stepping an iterator for pagination
+//! with growing offsets, run with
+//! `cargo run --release --example skip_bench`
+
+extern crate lmdb_rs as lmdb;
+
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+use lmdb::{EnvBuilder, DbFlags};
+
+const KEYS: u64 = 100_000;
+const ROUNDS: u32 = 20;
+
+fn main() {
+    let path = env::temp_dir().join("lmdb-rs-skip-bench");
+    let _ = fs::remove_dir_all(&path);
+
+    let env = EnvBuilder::new().map_size(64 * 1024 * 1024).open(&path, 0o777).unwrap();
+    let db_handle = env.get_default_db(DbFlags::empty()).unwrap();
+
+    env.with_write_txn(|txn| {
+        let db = txn.bind(&db_handle);
+        for i in 0..KEYS {
+            try!(db.set(&i.to_be_bytes().to_vec(), &i));
+        }
+        Ok(())
+    }).unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db_handle);
+
+    for &offset in &[10usize, 1_000, 50_000, 200_000] {
+        let start = Instant::now();
+        let mut skipped = 0;
+        for _ in 0..ROUNDS {
+            let mut cursor = db.new_cursor().unwrap();
+            cursor.to_first().unwrap();
+            skipped = cursor.skip(offset).unwrap();
+        }
+        let skip_time = start.elapsed() / ROUNDS;
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            let iter = db.iter().unwrap();
+            assert_eq!(iter.skip(offset).next().is_some(), skipped == offset);
+        }
+        let iter_time = start.elapsed() / ROUNDS;
+
+        println!("offset {:>7}: skipped {:>7}, skip {:>10?}, iterator {:>10?}",
+                 offset, skipped, skip_time, iter_time);
+    }
+
+    drop(reader);
+    drop(env);
+    let _ = fs::remove_dir_all(&path);
+}
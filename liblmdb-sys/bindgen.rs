@@ -0,0 +1,54 @@
+//! Generates bindings from lmdb.h, enabled by `bindgen` feature
+
+extern crate bindgen;
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use self::bindgen::callbacks::{IntKind, ParseCallbacks};
+
+#[derive(Debug)]
+struct Callbacks;
+
+impl ParseCallbacks for Callbacks {
+    /// Return codes are `int`, everything else is used as `unsigned int`
+    fn int_macro(&self, name: &str, _value: i64) -> Option<IntKind> {
+        match name {
+            "MDB_SUCCESS" | "MDB_KEYEXIST" | "MDB_NOTFOUND" | "MDB_PAGE_NOTFOUND" |
+            "MDB_CORRUPTED" | "MDB_PANIC" | "MDB_VERSION_MISMATCH" | "MDB_INVALID" |
+            "MDB_MAP_FULL" | "MDB_DBS_FULL" | "MDB_READERS_FULL" | "MDB_TLS_FULL" |
+            "MDB_TXN_FULL" | "MDB_CURSOR_FULL" | "MDB_PAGE_FULL" | "MDB_MAP_RESIZED" |
+            "MDB_INCOMPATIBLE" | "MDB_BAD_RSLOT" | "MDB_BAD_TXN" | "MDB_BAD_VALSIZE" |
+            "MDB_BAD_DBI" | "MDB_LAST_ERRCODE" => Some(IntKind::Int),
+            _ => Some(IntKind::UInt),
+        }
+    }
+}
+
+/// Writes `$OUT_DIR/bindings.rs` for header found in `include_dir`
+pub fn generate(include_dir: &Path) {
+    let header = include_dir.join("lmdb.h");
+    println!("cargo:rerun-if-changed={}", header.display());
+
+    let bindings = bindgen::Builder::default()
+        .header(header.to_string_lossy())
+        .allowlist_var("^(MDB|mdb)_.*")
+        .allowlist_type("^(MDB|mdb)_.*")
+        .allowlist_function("^(MDB|mdb)_.*")
+        .ctypes_prefix("::libc")
+        // platform dependent, defined by hand in lib.rs
+        .blocklist_item("mode_t")
+        .blocklist_item("mdb_mode_t")
+        .blocklist_item("mdb_filehandle_t")
+        .blocklist_item("^__.*")
+        .parse_callbacks(Box::new(Callbacks))
+        .layout_tests(false)
+        .prepend_enum_name(false)
+        .generate_comments(false)
+        .generate()
+        .expect("unable to generate LMDB bindings");
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    bindings.write_to_file(out_path.join("bindings.rs"))
+        .expect("unable to write LMDB bindings");
+}
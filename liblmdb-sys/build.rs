@@ -1,17 +1,167 @@
 extern crate gcc;
+extern crate pkg_config;
+
+#[cfg(feature = "bindgen")]
+#[path = "bindgen.rs"]
+mod generate;
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Major and minor version of LMDB the bindings were written against
+const BOUND_VERSION: (u32, u32) = (0, 9);
 
 fn main() {
-    let target = std::env::var("TARGET").unwrap();
+    println!("cargo:rerun-if-env-changed=LMDB_SYS_USE_SYSTEM");
+    println!("cargo:rerun-if-env-changed=LMDB_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=LMDB_INCLUDE_DIR");
+
+    let vendored = env::var_os("CARGO_FEATURE_VENDORED").is_some();
+    let force_system = env::var("LMDB_SYS_USE_SYSTEM").map(|v| v == "1").unwrap_or(false);
+
+    if vendored && !force_system {
+        build_vendored();
+    } else {
+        link_system();
+    }
+
+    #[cfg(feature = "bindgen")]
+    {
+        let include_dir = match env::var("LMDB_INCLUDE_DIR") {
+            Ok(dir) if !vendored || force_system => Path::new(&dir).to_path_buf(),
+            _ => Path::new("mdb/libraries/liblmdb").to_path_buf(),
+        };
+        generate::generate(&include_dir);
+    }
+}
+
+fn build_vendored() {
+    let target = env::var("TARGET").unwrap();
 
     let mut config = gcc::Config::new();
     config.file("mdb/libraries/liblmdb/mdb.c")
           .file("mdb/libraries/liblmdb/midl.c");
     config.opt_level(2);
 
+    if !target.contains("msvc") {
+        // as in LMDB Makefile
+        config.flag("-Wno-unused-parameter");
+    }
+
+    if target.contains("windows") {
+        // security descriptor functions used for mutexes
+        println!("cargo:rustc-link-lib=advapi32");
+    }
+
     if target.contains("dragonfly") {
         config.flag("-DMDB_DSYNC=O_SYNC");
         config.flag("-DMDB_FDATASYNC=fsync");
     }
 
+    lock_flags(&target, &mut config);
+
     config.compile("liblmdb.a");
 }
+
+/// Translates `robust-mutex` and `posix-sem` features into defines,
+/// without them LMDB picks lock implementation on its own
+fn lock_flags(target: &str, config: &mut gcc::Config) {
+    let robust = env::var_os("CARGO_FEATURE_ROBUST_MUTEX").is_some();
+    let mut posix_sem = env::var_os("CARGO_FEATURE_POSIX_SEM").is_some();
+
+    // LMDB always uses POSIX semaphores there
+    let sem_only = target.contains("apple") || target.contains("bsd") || target.contains("dragonfly");
+
+    // features are additive, so both may be enabled by different
+    // dependents; robust mutexes win as POSIX semaphores aren't robust
+    if robust && posix_sem {
+        println!("cargo:warning=both `robust-mutex` and `posix-sem` features are enabled, \
+                  using robust mutexes");
+        posix_sem = false;
+    }
+    if robust && (sem_only || target.contains("windows") || target.contains("android")) {
+        panic!("`robust-mutex` feature isn't supported on {}", target);
+    }
+    if posix_sem && target.contains("windows") {
+        panic!("`posix-sem` feature isn't supported on {}", target);
+    }
+
+    if robust {
+        config.flag("-DMDB_USE_ROBUST=1");
+    }
+    if posix_sem {
+        config.flag("-DMDB_USE_POSIX_SEM=1");
+        if !sem_only {
+            println!("cargo:rustc-link-lib=pthread");
+        }
+    }
+}
+
+fn link_system() {
+    if env::var_os("CARGO_FEATURE_ROBUST_MUTEX").is_some() || env::var_os("CARGO_FEATURE_POSIX_SEM").is_some() {
+        println!("cargo:warning=lock features have no effect on system liblmdb");
+    }
+
+    if let Ok(lib_dir) = env::var("LMDB_LIB_DIR") {
+        println!("cargo:rustc-link-search=native={}", lib_dir);
+        println!("cargo:rustc-link-lib=lmdb");
+
+        match env::var("LMDB_INCLUDE_DIR") {
+            Ok(include_dir) => match header_version(&Path::new(&include_dir).join("lmdb.h")) {
+                Some(version) => check_version(&version),
+                None => println!("cargo:warning=can't read LMDB version from {}/lmdb.h", include_dir),
+            },
+            Err(_) => println!("cargo:warning=LMDB_INCLUDE_DIR isn't set, LMDB version isn't checked"),
+        }
+        return;
+    }
+
+    match pkg_config::Config::new().probe("lmdb") {
+        Ok(lib) => check_version(&lib.version),
+        Err(e) => panic!("system liblmdb not found via pkg-config ({}), \
+                          set LMDB_LIB_DIR or enable `vendored` feature", e),
+    }
+}
+
+/// Extracts `MDB_VERSION_{MAJOR,MINOR,PATCH}` from lmdb.h
+fn header_version(path: &Path) -> Option<String> {
+    let mut header = String::new();
+    if File::open(path).and_then(|mut f| f.read_to_string(&mut header)).is_err() {
+        return None;
+    }
+
+    let part = |name: &str| {
+        header.lines()
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                match (words.next(), words.next(), words.next()) {
+                    (Some("#define"), Some(n), Some(v)) if n == name => Some(v.to_owned()),
+                    _ => None,
+                }
+            })
+            .next()
+    };
+
+    match (part("MDB_VERSION_MAJOR"), part("MDB_VERSION_MINOR"), part("MDB_VERSION_PATCH")) {
+        (Some(major), Some(minor), Some(patch)) => Some(format!("{}.{}.{}", major, minor, patch)),
+        _ => None,
+    }
+}
+
+fn check_version(version: &str) {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>().ok());
+    let found = match (parts.next(), parts.next()) {
+        (Some(Some(major)), Some(Some(minor))) => (major, minor),
+        _ => {
+            println!("cargo:warning=can't parse system LMDB version '{}'", version);
+            return;
+        }
+    };
+
+    if found != BOUND_VERSION {
+        println!("cargo:warning=system LMDB {} doesn't match bindings for {}.{}.x",
+                 version, BOUND_VERSION.0, BOUND_VERSION.1);
+    }
+}
@@ -0,0 +1,386 @@
+/* automatically generated by rust-bindgen from mdb/libraries/liblmdb/lmdb.h,
+ * regenerate by building with `bindgen` feature and copying
+ * $OUT_DIR/bindings.rs here */
+
+pub const MDB_VERSION_MAJOR: ::libc::c_uint = 0;
+pub const MDB_VERSION_MINOR: ::libc::c_uint = 9;
+pub const MDB_VERSION_PATCH: ::libc::c_uint = 21;
+pub const MDB_VERSION_DATE: &'static [u8; 13usize] = b"June 1, 2017\0";
+pub const MDB_FIXEDMAP: ::libc::c_uint = 1;
+pub const MDB_NOSUBDIR: ::libc::c_uint = 16384;
+pub const MDB_NOSYNC: ::libc::c_uint = 65536;
+pub const MDB_RDONLY: ::libc::c_uint = 131072;
+pub const MDB_NOMETASYNC: ::libc::c_uint = 262144;
+pub const MDB_WRITEMAP: ::libc::c_uint = 524288;
+pub const MDB_MAPASYNC: ::libc::c_uint = 1048576;
+pub const MDB_NOTLS: ::libc::c_uint = 2097152;
+pub const MDB_NOLOCK: ::libc::c_uint = 4194304;
+pub const MDB_NORDAHEAD: ::libc::c_uint = 8388608;
+pub const MDB_NOMEMINIT: ::libc::c_uint = 16777216;
+pub const MDB_REVERSEKEY: ::libc::c_uint = 2;
+pub const MDB_DUPSORT: ::libc::c_uint = 4;
+pub const MDB_INTEGERKEY: ::libc::c_uint = 8;
+pub const MDB_DUPFIXED: ::libc::c_uint = 16;
+pub const MDB_INTEGERDUP: ::libc::c_uint = 32;
+pub const MDB_REVERSEDUP: ::libc::c_uint = 64;
+pub const MDB_CREATE: ::libc::c_uint = 262144;
+pub const MDB_NOOVERWRITE: ::libc::c_uint = 16;
+pub const MDB_NODUPDATA: ::libc::c_uint = 32;
+pub const MDB_CURRENT: ::libc::c_uint = 64;
+pub const MDB_RESERVE: ::libc::c_uint = 65536;
+pub const MDB_APPEND: ::libc::c_uint = 131072;
+pub const MDB_APPENDDUP: ::libc::c_uint = 262144;
+pub const MDB_MULTIPLE: ::libc::c_uint = 524288;
+pub const MDB_CP_COMPACT: ::libc::c_uint = 1;
+pub const MDB_SUCCESS: ::libc::c_int = 0;
+pub const MDB_KEYEXIST: ::libc::c_int = -30799;
+pub const MDB_NOTFOUND: ::libc::c_int = -30798;
+pub const MDB_PAGE_NOTFOUND: ::libc::c_int = -30797;
+pub const MDB_CORRUPTED: ::libc::c_int = -30796;
+pub const MDB_PANIC: ::libc::c_int = -30795;
+pub const MDB_VERSION_MISMATCH: ::libc::c_int = -30794;
+pub const MDB_INVALID: ::libc::c_int = -30793;
+pub const MDB_MAP_FULL: ::libc::c_int = -30792;
+pub const MDB_DBS_FULL: ::libc::c_int = -30791;
+pub const MDB_READERS_FULL: ::libc::c_int = -30790;
+pub const MDB_TLS_FULL: ::libc::c_int = -30789;
+pub const MDB_TXN_FULL: ::libc::c_int = -30788;
+pub const MDB_CURSOR_FULL: ::libc::c_int = -30787;
+pub const MDB_PAGE_FULL: ::libc::c_int = -30786;
+pub const MDB_MAP_RESIZED: ::libc::c_int = -30785;
+pub const MDB_INCOMPATIBLE: ::libc::c_int = -30784;
+pub const MDB_BAD_RSLOT: ::libc::c_int = -30783;
+pub const MDB_BAD_TXN: ::libc::c_int = -30782;
+pub const MDB_BAD_VALSIZE: ::libc::c_int = -30781;
+pub const MDB_BAD_DBI: ::libc::c_int = -30780;
+pub const MDB_LAST_ERRCODE: ::libc::c_int = -30780;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_env {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_txn {
+    _unused: [u8; 0],
+}
+pub type MDB_dbi = ::libc::c_uint;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_cursor {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_val {
+    pub mv_size: usize,
+    pub mv_data: *mut ::libc::c_void,
+}
+pub type MDB_cmp_func = ::std::option::Option<
+    unsafe extern "C" fn(a: *const MDB_val, b: *const MDB_val) -> ::libc::c_int,
+>;
+pub type MDB_rel_func = ::std::option::Option<
+    unsafe extern "C" fn(
+        item: *mut MDB_val,
+        oldptr: *mut ::libc::c_void,
+        newptr: *mut ::libc::c_void,
+        relctx: *mut ::libc::c_void,
+    ),
+>;
+pub const MDB_FIRST: MDB_cursor_op = 0;
+pub const MDB_FIRST_DUP: MDB_cursor_op = 1;
+pub const MDB_GET_BOTH: MDB_cursor_op = 2;
+pub const MDB_GET_BOTH_RANGE: MDB_cursor_op = 3;
+pub const MDB_GET_CURRENT: MDB_cursor_op = 4;
+pub const MDB_GET_MULTIPLE: MDB_cursor_op = 5;
+pub const MDB_LAST: MDB_cursor_op = 6;
+pub const MDB_LAST_DUP: MDB_cursor_op = 7;
+pub const MDB_NEXT: MDB_cursor_op = 8;
+pub const MDB_NEXT_DUP: MDB_cursor_op = 9;
+pub const MDB_NEXT_MULTIPLE: MDB_cursor_op = 10;
+pub const MDB_NEXT_NODUP: MDB_cursor_op = 11;
+pub const MDB_PREV: MDB_cursor_op = 12;
+pub const MDB_PREV_DUP: MDB_cursor_op = 13;
+pub const MDB_PREV_NODUP: MDB_cursor_op = 14;
+pub const MDB_SET: MDB_cursor_op = 15;
+pub const MDB_SET_KEY: MDB_cursor_op = 16;
+pub const MDB_SET_RANGE: MDB_cursor_op = 17;
+pub const MDB_PREV_MULTIPLE: MDB_cursor_op = 18;
+pub type MDB_cursor_op = u32;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_stat {
+    pub ms_psize: ::libc::c_uint,
+    pub ms_depth: ::libc::c_uint,
+    pub ms_branch_pages: usize,
+    pub ms_leaf_pages: usize,
+    pub ms_overflow_pages: usize,
+    pub ms_entries: usize,
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MDB_envinfo {
+    pub me_mapaddr: *mut ::libc::c_void,
+    pub me_mapsize: usize,
+    pub me_last_pgno: usize,
+    pub me_last_txnid: usize,
+    pub me_maxreaders: ::libc::c_uint,
+    pub me_numreaders: ::libc::c_uint,
+}
+extern "C" {
+    pub fn mdb_version(
+        major: *mut ::libc::c_int,
+        minor: *mut ::libc::c_int,
+        patch: *mut ::libc::c_int,
+    ) -> *mut ::libc::c_char;
+}
+extern "C" {
+    pub fn mdb_strerror(err: ::libc::c_int) -> *mut ::libc::c_char;
+}
+extern "C" {
+    pub fn mdb_env_create(env: *mut *mut MDB_env) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_open(
+        env: *mut MDB_env,
+        path: *const ::libc::c_char,
+        flags: ::libc::c_uint,
+        mode: mdb_mode_t,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_copy(env: *mut MDB_env, path: *const ::libc::c_char) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_copyfd(env: *mut MDB_env, fd: mdb_filehandle_t) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_copy2(
+        env: *mut MDB_env,
+        path: *const ::libc::c_char,
+        flags: ::libc::c_uint,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_copyfd2(
+        env: *mut MDB_env,
+        fd: mdb_filehandle_t,
+        flags: ::libc::c_uint,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_stat(env: *mut MDB_env, stat: *mut MDB_stat) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_info(env: *mut MDB_env, stat: *mut MDB_envinfo) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_sync(env: *mut MDB_env, force: ::libc::c_int) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_close(env: *mut MDB_env);
+}
+extern "C" {
+    pub fn mdb_env_set_flags(
+        env: *mut MDB_env,
+        flags: ::libc::c_uint,
+        onoff: ::libc::c_int,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_flags(env: *mut MDB_env, flags: *mut ::libc::c_uint) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_path(env: *mut MDB_env, path: *mut *const ::libc::c_char) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_fd(env: *mut MDB_env, fd: *mut mdb_filehandle_t) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_set_mapsize(env: *mut MDB_env, size: usize) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_set_maxreaders(env: *mut MDB_env, readers: ::libc::c_uint) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_maxreaders(env: *mut MDB_env, readers: *mut ::libc::c_uint)
+        -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_set_maxdbs(env: *mut MDB_env, dbs: MDB_dbi) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_maxkeysize(env: *mut MDB_env) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_set_userctx(env: *mut MDB_env, ctx: *mut ::libc::c_void) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_env_get_userctx(env: *mut MDB_env) -> *mut ::libc::c_void;
+}
+pub type MDB_assert_func =
+    ::std::option::Option<unsafe extern "C" fn(env: *mut MDB_env, msg: *const ::libc::c_char)>;
+extern "C" {
+    pub fn mdb_env_set_assert(env: *mut MDB_env, func: MDB_assert_func) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_txn_begin(
+        env: *mut MDB_env,
+        parent: *mut MDB_txn,
+        flags: ::libc::c_uint,
+        txn: *mut *mut MDB_txn,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_txn_env(txn: *mut MDB_txn) -> *mut MDB_env;
+}
+extern "C" {
+    pub fn mdb_txn_id(txn: *mut MDB_txn) -> usize;
+}
+extern "C" {
+    pub fn mdb_txn_commit(txn: *mut MDB_txn) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_txn_abort(txn: *mut MDB_txn);
+}
+extern "C" {
+    pub fn mdb_txn_reset(txn: *mut MDB_txn);
+}
+extern "C" {
+    pub fn mdb_txn_renew(txn: *mut MDB_txn) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_dbi_open(
+        txn: *mut MDB_txn,
+        name: *const ::libc::c_char,
+        flags: ::libc::c_uint,
+        dbi: *mut MDB_dbi,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_stat(txn: *mut MDB_txn, dbi: MDB_dbi, stat: *mut MDB_stat) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_dbi_flags(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        flags: *mut ::libc::c_uint,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_dbi_close(env: *mut MDB_env, dbi: MDB_dbi);
+}
+extern "C" {
+    pub fn mdb_drop(txn: *mut MDB_txn, dbi: MDB_dbi, del: ::libc::c_int) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_set_compare(txn: *mut MDB_txn, dbi: MDB_dbi, cmp: MDB_cmp_func) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_set_dupsort(txn: *mut MDB_txn, dbi: MDB_dbi, cmp: MDB_cmp_func) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_set_relfunc(txn: *mut MDB_txn, dbi: MDB_dbi, rel: MDB_rel_func) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_set_relctx(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        ctx: *mut ::libc::c_void,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_get(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        key: *mut MDB_val,
+        data: *mut MDB_val,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_put(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        key: *mut MDB_val,
+        data: *mut MDB_val,
+        flags: ::libc::c_uint,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_del(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        key: *mut MDB_val,
+        data: *mut MDB_val,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_open(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        cursor: *mut *mut MDB_cursor,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_close(cursor: *mut MDB_cursor);
+}
+extern "C" {
+    pub fn mdb_cursor_renew(txn: *mut MDB_txn, cursor: *mut MDB_cursor) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_txn(cursor: *mut MDB_cursor) -> *mut MDB_txn;
+}
+extern "C" {
+    pub fn mdb_cursor_dbi(cursor: *mut MDB_cursor) -> MDB_dbi;
+}
+extern "C" {
+    pub fn mdb_cursor_get(
+        cursor: *mut MDB_cursor,
+        key: *mut MDB_val,
+        data: *mut MDB_val,
+        op: MDB_cursor_op,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_put(
+        cursor: *mut MDB_cursor,
+        key: *mut MDB_val,
+        data: *mut MDB_val,
+        flags: ::libc::c_uint,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_del(cursor: *mut MDB_cursor, flags: ::libc::c_uint) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cursor_count(cursor: *mut MDB_cursor, countp: *mut usize) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_cmp(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        a: *const MDB_val,
+        b: *const MDB_val,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_dcmp(
+        txn: *mut MDB_txn,
+        dbi: MDB_dbi,
+        a: *const MDB_val,
+        b: *const MDB_val,
+    ) -> ::libc::c_int;
+}
+pub type MDB_msg_func = ::std::option::Option<
+    unsafe extern "C" fn(msg: *const ::libc::c_char, ctx: *mut ::libc::c_void) -> ::libc::c_int,
+>;
+extern "C" {
+    pub fn mdb_reader_list(
+        env: *mut MDB_env,
+        func: MDB_msg_func,
+        ctx: *mut ::libc::c_void,
+    ) -> ::libc::c_int;
+}
+extern "C" {
+    pub fn mdb_reader_check(env: *mut MDB_env, dead: *mut ::libc::c_int) -> ::libc::c_int;
+}
@@ -4,6 +4,7 @@ extern crate libc;
 
 pub use self::os::{mdb_mode_t, mdb_filehandle_t};
 use libc::{c_int, c_uint, c_void, c_char, size_t};
+use std::marker::{PhantomData, PhantomPinned};
 
 #[cfg(any(target_os = "macos", target_os = "ios", target_os = "linux",
           target_os = "freebsd", target_os = "dragonfly",
@@ -21,9 +22,15 @@ mod os {
 mod os {
     use libc;
 
+    // lmdb.h uses `int` for MSVC, `mode_t` otherwise; it is
+    // ignored on Windows anyway
+    #[cfg(target_env = "msvc")]
     pub type mdb_mode_t = libc::c_int;
+    #[cfg(not(target_env = "msvc"))]
+    pub type mdb_mode_t = libc::c_ushort;
 
-    pub type mdb_filehandle_t = libc::c_int;
+    /// `HANDLE`
+    pub type mdb_filehandle_t = *mut libc::c_void;
 }
 
 pub type MDB_dbi = c_uint;
@@ -31,6 +38,7 @@ pub type MDB_dbi = c_uint;
 pub type MDB_rel_func = extern fn(*const MDB_val, *const c_void, *const c_void, *const c_void);
 pub type MDB_msg_func = extern fn(*const c_char, *const c_void) -> c_int;
 pub type MDB_cmp_func = extern fn(*const MDB_val, *const MDB_val) -> c_int;
+pub type MDB_assert_func = extern fn(*mut MDB_env, *const c_char);
 
 #[derive(Copy, Clone)]
 #[repr(C)]
@@ -39,14 +47,25 @@ pub struct MDB_val {
     pub mv_data: *const c_void,
 }
 
-#[allow(missing_copy_implementations)]
-pub enum MDB_env {}
+// Opaque handles, only used behind pointers. Zero-sized field keeps
+// them unconstructible outside, marker removes Send, Sync and Unpin.
+#[repr(C)]
+pub struct MDB_env {
+    _private: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
 
-#[allow(missing_copy_implementations)]
-pub enum MDB_txn {}
+#[repr(C)]
+pub struct MDB_txn {
+    _private: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
 
-#[allow(missing_copy_implementations)]
-pub enum MDB_cursor {}
+#[repr(C)]
+pub struct MDB_cursor {
+    _private: [u8; 0],
+    _marker: PhantomData<(*mut u8, PhantomPinned)>,
+}
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -90,9 +109,16 @@ pub enum MDB_cursor_op {
     MDB_PREV_NODUP,
     MDB_SET,
     MDB_SET_KEY,
-    MDB_SET_RANGE
+    MDB_SET_RANGE,
+    // since LMDB 0.9.19
+    MDB_PREV_MULTIPLE
 }
 
+// Version of vendored lmdb.h, use `mdb_version` for the linked one
+pub const MDB_VERSION_MAJOR: c_int = 0;
+pub const MDB_VERSION_MINOR: c_int = 9;
+pub const MDB_VERSION_PATCH: c_int = 21;
+
 // Return codes
 pub const MDB_SUCCESS: c_int = 0;
 pub const MDB_KEYEXIST: c_int = -30799;
@@ -114,6 +140,10 @@ pub const MDB_INCOMPATIBLE: c_int = -30784;
 pub const MDB_BAD_RSLOT: c_int = -30783;
 pub const MDB_BAD_TXN: c_int = -30782;
 pub const MDB_BAD_VALSIZE: c_int = -30781;
+pub const MDB_BAD_DBI: c_int = -30780;
+// not defined by the vendored header, newer LMDB may return it
+pub const MDB_PROBLEM: c_int = -30779;
+pub const MDB_LAST_ERRCODE: c_int = MDB_PROBLEM;
 
 // Write flags
 pub const MDB_NOOVERWRITE: c_uint = 0x10;
@@ -146,29 +176,38 @@ pub const MDB_NOLOCK: c_uint =  0x400000;
 pub const MDB_NORDAHEAD: c_uint = 0x800000;
 pub const MDB_NOMEMINIT: c_uint =  0x1000000;
 
+// Copy flags
+pub const MDB_CP_COMPACT: c_uint = 0x01;
+
 // Embedding should work better for now
 extern "C" {
-    pub fn mdb_version(major: *mut c_int, minor: *mut c_int, patch: *mut c_int) -> *const c_char;
-    pub fn mdb_strerror(err: c_int) -> *const c_char;
+    pub fn mdb_version(major: *mut c_int, minor: *mut c_int, patch: *mut c_int) -> *mut c_char;
+    pub fn mdb_strerror(err: c_int) -> *mut c_char;
     pub fn mdb_env_create(env: *mut *mut MDB_env) -> c_int;
     pub fn mdb_env_open(env: *mut MDB_env, path: *const c_char, flags: c_uint, mode: mdb_mode_t) -> c_int;
     pub fn mdb_env_copy(env: *mut MDB_env, path: *const c_char) -> c_int;
     pub fn mdb_env_copyfd(env: *mut MDB_env, fd: mdb_filehandle_t) -> c_int;
+    pub fn mdb_env_copy2(env: *mut MDB_env, path: *const c_char, flags: c_uint) -> c_int;
+    pub fn mdb_env_copyfd2(env: *mut MDB_env, fd: mdb_filehandle_t, flags: c_uint) -> c_int;
     pub fn mdb_env_stat(env: *mut MDB_env, stat: *mut MDB_stat) -> c_int;
     pub fn mdb_env_info(env: *mut MDB_env, info: *mut MDB_envinfo) -> c_int;
     pub fn mdb_env_sync(env: *mut MDB_env, force: c_int) -> c_int;
     pub fn mdb_env_close(env: *mut MDB_env);
     pub fn mdb_env_set_flags(env: *mut MDB_env, flags: c_uint, onoff: c_int) -> c_int;
     pub fn mdb_env_get_flags(env: *mut MDB_env, flags: *mut c_uint) -> c_int;
-    pub fn mdb_env_get_path(env: *mut MDB_env, path: *mut *mut c_char) -> c_int;
+    pub fn mdb_env_get_path(env: *mut MDB_env, path: *mut *const c_char) -> c_int;
     pub fn mdb_env_get_fd(env: *mut MDB_env, fd: *mut mdb_filehandle_t) -> c_int;
     pub fn mdb_env_set_mapsize(env: *mut MDB_env, size: size_t) -> c_int;
     pub fn mdb_env_set_maxreaders(env: *mut MDB_env, readers: c_uint) -> c_int;
     pub fn mdb_env_get_maxreaders(env: *mut MDB_env, readers: *mut c_uint) -> c_int;
     pub fn mdb_env_set_maxdbs(env: *mut MDB_env, dbs: MDB_dbi) -> c_int;
     pub fn mdb_env_get_maxkeysize(env: *mut MDB_env) -> c_int;
+    pub fn mdb_env_set_userctx(env: *mut MDB_env, ctx: *mut c_void) -> c_int;
+    pub fn mdb_env_get_userctx(env: *mut MDB_env) -> *mut c_void;
+    pub fn mdb_env_set_assert(env: *mut MDB_env, func: MDB_assert_func) -> c_int;
     pub fn mdb_txn_begin(env: *mut MDB_env, parent: *mut MDB_txn, flags: c_uint, txn: *mut *mut MDB_txn) -> c_int;
     pub fn mdb_txn_env(txn: *mut MDB_txn) -> *mut MDB_env;
+    pub fn mdb_txn_id(txn: *mut MDB_txn) -> size_t;
     pub fn mdb_txn_commit(txn: *mut MDB_txn) -> c_int;
     pub fn mdb_txn_abort(txn: *mut MDB_txn);
     pub fn mdb_txn_reset(txn: *mut MDB_txn);
@@ -181,22 +220,22 @@ extern "C" {
     pub fn mdb_set_compare(txn: *mut MDB_txn, dbi: MDB_dbi, cmp: MDB_cmp_func) -> c_int;
     pub fn mdb_set_dupsort(txn: *mut MDB_txn, dbi: MDB_dbi, cmp: MDB_cmp_func) -> c_int;
     pub fn mdb_set_relfunc(txn: *mut MDB_txn, dbi: MDB_dbi, rel: MDB_rel_func) -> c_int;
-    pub fn mdb_set_relctx(txn: *mut MDB_txn, dbi: MDB_dbi, ctx: *const c_void) -> c_int;
+    pub fn mdb_set_relctx(txn: *mut MDB_txn, dbi: MDB_dbi, ctx: *mut c_void) -> c_int;
     pub fn mdb_get(txn: *mut MDB_txn, dbi: MDB_dbi, key: *mut MDB_val, data: *mut MDB_val) -> c_int;
     pub fn mdb_put(txn: *mut MDB_txn, dbi: MDB_dbi, key: *mut MDB_val, data: *mut MDB_val, flags: c_uint) -> c_int;
     pub fn mdb_del(txn: *mut MDB_txn, dbi: MDB_dbi, key: *mut MDB_val, data: *mut MDB_val) -> c_int;
     pub fn mdb_cursor_open(txn: *mut MDB_txn, dbi: MDB_dbi, cursor: *mut *mut MDB_cursor) -> c_int;
-    pub fn mdb_cursor_close(cursor: *mut MDB_cursor) -> c_int;
+    pub fn mdb_cursor_close(cursor: *mut MDB_cursor);
     pub fn mdb_cursor_renew(txn: *mut MDB_txn, cursor: *mut MDB_cursor) -> c_int;
     pub fn mdb_cursor_txn(cursor: *mut MDB_cursor) -> *mut MDB_txn;
-    pub fn mdb_cursor_dbi(cursor: *mut MDB_cursor) -> *mut MDB_dbi;
+    pub fn mdb_cursor_dbi(cursor: *mut MDB_cursor) -> MDB_dbi;
     pub fn mdb_cursor_get(cursor: *mut MDB_cursor, key: *mut MDB_val, data: *mut MDB_val, op: MDB_cursor_op) -> c_int;
     pub fn mdb_cursor_put(cursor: *mut MDB_cursor, key: *mut MDB_val, data: *mut MDB_val, flags: c_uint) -> c_int;
     pub fn mdb_cursor_del(cursor: *mut MDB_cursor, flags: c_uint) -> c_int;
     pub fn mdb_cursor_count(cursor: *mut MDB_cursor, countp: *mut size_t) -> c_int;
-    pub fn mdb_cmp(txn: *mut MDB_txn, dbi: MDB_dbi, a: *mut MDB_val, b: *mut MDB_val) -> c_int;
-    pub fn mdb_dcmp(txn: *mut MDB_txn, dbi: MDB_dbi, a: *mut MDB_val, b: *mut MDB_val) -> c_int;
-    pub fn mdb_reader_list(env: *mut MDB_env, func: MDB_msg_func, ctx: *const c_void) -> c_int;
+    pub fn mdb_cmp(txn: *mut MDB_txn, dbi: MDB_dbi, a: *const MDB_val, b: *const MDB_val) -> c_int;
+    pub fn mdb_dcmp(txn: *mut MDB_txn, dbi: MDB_dbi, a: *const MDB_val, b: *const MDB_val) -> c_int;
+    pub fn mdb_reader_list(env: *mut MDB_env, func: MDB_msg_func, ctx: *mut c_void) -> c_int;
     pub fn mdb_reader_check(env: *mut MDB_env, dead: *mut c_int) -> c_int;
 }
 
@@ -209,3 +248,177 @@ impl std::fmt::Debug for MDB_val {
         }
     }
 }
+
+/// Bindings generated by bindgen from lmdb.h, either pre-generated
+/// or at build time with `bindgen` feature. Used to keep hand-written
+/// declarations above in sync with the header.
+#[allow(non_upper_case_globals, unknown_lints, clashing_extern_declarations, unused_imports)]
+mod generated {
+    pub use super::{mdb_mode_t, mdb_filehandle_t};
+
+    #[cfg(feature = "bindgen")]
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+    #[cfg(not(feature = "bindgen"))]
+    include!("bindings.rs");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    use super::generated;
+
+    macro_rules! assert_same_consts {
+        ($($name:ident),*) => ({
+            $(assert_eq!(super::$name as i64, generated::$name as i64, stringify!($name));)*
+        })
+    }
+
+    // Signature is spelled once and checked against both declarations,
+    // type names are resolved in scope of each module
+    macro_rules! assert_same_fns {
+        ($($name:ident: ($($arg:ty),*) $(-> $ret:ty)*;)*) => ({
+            $({
+                #[allow(unused_imports)]
+                use super::*;
+                let _: unsafe extern "C" fn($($arg),*) $(-> $ret)* = super::$name;
+            }
+            {
+                #[allow(unused_imports)]
+                use generated::*;
+                let _: unsafe extern "C" fn($($arg),*) $(-> $ret)* = generated::$name;
+            })*
+        })
+    }
+
+    macro_rules! assert_same_layout {
+        ($($name:ident),*) => ({
+            $(
+                assert_eq!(mem::size_of::<super::$name>(), mem::size_of::<generated::$name>(), stringify!($name));
+                assert_eq!(mem::align_of::<super::$name>(), mem::align_of::<generated::$name>(), stringify!($name));
+            )*
+        })
+    }
+
+    #[test]
+    fn test_constants_match_header() {
+        assert_same_consts!(MDB_VERSION_MAJOR, MDB_VERSION_MINOR, MDB_VERSION_PATCH);
+
+        assert_same_consts!(MDB_SUCCESS, MDB_KEYEXIST, MDB_NOTFOUND, MDB_PAGE_NOTFOUND,
+                            MDB_CORRUPTED, MDB_PANIC, MDB_VERSION_MISMATCH, MDB_INVALID,
+                            MDB_MAP_FULL, MDB_DBS_FULL, MDB_READERS_FULL, MDB_TLS_FULL,
+                            MDB_TXN_FULL, MDB_CURSOR_FULL, MDB_PAGE_FULL, MDB_MAP_RESIZED,
+                            MDB_INCOMPATIBLE, MDB_BAD_RSLOT, MDB_BAD_TXN, MDB_BAD_VALSIZE,
+                            MDB_BAD_DBI);
+
+        assert_same_consts!(MDB_NOOVERWRITE, MDB_NODUPDATA, MDB_CURRENT, MDB_RESERVE,
+                            MDB_APPEND, MDB_APPENDDUP, MDB_MULTIPLE);
+
+        assert_same_consts!(MDB_REVERSEKEY, MDB_DUPSORT, MDB_INTEGERKEY, MDB_DUPFIXED,
+                            MDB_INTEGERDUP, MDB_REVERSEDUP, MDB_CREATE);
+
+        assert_same_consts!(MDB_FIXEDMAP, MDB_NOSUBDIR, MDB_NOSYNC, MDB_RDONLY,
+                            MDB_NOMETASYNC, MDB_WRITEMAP, MDB_MAPASYNC, MDB_NOTLS,
+                            MDB_NOLOCK, MDB_NORDAHEAD, MDB_NOMEMINIT, MDB_CP_COMPACT);
+    }
+
+    #[test]
+    fn test_cursor_ops_match_header() {
+        use super::MDB_cursor_op::*;
+
+        let ops = [(MDB_FIRST, generated::MDB_FIRST),
+                   (MDB_FIRST_DUP, generated::MDB_FIRST_DUP),
+                   (MDB_GET_BOTH, generated::MDB_GET_BOTH),
+                   (MDB_GET_BOTH_RANGE, generated::MDB_GET_BOTH_RANGE),
+                   (MDB_GET_CURRENT, generated::MDB_GET_CURRENT),
+                   (MDB_GET_MULTIPLE, generated::MDB_GET_MULTIPLE),
+                   (MDB_LAST, generated::MDB_LAST),
+                   (MDB_LAST_DUP, generated::MDB_LAST_DUP),
+                   (MDB_NEXT, generated::MDB_NEXT),
+                   (MDB_NEXT_DUP, generated::MDB_NEXT_DUP),
+                   (MDB_NEXT_MULTIPLE, generated::MDB_NEXT_MULTIPLE),
+                   (MDB_NEXT_NODUP, generated::MDB_NEXT_NODUP),
+                   (MDB_PREV, generated::MDB_PREV),
+                   (MDB_PREV_DUP, generated::MDB_PREV_DUP),
+                   (MDB_PREV_NODUP, generated::MDB_PREV_NODUP),
+                   (MDB_SET, generated::MDB_SET),
+                   (MDB_SET_KEY, generated::MDB_SET_KEY),
+                   (MDB_SET_RANGE, generated::MDB_SET_RANGE),
+                   (MDB_PREV_MULTIPLE, generated::MDB_PREV_MULTIPLE)];
+
+        for &(op, expected) in ops.iter() {
+            assert_eq!(op as u32, expected as u32);
+        }
+        assert_eq!(mem::size_of::<super::MDB_cursor_op>(), mem::size_of::<generated::MDB_cursor_op>());
+    }
+
+    #[test]
+    fn test_structs_match_header() {
+        assert_same_layout!(MDB_val, MDB_stat, MDB_envinfo);
+        assert_eq!(mem::size_of::<super::MDB_dbi>(), mem::size_of::<generated::MDB_dbi>());
+    }
+
+    #[test]
+    fn test_functions_match_header() {
+        use libc::{c_char, c_int, c_uint, c_void};
+
+        assert_same_fns! {
+            mdb_version: (*mut c_int, *mut c_int, *mut c_int) -> *mut c_char;
+            mdb_strerror: (c_int) -> *mut c_char;
+            mdb_env_create: (*mut *mut MDB_env) -> c_int;
+            mdb_env_open: (*mut MDB_env, *const c_char, c_uint, mdb_mode_t) -> c_int;
+            mdb_env_copy: (*mut MDB_env, *const c_char) -> c_int;
+            mdb_env_copyfd: (*mut MDB_env, mdb_filehandle_t) -> c_int;
+            mdb_env_copy2: (*mut MDB_env, *const c_char, c_uint) -> c_int;
+            mdb_env_copyfd2: (*mut MDB_env, mdb_filehandle_t, c_uint) -> c_int;
+            mdb_env_stat: (*mut MDB_env, *mut MDB_stat) -> c_int;
+            mdb_env_info: (*mut MDB_env, *mut MDB_envinfo) -> c_int;
+            mdb_env_sync: (*mut MDB_env, c_int) -> c_int;
+            mdb_env_close: (*mut MDB_env);
+            mdb_env_set_flags: (*mut MDB_env, c_uint, c_int) -> c_int;
+            mdb_env_get_flags: (*mut MDB_env, *mut c_uint) -> c_int;
+            mdb_env_get_path: (*mut MDB_env, *mut *const c_char) -> c_int;
+            mdb_env_get_fd: (*mut MDB_env, *mut mdb_filehandle_t) -> c_int;
+            mdb_env_set_mapsize: (*mut MDB_env, usize) -> c_int;
+            mdb_env_set_maxreaders: (*mut MDB_env, c_uint) -> c_int;
+            mdb_env_get_maxreaders: (*mut MDB_env, *mut c_uint) -> c_int;
+            mdb_env_set_maxdbs: (*mut MDB_env, MDB_dbi) -> c_int;
+            mdb_env_get_maxkeysize: (*mut MDB_env) -> c_int;
+            mdb_env_set_userctx: (*mut MDB_env, *mut c_void) -> c_int;
+            mdb_env_get_userctx: (*mut MDB_env) -> *mut c_void;
+            mdb_env_set_assert: (*mut MDB_env, MDB_assert_func) -> c_int;
+            mdb_txn_begin: (*mut MDB_env, *mut MDB_txn, c_uint, *mut *mut MDB_txn) -> c_int;
+            mdb_txn_env: (*mut MDB_txn) -> *mut MDB_env;
+            mdb_txn_id: (*mut MDB_txn) -> usize;
+            mdb_txn_commit: (*mut MDB_txn) -> c_int;
+            mdb_txn_abort: (*mut MDB_txn);
+            mdb_txn_reset: (*mut MDB_txn);
+            mdb_txn_renew: (*mut MDB_txn) -> c_int;
+            mdb_dbi_open: (*mut MDB_txn, *const c_char, c_uint, *mut MDB_dbi) -> c_int;
+            mdb_stat: (*mut MDB_txn, MDB_dbi, *mut MDB_stat) -> c_int;
+            mdb_dbi_flags: (*mut MDB_txn, MDB_dbi, *mut c_uint) -> c_int;
+            mdb_dbi_close: (*mut MDB_env, MDB_dbi);
+            mdb_drop: (*mut MDB_txn, MDB_dbi, c_int) -> c_int;
+            mdb_set_compare: (*mut MDB_txn, MDB_dbi, MDB_cmp_func) -> c_int;
+            mdb_set_dupsort: (*mut MDB_txn, MDB_dbi, MDB_cmp_func) -> c_int;
+            mdb_set_relfunc: (*mut MDB_txn, MDB_dbi, MDB_rel_func) -> c_int;
+            mdb_set_relctx: (*mut MDB_txn, MDB_dbi, *mut c_void) -> c_int;
+            mdb_get: (*mut MDB_txn, MDB_dbi, *mut MDB_val, *mut MDB_val) -> c_int;
+            mdb_put: (*mut MDB_txn, MDB_dbi, *mut MDB_val, *mut MDB_val, c_uint) -> c_int;
+            mdb_del: (*mut MDB_txn, MDB_dbi, *mut MDB_val, *mut MDB_val) -> c_int;
+            mdb_cursor_open: (*mut MDB_txn, MDB_dbi, *mut *mut MDB_cursor) -> c_int;
+            mdb_cursor_close: (*mut MDB_cursor);
+            mdb_cursor_renew: (*mut MDB_txn, *mut MDB_cursor) -> c_int;
+            mdb_cursor_txn: (*mut MDB_cursor) -> *mut MDB_txn;
+            mdb_cursor_dbi: (*mut MDB_cursor) -> MDB_dbi;
+            mdb_cursor_get: (*mut MDB_cursor, *mut MDB_val, *mut MDB_val, MDB_cursor_op) -> c_int;
+            mdb_cursor_put: (*mut MDB_cursor, *mut MDB_val, *mut MDB_val, c_uint) -> c_int;
+            mdb_cursor_del: (*mut MDB_cursor, c_uint) -> c_int;
+            mdb_cursor_count: (*mut MDB_cursor, *mut usize) -> c_int;
+            mdb_cmp: (*mut MDB_txn, MDB_dbi, *const MDB_val, *const MDB_val) -> c_int;
+            mdb_dcmp: (*mut MDB_txn, MDB_dbi, *const MDB_val, *const MDB_val) -> c_int;
+            mdb_reader_list: (*mut MDB_env, MDB_msg_func, *mut c_void) -> c_int;
+            mdb_reader_check: (*mut MDB_env, *mut c_int) -> c_int;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Environment configuration loadable with serde
+//!
+//! Allows keeping environment settings in a config file, for example
+//! in TOML:
+//!
+//! ```toml
+//! path = "/var/lib/app/db"
+//! map_size = 1073741824
+//! max_dbs = 8
+//! flags = ["NOSYNC", "NOTLS"]
+//! ```
+//!
+//! Flag names are the LMDB ones without `MDB_` prefix, matched case
+//! insensitively, the same as accepted by `EnvCreateFlags::from_str`.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use core::{EnvBuilder, EnvCreateFlags, Environment, MdbResult};
+use core::{EnvCreateNoSubDir, EnvCreateReadOnly};
+
+fn default_mode() -> u32 {
+    0o644
+}
+
+fn default_autocreate_dir() -> bool {
+    true
+}
+
+/// Deserializable environment settings
+///
+/// Unset optional values fall back to `EnvBuilder` defaults.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EnvConfig {
+    /// Path to environment directory (or file if `no_sub_dir` is set)
+    pub path: PathBuf,
+    /// Permissions of created files, defaults to `0o644`
+    #[serde(default = "default_mode")]
+    pub mode: u32,
+    pub map_size: Option<u64>,
+    pub max_dbs: Option<usize>,
+    pub max_readers: Option<usize>,
+    /// Names of `EnvCreateFlags`, e.g. `"NOSYNC"`
+    #[serde(default)]
+    pub flags: Vec<String>,
+    /// Shortcut for `RDONLY` flag
+    #[serde(default)]
+    pub read_only: bool,
+    /// Shortcut for `NOSUBDIR` flag
+    #[serde(default)]
+    pub no_sub_dir: bool,
+    /// Create missing directories, defaults to `true`
+    #[serde(default = "default_autocreate_dir")]
+    pub autocreate_dir: bool,
+}
+
+impl EnvConfig {
+    /// Parses configured flag names into `EnvCreateFlags`
+    pub fn env_flags(&self) -> MdbResult<EnvCreateFlags> {
+        let mut flags = EnvCreateFlags::empty();
+        for name in &self.flags {
+            flags.insert(try!(EnvCreateFlags::from_str(name)));
+        }
+        if self.read_only {
+            flags.insert(EnvCreateReadOnly);
+        }
+        if self.no_sub_dir {
+            flags.insert(EnvCreateNoSubDir);
+        }
+        Ok(flags)
+    }
+
+    /// Opens environment described by config
+    ///
+    /// Fails with `StateError` on unknown flag name before touching
+    /// the file system.
+    pub fn open(&self) -> MdbResult<Environment> {
+        let mut builder = EnvBuilder::new()
+            .flags(try!(self.env_flags()))
+            .autocreate_dir(self.autocreate_dir);
+        if let Some(map_size) = self.map_size {
+            builder = builder.map_size(map_size);
+        }
+        if let Some(max_dbs) = self.max_dbs {
+            builder = builder.max_dbs(max_dbs);
+        }
+        if let Some(max_readers) = self.max_readers {
+            builder = builder.max_readers(max_readers);
+        }
+        builder.open(&self.path, self.mode)
+    }
+}
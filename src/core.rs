@@ -40,24 +40,33 @@
 
 #![allow(non_upper_case_globals)]
 
-use libc::{c_int, c_uint, size_t, c_void};
+use libc::{c_char, c_int, c_uint, size_t, c_void};
 use std;
 use std::borrow::ToOwned;
-use std::cell::{UnsafeCell};
-use std::cmp::{Ordering};
-use std::collections::HashMap;
+use std::cell::{Cell, UnsafeCell};
+use std::convert::TryFrom;
+use std::cmp::{self, Ordering};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use std::ffi::{CString};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io::{self, ErrorKind};
+use std::hash::{Hash, Hasher};
 use std::path::Path;
 use std::mem;
+use std::ops::{ControlFlow, Deref, DerefMut};
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
+use std::str::FromStr;
+use std::str;
 use std::result::Result;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 use ffi::{self, MDB_val};
-pub use MdbError::{NotFound, KeyExists, Other, StateError, Corrupted, Panic};
-pub use MdbError::{InvalidPath, TxnFull, CursorFull, PageFull, CacheError};
-use traits::{ToMdbValue, FromMdbValue};
+pub use MdbError::{NotFound, KeyExists, Other, StateError, Corrupted, Panic, Problem};
+pub use MdbError::{InvalidPath, TxnFull, CursorFull, PageFull, CacheError, BadValSize};
+use traits::{ToMdbValue, FromMdbValue, FromMdbValueOwned};
 use utils::{error_msg};
 
 
@@ -110,8 +119,18 @@ macro_rules! assert_state_not {
             }})
 }
 
+/// Returns `(major, minor, patch)` version of linked LMDB library,
+/// which may differ from the vendored one when linking system liblmdb
+pub fn version() -> (i32, i32, i32) {
+    let (mut major, mut minor, mut patch) = (0, 0, 0);
+    unsafe {
+        ffi::mdb_version(&mut major, &mut minor, &mut patch);
+    }
+    (major, minor, patch)
+}
+
 /// MdbError wraps information about LMDB error
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum MdbError {
     NotFound,
     KeyExists,
@@ -120,12 +139,24 @@ pub enum MdbError {
     PageFull,
     Corrupted,
     Panic,
+    /// Unexpected problem, transaction should be aborted (MDB_PROBLEM)
+    Problem,
     InvalidPath,
     StateError(String),
     CacheError,
+    /// Key or value size is out of range accepted by LMDB, reported
+    /// before calling into LMDB (which would fail with MDB_BAD_VALSIZE)
+    BadValSize { what: KeyOrValue, size: usize, max: usize },
     Other(c_int, String)
 }
 
+/// Argument which caused `BadValSize`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyOrValue {
+    Key,
+    Value,
+}
+
 
 impl MdbError {
     pub fn new_with_code(code: c_int) -> MdbError {
@@ -137,46 +168,161 @@ impl MdbError {
             ffi::MDB_PAGE_FULL   => PageFull,
             ffi::MDB_CORRUPTED   => Corrupted,
             ffi::MDB_PANIC       => Panic,
+            ffi::MDB_PROBLEM     => Problem,
             _                    => Other(code, error_msg(code))
         }
     }
+
+    /// Returns true if key or item isn't found, also recognizes
+    /// errors decorated by `with_context`
+    pub fn is_not_found(&self) -> bool {
+        self.is_code(NotFound, ffi::MDB_NOTFOUND)
+    }
+
+    /// Returns true if key or item already exists, also recognizes
+    /// errors decorated by `with_context`
+    pub fn is_key_exists(&self) -> bool {
+        self.is_code(KeyExists, ffi::MDB_KEYEXIST)
+    }
+
+    /// Returns true if environment map size is reached
+    pub fn is_map_full(&self) -> bool {
+        match *self {
+            Other(code, _) => code == ffi::MDB_MAP_FULL,
+            _ => false,
+        }
+    }
+
+    fn is_code(&self, variant: MdbError, code: c_int) -> bool {
+        match *self {
+            Other(other, _) => other == code,
+            ref e => *e == variant,
+        }
+    }
+
+    /// Decorates error with context, e.g. operation or database name.
+    ///
+    /// The result is always `Other` with message `"{ctx}: {original}"`,
+    /// keeping LMDB error code when there is one (0 otherwise). Note
+    /// that matching on specific variants like `NotFound` won't work
+    /// on decorated errors.
+    pub fn with_context<S: Into<String>>(self, ctx: S) -> MdbError {
+        let code = match self {
+            NotFound => ffi::MDB_NOTFOUND,
+            KeyExists => ffi::MDB_KEYEXIST,
+            TxnFull => ffi::MDB_TXN_FULL,
+            CursorFull => ffi::MDB_CURSOR_FULL,
+            PageFull => ffi::MDB_PAGE_FULL,
+            Corrupted => ffi::MDB_CORRUPTED,
+            Panic => ffi::MDB_PANIC,
+            Problem => ffi::MDB_PROBLEM,
+            Other(code, _) => code,
+            BadValSize { .. } => ffi::MDB_BAD_VALSIZE,
+            InvalidPath | StateError(_) | CacheError => 0,
+        };
+        let msg = match self {
+            Other(_, msg) => msg,
+            e => e.to_string(),
+        };
+        Other(code, format!("{}: {}", ctx.into(), msg))
+    }
 }
 
 
 impl std::fmt::Display for MdbError {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            &NotFound | &KeyExists | &TxnFull |
-            &CursorFull | &PageFull | &Corrupted |
-            &Panic | &InvalidPath | &CacheError => write!(fmt, "{}", self.description()),
+            &NotFound => write!(fmt, "not found"),
+            &KeyExists => write!(fmt, "key exists"),
+            &TxnFull => write!(fmt, "txn full"),
+            &CursorFull => write!(fmt, "cursor full"),
+            &PageFull => write!(fmt, "page full"),
+            &Corrupted => write!(fmt, "corrupted"),
+            &Panic => write!(fmt, "panic"),
+            &Problem => write!(fmt, "unexpected problem, txn should abort"),
+            &InvalidPath => write!(fmt, "invalid path for database"),
+            &CacheError => write!(fmt, "db cache error"),
             &StateError(ref msg) => write!(fmt, "{}", msg),
+            &BadValSize { what: KeyOrValue::Key, size, max } =>
+                write!(fmt, "bad key size {}, expected 1..={}", size, max),
+            &BadValSize { what: KeyOrValue::Value, size, max } =>
+                write!(fmt, "bad value size {}, expected at most {}", size, max),
             &Other(code, ref msg) => write!(fmt, "{}: {}", code, msg)
         }
     }
 }
 
-impl Error for MdbError {
-    fn description(&self) -> &'static str {
-        match self {
-            &NotFound => "not found",
-            &KeyExists => "key exists",
-            &TxnFull => "txn full",
-            &CursorFull => "cursor full",
-            &PageFull => "page full",
-            &Corrupted => "corrupted",
-            &Panic => "panic",
-            &InvalidPath => "invalid path for database",
-            &StateError(_) => "state error",
-            &CacheError => "db cache error",
-            &Other(_, _) => "other error",
+// no variant wraps another error, so default `source` is enough
+impl Error for MdbError {}
+
+/// Maps error to the closest I/O error kind, `Other` errors with
+/// system error codes are mapped the same way `io::Error` does
+impl From<MdbError> for ErrorKind {
+    fn from(e: MdbError) -> ErrorKind {
+        match e {
+            NotFound => ErrorKind::NotFound,
+            KeyExists => ErrorKind::AlreadyExists,
+            Corrupted => ErrorKind::InvalidData,
+            InvalidPath | BadValSize { .. } => ErrorKind::InvalidInput,
+            Other(code, _) if code > 0 => ::std::io::Error::from_raw_os_error(code).kind(),
+            TxnFull | CursorFull | PageFull | Panic | Problem |
+            StateError(_) | CacheError | Other(_, _) => ErrorKind::Other,
         }
     }
 }
 
+/// Converts to I/O error of kind `ErrorKind::from(e)`, original
+/// error is kept as its inner error
+impl From<MdbError> for io::Error {
+    fn from(e: MdbError) -> io::Error {
+        io::Error::new(ErrorKind::from(e.clone()), e)
+    }
+}
+
+/// Maps I/O error kind to error, kinds without LMDB counterpart
+/// become `Other` with corresponding system error code (if any)
+impl From<ErrorKind> for MdbError {
+    fn from(kind: ErrorKind) -> MdbError {
+        let code = match kind {
+            ErrorKind::NotFound => return NotFound,
+            ErrorKind::AlreadyExists => return KeyExists,
+            ErrorKind::InvalidData => return Corrupted,
+            ErrorKind::PermissionDenied => ::libc::EACCES,
+            ErrorKind::InvalidInput => ::libc::EINVAL,
+            ErrorKind::WouldBlock => ::libc::EAGAIN,
+            ErrorKind::Interrupted => ::libc::EINTR,
+            ErrorKind::OutOfMemory => ::libc::ENOMEM,
+            _ => 0,
+        };
+        if code == 0 {
+            Other(0, kind.to_string())
+        } else {
+            Other(code, error_msg(code))
+        }
+    }
+}
 
 pub type MdbResult<T> = Result<T, MdbError>;
 
-bitflags! {
+/// Declares flags type in bitflags 0.7 syntax: flags are available
+/// both as associated and as module level constants
+macro_rules! lmdb_flags {
+    ($(#[$attr:meta])* pub flags $t:ident: $ty:ty {
+        $($(#[$flag_attr:meta])* const $flag:ident = $value:expr),+ $(,)*
+    }) => (
+        bitflags! {
+            $(#[$attr])*
+            #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $t: $ty {
+                $(#[allow(non_upper_case_globals)] const $flag = $value;)+
+            }
+        }
+
+        $($(#[$flag_attr])* pub const $flag: $t = $t::$flag;)+
+    )
+}
+
+lmdb_flags! {
     #[doc = "A set of environment flags which could be changed after opening"]
 
     pub flags EnvFlags: c_uint {
@@ -241,7 +387,7 @@ bitflags! {
     }
 }
 
-bitflags! {
+lmdb_flags! {
     #[doc = "A set of all environment flags"]
 
     pub flags EnvCreateFlags: c_uint {
@@ -347,7 +493,7 @@ bitflags! {
     }
 }
 
-bitflags! {
+lmdb_flags! {
     #[doc = "A set of database flags"]
 
     pub flags DbFlags: c_uint {
@@ -385,19 +531,247 @@ bitflags! {
     }
 }
 
+lmdb_flags! {
+    #[doc = "A set of environment copy flags"]
+
+    pub flags EnvCopyFlags: c_uint {
+        #[doc="Perform compaction while copying: omit free pages and"]
+        #[doc="sequentially renumber all pages in output. This option"]
+        #[doc="consumes more CPU and runs more slowly than the default."]
+        const EnvCopyCompact = ffi::MDB_CP_COMPACT,
+    }
+}
+
+macro_rules! flags_raw_conversions {
+    ($t:ident) => (
+        impl From<$t> for u32 {
+            fn from(flags: $t) -> u32 {
+                flags.bits()
+            }
+        }
+
+        impl TryFrom<u32> for $t {
+            type Error = ();
+
+            /// Fails if there are unknown bits set
+            fn try_from(bits: u32) -> Result<$t, ()> {
+                $t::from_bits(bits).ok_or(())
+            }
+        }
+    )
+}
+
+flags_raw_conversions!(EnvFlags);
+flags_raw_conversions!(EnvCreateFlags);
+flags_raw_conversions!(DbFlags);
+flags_raw_conversions!(EnvCopyFlags);
+
+macro_rules! flags_default {
+    ($t:ident) => (
+        /// No flags set, same as `empty()`
+        impl Default for $t {
+            fn default() -> $t {
+                $t::empty()
+            }
+        }
+    )
+}
+
+flags_default!(EnvFlags);
+flags_default!(EnvCreateFlags);
+flags_default!(DbFlags);
+flags_default!(EnvCopyFlags);
+
+macro_rules! flags_names {
+    ($t:ident { $($flag:ident => $name:expr),+ }) => (
+        impl $t {
+            /// Flags with their canonical (LMDB) names
+            fn names() -> &'static [($t, &'static str)] {
+                &[$(($flag, $name)),+]
+            }
+        }
+
+        /// Formats flags as pipe-separated LMDB names, e.g. `DUPSORT|INTEGERKEY`
+        impl fmt::Display for $t {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                let mut first = true;
+                for &(flag, name) in $t::names() {
+                    if self.contains(flag) {
+                        if !first {
+                            try!(fmt.write_str("|"));
+                        }
+                        first = false;
+                        try!(fmt.write_str(name));
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        /// Formats flags as LMDB names followed by raw bits, e.g.
+        /// `DbFlags(DUPSORT|INTEGERKEY, 0xc)`
+        impl fmt::Debug for $t {
+            fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+                let names = self.to_string();
+                if names.is_empty() {
+                    write!(fmt, "{}({:#x})", stringify!($t), self.bits())
+                } else {
+                    write!(fmt, "{}({}, {:#x})", stringify!($t), names, self.bits())
+                }
+            }
+        }
+
+        /// Parses pipe-separated LMDB names ignoring case, empty
+        /// string gives empty flags
+        impl FromStr for $t {
+            type Err = MdbError;
+
+            fn from_str(s: &str) -> MdbResult<$t> {
+                let mut res = $t::empty();
+                for part in s.split('|').map(|part| part.trim()) {
+                    if part.is_empty() && s.trim().is_empty() {
+                        continue;
+                    }
+                    match $t::names().iter().find(|&&(_, name)| name.eq_ignore_ascii_case(part)) {
+                        Some(&(flag, _)) => res.insert(flag),
+                        None => return Err(StateError(format!("Unknown {} flag '{}'", stringify!($t), part))),
+                    }
+                }
+                Ok(res)
+            }
+        }
+    )
+}
+
+flags_names!(EnvFlags {
+    EnvNoSync => "NOSYNC",
+    EnvNoMetaSync => "NOMETASYNC",
+    EnvMapAsync => "MAPASYNC",
+    EnvNoMemInit => "NOMEMINIT"
+});
+
+flags_names!(EnvCreateFlags {
+    EnvCreateFixedMap => "FIXEDMAP",
+    EnvCreateNoSubDir => "NOSUBDIR",
+    EnvCreateNoSync => "NOSYNC",
+    EnvCreateReadOnly => "RDONLY",
+    EnvCreateNoMetaSync => "NOMETASYNC",
+    EnvCreateWriteMap => "WRITEMAP",
+    EnvCreataMapAsync => "MAPASYNC",
+    EnvCreateNoTls => "NOTLS",
+    EnvCreateNoLock => "NOLOCK",
+    EnvCreateNoReadAhead => "NORDAHEAD",
+    EnvCreateNoMemInit => "NOMEMINIT"
+});
+
+flags_names!(DbFlags {
+    DbReverseKey => "REVERSEKEY",
+    DbAllowDups => "DUPSORT",
+    DbIntKey => "INTEGERKEY",
+    DbDupFixed => "DUPFIXED",
+    DbAllowIntDups => "INTEGERDUP",
+    DbReversedDups => "REVERSEDUP",
+    DbCreate => "CREATE"
+});
+
+flags_names!(EnvCopyFlags {
+    EnvCopyCompact => "CP_COMPACT"
+});
+
+/// Marks databases and cursors which allow writes
+#[derive(Debug)]
+pub enum ReadWrite {}
+
+/// Marks databases and cursors bound to a read-only transaction
+#[derive(Debug)]
+pub enum ReadOnly {}
+
 /// Database
+///
+/// Read operations are available in any mode, while writes require
+/// `ReadWrite`, i.e. database bound to a read-write transaction.
 #[derive(Debug)]
-pub struct Database<'a> {
+pub struct Database<'a, M = ReadWrite> {
     handle: ffi::MDB_dbi,
+    name: &'static str,
     txn: &'a NativeTransaction<'a>,
+    mode: ::std::marker::PhantomData<M>,
 }
 
-// FIXME: provide different interfaces for read-only/read-write databases
+/// Database bound to a `ReadonlyTransaction`, mutating methods
+/// are unavailable:
+///
+/// ```compile_fail
+/// # use lmdb_rs::core::EnvBuilder;
+/// # let env = EnvBuilder::new().open("unused", 0o777).unwrap();
+/// # let handle = env.get_default_db(lmdb_rs::DbFlags::empty()).unwrap();
+/// let reader = env.get_reader().unwrap();
+/// let db = reader.bind(&handle);
+/// db.set(&"key", &"value").unwrap();
+/// ```
+///
+/// The same holds for its cursors:
+///
+/// ```compile_fail
+/// # use lmdb_rs::core::EnvBuilder;
+/// # let env = EnvBuilder::new().open("unused", 0o777).unwrap();
+/// # let handle = env.get_default_db(lmdb_rs::DbFlags::empty()).unwrap();
+/// let reader = env.get_reader().unwrap();
+/// let db = reader.bind(&handle);
+/// let mut cursor = db.new_cursor().unwrap();
+/// cursor.to_first().unwrap();
+/// cursor.del().unwrap();
+/// ```
+pub type ReadonlyDatabase<'a> = Database<'a, ReadOnly>;
+
 // FIXME: provide different interfaces for simple KV and storage with duplicates
 
-impl<'a> Database<'a> {
-    fn new_with_handle(handle: ffi::MDB_dbi, txn: &'a NativeTransaction<'a>) -> Database<'a> {
-        Database { handle: handle, txn: txn }
+impl<'a, M> Database<'a, M> {
+    fn new_with_handle(handle: ffi::MDB_dbi, name: &'static str, txn: &'a NativeTransaction<'a>) -> Database<'a, M> {
+        Database { handle: handle, name: name, txn: txn, mode: ::std::marker::PhantomData }
+    }
+
+    /// Name database was opened with, empty for the default one.
+    ///
+    /// Names are interned for the whole process so handles stay
+    /// `Copy`: memory of every distinct name ever opened is never
+    /// freed, which matters only if names are generated unboundedly.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Whether database was bound from `handle`
+    pub(crate) fn is_bound_to(&self, handle: &DbHandle) -> bool {
+        self.handle == handle.handle
+    }
+
+    /// Fails with `StateError` if `start > end` in the database ordering
+    fn check_range_bounds(&self, start: &MdbValue, end: &MdbValue) -> MdbResult<()> {
+        let mut start_val = start.value;
+        let mut end_val = end.value;
+        let cmp = unsafe { ffi::mdb_cmp(self.txn.handle, self.handle, &mut start_val, &mut end_val) };
+        if cmp > 0 {
+            Err(StateError(format!("range start is greater than end in ordering of {}", describe_db(self.name))))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Compares keys using key ordering of the database
+    fn cmp_keys<K: ToMdbValue>(&self, a: &K, b: &K) -> Ordering {
+        let (a, b) = (a.to_mdb_value(), b.to_mdb_value());
+        let cmp = unsafe { ffi::mdb_cmp(self.txn.handle, self.handle, &a.value, &b.value) };
+        cmp.cmp(&0)
+    }
+
+    /// Fails with `StateError` unless database was created with all of `flags`
+    fn require_flags(&self, flags: DbFlags, op: &str) -> MdbResult<()> {
+        let actual = DbFlags::from_bits_truncate(try!(self.txn.dbi_flags(self.handle)));
+        if actual.contains(flags) {
+            Ok(())
+        } else {
+            Err(StateError(format!("{} requires {} flags on {}", op, flags, describe_db(self.name))))
+        }
     }
 
     /// Retrieves current db's statistics.
@@ -405,58 +779,175 @@ impl<'a> Database<'a> {
         self.txn.stat(self.handle)
     }
 
+    /// Returns count of items (key/value pairs) in database, with
+    /// DbAllowDups each duplicate is counted, see `key_count` for
+    /// distinct keys
+    pub fn len(&'a self) -> MdbResult<usize> {
+        self.stat().map(|stat| stat.ms_entries as usize)
+    }
+
+    /// Same as `len`
+    pub fn count(&'a self) -> MdbResult<usize> {
+        self.len()
+    }
+
+    /// Returns true if database has no items
+    pub fn is_empty(&'a self) -> MdbResult<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Returns count of distinct keys. Walks all keys, so it takes
+    /// time proportional to their count, unlike `len`.
+    pub fn key_count(&'a self) -> MdbResult<usize> {
+        let mut cursor = try!(self.new_cursor());
+        let mut count = 0;
+        let mut res = cursor.to_first();
+        loop {
+            match res {
+                Ok(_) => count += 1,
+                Err(NotFound) => return Ok(count),
+                Err(e) => return Err(e),
+            }
+            res = cursor.to_next_key();
+        }
+    }
+
+    /// Returns count of items of `key`, fails with `NotFound` if there
+    /// is no such key. Should be used only with DbAllowDups.
+    pub fn count_dups<K: ToMdbValue>(&'a self, key: &K) -> MdbResult<usize> {
+        try!(self.require_flags(DbAllowDups, "count_dups"));
+        let mut cursor = try!(self.new_cursor());
+        try!(cursor.to_key(key));
+        cursor.count_items()
+    }
+
+    /// Returns fraction of environment pages used by this database,
+    /// i.e. its branch, leaf and overflow pages divided by count of
+    /// pages allocated in the environment file.
+    ///
+    /// The value is approximate: environment info reflects the last
+    /// committed transaction and free pages aren't accounted for,
+    /// but it's good enough to decide whether compaction makes sense.
+    pub fn page_utilization(&'a self) -> MdbResult<f64> {
+        let stat = try!(self.stat());
+        let info = try!(self.txn.env.info());
+        let db_pages = stat.ms_branch_pages + stat.ms_leaf_pages + stat.ms_overflow_pages;
+        let total_pages = info.me_last_pgno + 1;
+        Ok(db_pages as f64 / total_pages as f64)
+    }
+
     /// Retrieves a value by key. In case of DbAllowDups it will be the first value
     pub fn get<V: FromMdbValue + 'a>(&'a self, key: &ToMdbValue) -> MdbResult<V> {
         self.txn.get(self.handle, key)
     }
 
-    /// Sets value for key. In case of DbAllowDups it will add a new item
-    pub fn set(&self, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<()> {
-        self.txn.set(self.handle, key, value)
+    /// Returns true if key exists, value isn't decoded
+    pub fn exists(&self, key: &ToMdbValue) -> MdbResult<bool> {
+        assert_state_eq!(txn, self.txn.state, TransactionState::Normal);
+        let mut key_val = key.to_mdb_value();
+        let mut data_val: ffi::MDB_val = unsafe { std::mem::zeroed() };
+        match unsafe { ffi::mdb_get(self.txn.handle, self.handle, &mut key_val.value, &mut data_val) } {
+            ffi::MDB_SUCCESS => Ok(true),
+            ffi::MDB_NOTFOUND => Ok(false),
+            code => Err(MdbError::new_with_code(code)),
+        }
     }
 
-    /// Appends new key-value pair to database, starting a new page instead of splitting an
-    /// existing one if necessary. Requires that key be >= all existing keys in the database
-    /// (or will return KeyExists error).
-    pub fn append<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<()> {
-        self.txn.append(self.handle, key, value)
+    /// Returns true if key has exactly this value. Should be used
+    /// only with DbAllowDups.
+    pub fn exists_item(&'a self, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<bool> {
+        try!(self.require_flags(DbAllowDups, "exists_item"));
+        let mut cursor = try!(self.new_cursor());
+        match cursor.to_item(&key.to_mdb_value(), &value.to_mdb_value()) {
+            Ok(_) => Ok(true),
+            Err(NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 
-    /// Appends new value for the given key (requires DbAllowDups), starting a new page instead
-    /// of splitting an existing one if necessary. Requires that value be >= all existing values
-    /// for the given key (or will return KeyExists error).
-    pub fn append_duplicate<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<()> {
-        self.txn.append_duplicate(self.handle, key, value)
+    /// Returns an `io::Read` adapter over value of `key` which reads
+    /// directly from the memory map, nothing is copied upfront
+    pub fn blob_reader(&'a self, key: &ToMdbValue) -> MdbResult<DatabaseBlobReader<'a>> {
+        self.get(key).map(|data| DatabaseBlobReader { data: data, pos: 0 })
     }
 
-    /// Set value for key. Fails if key already exists, even when duplicates are allowed.
-    pub fn insert(&self, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<()> {
-        self.txn.insert(self.handle, key, value)
+    /// Retrieves a value by key as a slice pointing directly into
+    /// the memory map, which isn't bound to this transaction.
+    ///
+    /// # Safety
+    ///
+    /// Once the transaction is finished LMDB may reuse the page for
+    /// other data, so the slice is valid only as long as the caller
+    /// guarantees there are no concurrent writers to environment (e.g.
+    /// it is opened read-only in every process). Slice borrows `env`,
+    /// which keeps the mapping alive, so it must be the environment
+    /// of this transaction (or its clone), `StateError` otherwise. See
+    /// [read_only_mapped_view](struct.Environment.html#method.read_only_mapped_view)
+    /// for a safe wrapper.
+    ///
+    /// ```compile_fail
+    /// # use lmdb_rs::core::{EnvBuilder, EnvCreateReadOnly};
+    /// # let handle = EnvBuilder::new().open("unused", 0o777).unwrap()
+    /// #     .get_default_db(lmdb_rs::DbFlags::empty()).unwrap();
+    /// let env = EnvBuilder::new().flags(EnvCreateReadOnly).open("unused", 0o777).unwrap();
+    /// let value = {
+    ///     let reader = env.get_reader().unwrap();
+    ///     unsafe { reader.bind(&handle).get_mapped(&env, &"key").unwrap() }
+    /// };
+    /// drop(env);
+    /// println!("{:?}", value);
+    /// ```
+    pub unsafe fn get_mapped<'env>(&self, env: &'env Environment, key: &ToMdbValue) -> MdbResult<&'env [u8]> {
+        assert_state_eq!(txn, self.txn.state, TransactionState::Normal);
+        if env.env.0 != self.txn.env.env.0 {
+            return Err(StateError("get_mapped requires environment of the transaction".to_owned()));
+        }
+        let mut key_val = key.to_mdb_value();
+        let mut data_val: ffi::MDB_val = std::mem::zeroed();
+        try_mdb!(ffi::mdb_get(self.txn.handle, self.handle, &mut key_val.value, &mut data_val));
+        Ok(MdbValue::<'env>::from_raw(&data_val).as_slice())
     }
 
-    /// Deletes value for key.
-    pub fn del(&self, key: &ToMdbValue) -> MdbResult<()> {
-        self.txn.del(self.handle, key)
-    }
+    /// Retrieves the greatest key which is less than or equal to `key`
+    /// together with its value, `None` if all keys are greater. In
+    /// case of DbAllowDups the value is the first item of found key.
+    pub fn get_lte<K, V>(&'a self, key: &K) -> MdbResult<Option<(K, V)>>
+        where K: ToMdbValue + FromMdbValue + 'a, V: FromMdbValue + 'a
+    {
+        let mut cursor = try!(self.new_cursor());
+        let moved = match cursor.to_key_or_next(key) {
+            Ok(true) => Ok(()),
+            Ok(false) => cursor.to_prev_key(),
+            Err(NotFound) => cursor.to_last(),
+            Err(e) => return Err(e),
+        };
+        match moved {
+            Ok(_) => (),
+            Err(NotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        }
 
-    /// Should be used only with DbAllowDups. Deletes corresponding (key, value)
-    pub fn del_item(&self, key: &ToMdbValue, data: &ToMdbValue) -> MdbResult<()> {
-        self.txn.del_item(self.handle, key, data)
+        to_first_item_of_key(&mut cursor);
+        let (k, v) = try!(cursor.get_plain());
+        Ok(Some((FromMdbValue::from_mdb_value(&k), FromMdbValue::from_mdb_value(&v))))
     }
 
-    /// Returns a new cursor
-    pub fn new_cursor(&'a self) -> MdbResult<Cursor<'a>> {
-        self.txn.new_cursor(self.handle)
+    /// Retrieves a value by key, returns `default` if key is missing
+    pub fn get_with_default<K: ToMdbValue, V: FromMdbValue + 'a>(&'a self, key: &K, default: V) -> MdbResult<V> {
+        match self.txn.get(self.handle, key) {
+            Err(NotFound) => Ok(default),
+            res => res,
+        }
     }
 
-    /// Deletes current db, also moves it out
-    pub fn del_db(self) -> MdbResult<()> {
-        self.txn.del_db(self)
+    /// Retrieves a value by key, returns `V::default()` if key is missing
+    pub fn get_or_default<K: ToMdbValue, V: FromMdbValue + Default + 'a>(&'a self, key: &K) -> MdbResult<V> {
+        self.get_with_default(key, V::default())
     }
 
-    /// Removes all key/values from db
-    pub fn clear(&self) -> MdbResult<()> {
-        self.txn.clear_db(self.handle)
+    /// Returns a new cursor
+    pub fn new_cursor(&'a self) -> MdbResult<Cursor<'a, M>> {
+        Cursor::new(self.txn, self.handle)
     }
 
     /// Returns an iterator for all values in database
@@ -465,6 +956,79 @@ impl<'a> Database<'a> {
             .and_then(|c| Ok(CursorIterator::wrap(c, CursorIter)))
     }
 
+    /// Returns an iterator for all key-value pairs in database
+    /// converted to `(K, V)`
+    pub fn iter_typed<K, V>(&'a self) -> MdbResult<TypedIter<'a, CursorIter, K, V>>
+        where K: FromMdbValue + 'a, V: FromMdbValue + 'a
+    {
+        self.iter().map(|it| it.typed())
+    }
+
+    /// Collects the whole database into an `IndexMap`, which keeps
+    /// database key order. For DbAllowDups only the first value of
+    /// each key is collected
+    #[cfg(feature = "indexmap")]
+    pub fn scan_ordered<K, V>(&'a self) -> MdbResult<::indexmap::IndexMap<K, V>>
+        where K: FromMdbValue + Eq + Hash + 'a, V: FromMdbValue + 'a
+    {
+        let iter = try!(self.iter());
+        Ok(iter.map(|cv| cv.get()).collect())
+    }
+
+    /// Merges this database and `src` into `dest` in a single pass
+    /// over both, like the merge step of merge sort. Keys present in
+    /// both get `merge_fn(value, src_value)`, others are copied as is.
+    /// Returns count of records written to `dest`.
+    ///
+    /// Keys are compared with key ordering of this database, so `src`
+    /// must use the same one (e.g. both with or without DbIntKey), and
+    /// `dest` must be a different database. Keys and
+    /// values are read while `dest` is modified, so they should be
+    /// owned types. Intended for databases without DbAllowDups.
+    pub fn scan_and_merge<'s, K, V, F, M2>(&'a self, src: &'s Database<'s, M2>, merge_fn: F,
+                                          dest: &Database<ReadWrite>) -> MdbResult<usize>
+        where K: ToMdbValue + FromMdbValue + 'a + 's,
+              V: ToMdbValue + FromMdbValue + 'a + 's,
+              F: Fn(V, V) -> V
+    {
+        let mut left = try!(self.iter_typed::<K, V>()).peekable();
+        let mut right = try!(src.iter_typed::<K, V>()).peekable();
+        let mut written = 0;
+
+        loop {
+            let order = match (left.peek(), right.peek()) {
+                (Some(&(ref lk, _)), Some(&(ref rk, _))) => self.cmp_keys(lk, rk),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+
+            let (key, value) = match order {
+                Ordering::Less => left.next().unwrap(),
+                Ordering::Greater => right.next().unwrap(),
+                Ordering::Equal => {
+                    let (key, value) = left.next().unwrap();
+                    let (_, src_value) = right.next().unwrap();
+                    (key, merge_fn(value, src_value))
+                }
+            };
+            try!(dest.set(&key, &value));
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    /// Returns an iterator for all values in database
+    pub fn iter_values<V: FromMdbValue + 'a>(&'a self) -> MdbResult<impl Iterator<Item=V> + 'a> {
+        self.iter().map(|it| it.map(|cv| cv.get_value::<V>()))
+    }
+
+    /// Returns an iterator for all keys in database
+    pub fn iter_keys<K: FromMdbValue + 'a>(&'a self) -> MdbResult<impl Iterator<Item=K> + 'a> {
+        self.iter().map(|it| it.map(|cv| cv.get_key::<K>()))
+    }
+
     /// Returns an iterator through keys starting with start_key (>=), start_key is included
     pub fn keyrange_from<'c, K: ToMdbValue + 'c>(&'c self, start_key: &'c K) -> MdbResult<CursorIterator<'c, CursorFromKeyIter>> {
         let cursor = try!(self.txn.new_cursor(self.handle));
@@ -473,6 +1037,29 @@ impl<'a> Database<'a> {
         Ok(wrap)
     }
 
+    /// Same as `iter_keys`, but conversion failures, i.e. panics in
+    /// `FromMdbValue`, are yielded as `Other` errors instead of aborting
+    /// iteration. A cursor error which stopped iteration is yielded
+    /// as the last item.
+    pub fn try_iter_keys<K: FromMdbValue + 'a>(&'a self) -> MdbResult<impl Iterator<Item=MdbResult<K>> + 'a> {
+        self.iter().map(|it| try_convert_iter(it, |cv| cv.get_key::<K>()))
+    }
+
+    /// Same as `iter_values`, but yields conversion failures as errors,
+    /// see `try_iter_keys`
+    pub fn try_iter_values<V: FromMdbValue + 'a>(&'a self) -> MdbResult<impl Iterator<Item=MdbResult<V>> + 'a> {
+        self.iter().map(|it| try_convert_iter(it, |cv| cv.get_value::<V>()))
+    }
+
+    /// Returns an iterator through keys starting with `start_key`, which
+    /// is included only if `inclusive` is true. `start_key` doesn't need
+    /// to exist, iteration then starts from the next greater key.
+    pub fn iter_from<'c, K: ToMdbValue + 'c>(&'c self, start_key: &'c K, inclusive: bool)
+                                             -> MdbResult<CursorIterator<'c, CursorFromKeyIter<'c>>> {
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        Ok(CursorIterator::wrap(cursor, CursorFromKeyIter::new_with_inclusive(start_key, inclusive)))
+    }
+
     /// Returns an iterator through keys less than end_key, end_key is not included
     pub fn keyrange_to<'c, K: ToMdbValue + 'c>(&'c self, end_key: &'c K) -> MdbResult<CursorIterator<'c, CursorToKeyIter>> {
         let cursor = try!(self.txn.new_cursor(self.handle));
@@ -483,9 +1070,13 @@ impl<'a> Database<'a> {
 
     /// Returns an iterator through keys `start_key <= x < end_key`. This is, start_key is
     /// included in the iteration, while end_key is kept excluded.
-    pub fn keyrange_from_to<'c, K: ToMdbValue + 'c>(&'c self, start_key: &'c K, end_key: &'c K)
+    ///
+    /// Fails with `StateError` if `start_key > end_key`, see `keyrange`.
+    pub fn keyrange_from_to<'c, K1, K2>(&'c self, start_key: &'c K1, end_key: &'c K2)
                                -> MdbResult<CursorIterator<'c, CursorKeyRangeIter>>
+        where K1: ToMdbValue + 'c, K2: ToMdbValue + 'c
     {
+        try!(self.check_range_bounds(&start_key.to_mdb_value(), &end_key.to_mdb_value()));
         let cursor = try!(self.txn.new_cursor(self.handle));
         let key_range = CursorKeyRangeIter::new(start_key, end_key, false);
         let wrap = CursorIterator::wrap(cursor, key_range);
@@ -496,15 +1087,73 @@ impl<'a> Database<'a> {
     /// Currently it works only for unique keys (i.e. it will skip
     /// multiple items when DB created with ffi::MDB_DUPSORT).
     /// Iterator is valid while cursor is valid
-    pub fn keyrange<'c, K: ToMdbValue + 'c>(&'c self, start_key: &'c K, end_key: &'c K)
+    ///
+    /// Bounds are compared using the database ordering and it is an
+    /// error (`StateError`) to pass `start_key > end_key`. Note that with
+    /// `DbReverseKey` keys are compared starting from their last byte, so
+    /// bounds should be ordered that way: for example, "b1" < "a2" there,
+    /// so a range over both is `keyrange(&"b1", &"a2")`.
+    pub fn keyrange<'c, K1, K2>(&'c self, start_key: &'c K1, end_key: &'c K2)
                                -> MdbResult<CursorIterator<'c, CursorKeyRangeIter>>
+        where K1: ToMdbValue + 'c, K2: ToMdbValue + 'c
     {
+        try!(self.check_range_bounds(&start_key.to_mdb_value(), &end_key.to_mdb_value()));
         let cursor = try!(self.txn.new_cursor(self.handle));
         let key_range = CursorKeyRangeIter::new(start_key, end_key, true);
         let wrap = CursorIterator::wrap(cursor, key_range);
         Ok(wrap)
     }
 
+    /// Same as `keyrange`, but owns bound keys, so they could be
+    /// constructed locally and iterator still returned to the caller
+    pub fn keyrange_owned<'c>(&'c self, start_key: Vec<u8>, end_key: Vec<u8>)
+                              -> MdbResult<CursorIterator<'c, CursorOwnedKeyRangeIter>> {
+        try!(self.check_range_bounds(&start_key.to_mdb_value(), &end_key.to_mdb_value()));
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        let key_range = CursorOwnedKeyRangeIter::new(start_key, end_key, true);
+        Ok(CursorIterator::wrap(cursor, key_range))
+    }
+
+    /// Same as `keyrange`, but yields `(K, V)` pairs
+    pub fn keyrange_typed<'c, K1, K2, K, V>(&'c self, start_key: &'c K1, end_key: &'c K2)
+                                            -> MdbResult<TypedIter<'c, CursorKeyRangeIter<'c>, K, V>>
+        where K1: ToMdbValue + 'c, K2: ToMdbValue + 'c, K: FromMdbValue + 'c, V: FromMdbValue + 'c
+    {
+        self.keyrange(start_key, end_key).map(|it| it.typed())
+    }
+
+    /// Returns up to `limit` key-value pairs with keys strictly greater
+    /// than `after`, or starting from the first key if it is `None`.
+    /// Passing the last key of a page as `after` gives the next page.
+    pub fn get_page<K, V>(&'a self, after: Option<&ToMdbValue>, limit: usize) -> MdbResult<Vec<(K, V)>>
+        where K: FromMdbValue + 'a, V: FromMdbValue + 'a
+    {
+        let mut page = Vec::new();
+        if limit == 0 {
+            return Ok(page);
+        }
+
+        let mut cursor = try!(self.new_cursor());
+        let mut res = match after {
+            None => cursor.to_first(),
+            Some(key) => match cursor.to_key_or_next(&key.to_mdb_value()) {
+                Ok(true) => cursor.to_next_key(),
+                Ok(false) => Ok(()),
+                Err(e) => Err(e),
+            }
+        };
+
+        while res.is_ok() && page.len() < limit {
+            page.push(try!(cursor.get::<K, V>()));
+            res = cursor.to_next_key();
+        }
+
+        match res {
+            Ok(_) | Err(NotFound) => Ok(page),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Returns an iterator for all items (i.e. values with same key)
     pub fn item_iter<'c, 'db: 'c, K: ToMdbValue>(&'db self, key: &'c K) -> MdbResult<CursorIterator<'c, CursorItemIter<'c>>> {
         let cursor = try!(self.txn.new_cursor(self.handle));
@@ -512,6 +1161,103 @@ impl<'a> Database<'a> {
         Ok(CursorIterator::<'c>::wrap(cursor, inner_iter))
     }
 
+    /// Returns an iterator over items of key starting from the first
+    /// one which is greater than or equal to `start_value`
+    pub fn item_iter_from<'c, 'db: 'c, K, V>(&'db self, key: &'c K, start_value: &'c V)
+                                             -> MdbResult<CursorIterator<'c, CursorItemFromIter<'c>>>
+        where K: ToMdbValue, V: ToMdbValue
+    {
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        Ok(CursorIterator::wrap(cursor, CursorItemFromIter::new(key, start_value)))
+    }
+
+    /// Returns groups of values sharing the same key. Intended for
+    /// databases with DbAllowDups, otherwise every group has a single
+    /// value. All groups are walked by a single cursor
+    pub fn groups(&'a self) -> MdbResult<GroupIter<'a>> {
+        let flags = DbFlags::from_bits_truncate(try!(self.txn.dbi_flags(self.handle)));
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        Ok(GroupIter {
+            cursor: cursor,
+            started: false,
+            dup_sort: flags.contains(DbAllowDups),
+        })
+    }
+
+    /// Returns an iterator over distinct components following `prefix`
+    /// up to `delimiter` (or the end of key), like listing a directory
+    /// of a flat keyspace with paths as keys. Keys below each
+    /// component are skipped without being visited.
+    ///
+    /// Assumes default lexicographic key ordering.
+    pub fn child_prefixes(&'a self, prefix: &[u8], delimiter: u8) -> MdbResult<ChildPrefixes<'a, M>> {
+        let cursor = try!(self.new_cursor());
+        Ok(ChildPrefixes {
+            cursor: cursor,
+            prefix: prefix.to_vec(),
+            delimiter: delimiter,
+            started: false,
+            done: false,
+            skip: None,
+            last: None,
+            visited: 0,
+        })
+    }
+
+    /// Returns an iterator for all items of key in reverse order,
+    /// i.e. starting from the last one.
+    ///
+    /// The same could be done manually by positioning a cursor with
+    /// `to_key`, then `to_last_item` and walking with `to_prev_item`.
+    pub fn item_iter_rev<'c, 'db: 'c, K: ToMdbValue>(&'db self, key: &'c K) -> MdbResult<CursorIterator<'c, CursorItemRevIter<'c>>> {
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        Ok(CursorIterator::wrap(cursor, CursorItemRevIter::new(key)))
+    }
+
+    /// Returns an iterator over items of key in page-sized chunks,
+    /// which is much faster than `item_iter` for many duplicates.
+    /// Each chunk is a raw array of fixed-size values, so database
+    /// must be created with DbDupFixed.
+    pub fn item_chunks<K: ToMdbValue>(&'a self, key: &K) -> MdbResult<ItemChunks<'a, M>> {
+        self.new_item_chunks(key, false)
+    }
+
+    /// Same as `item_chunks`, but yields chunks starting from the last
+    /// one, i.e. the newest items first for monotonic values. Items
+    /// inside each chunk are still in database order.
+    pub fn item_chunks_rev<K: ToMdbValue>(&'a self, key: &K) -> MdbResult<ItemChunks<'a, M>> {
+        self.new_item_chunks(key, true)
+    }
+
+    fn new_item_chunks<K: ToMdbValue>(&'a self, key: &K, reverse: bool) -> MdbResult<ItemChunks<'a, M>> {
+        try!(self.require_flags(DbAllowDups | DbDupFixed, "item_chunks"));
+        let mut cursor = try!(self.new_cursor());
+        let done = match cursor.to_key(key) {
+            Ok(_) => false,
+            Err(NotFound) => true,
+            Err(e) => return Err(e),
+        };
+        Ok(ItemChunks {
+            cursor: cursor,
+            reverse: reverse,
+            started: false,
+            done: done,
+        })
+    }
+
+    /// Same as `item_iter`, but yields `(K, V)` pairs
+    pub fn item_iter_typed<'c, 'db: 'c, T, K, V>(&'db self, key: &'c T) -> MdbResult<TypedIter<'c, CursorItemIter<'c>, K, V>>
+        where T: ToMdbValue, K: FromMdbValue + 'c, V: FromMdbValue + 'c
+    {
+        self.item_iter(key).map(|it| it.typed())
+    }
+
+    /// Same as `item_iter`, but owns the key
+    pub fn item_iter_owned<'c>(&'c self, key: Vec<u8>) -> MdbResult<CursorIterator<'c, CursorOwnedItemIter>> {
+        let cursor = try!(self.txn.new_cursor(self.handle));
+        Ok(CursorIterator::wrap(cursor, CursorOwnedItemIter::new(key)))
+    }
+
     /// Sets the key compare function for this database.
     ///
     /// Warning: This function must be called before any data access functions
@@ -546,20 +1292,407 @@ impl<'a> Database<'a> {
             ffi::mdb_set_dupsort(self.txn.handle, self.handle, cmp_fn)
         })
     }
-}
 
+    /// Walks the whole database and checks that keys (and values of
+    /// the same key in case of DbAllowDups) are ordered according to the
+    /// currently registered compare functions.
+    ///
+    /// A violation usually means that compare function was changed
+    /// after data was written. Only the first one is reported.
+    pub fn verify_order(&'a self) -> MdbResult<OrderReport> {
+        let flags = try!(self.txn.dbi_flags(self.handle));
+        let has_dups = (flags & ffi::MDB_DUPSORT) == ffi::MDB_DUPSORT;
+
+        let mut cursor = try!(self.new_cursor());
+        let mut report = OrderReport { checked: 0, violation: None };
+        let mut prev: Option<(MdbValue, MdbValue)> = None;
+        let mut res = cursor.to_first();
+
+        while res.is_ok() {
+            let (key, value) = try!(cursor.get_plain());
+            if let Some((prev_key, prev_value)) = prev {
+                let (mut pk, mut k) = (prev_key.value, key.value);
+                let key_cmp = unsafe { ffi::mdb_cmp(self.txn.handle, self.handle, &mut pk, &mut k) };
+                let violation = if key_cmp > 0 || (key_cmp == 0 && !has_dups) {
+                    Some(OrderViolation::Key {
+                        index: report.checked,
+                        previous_key: FromMdbValue::from_mdb_value(&prev_key),
+                        key: FromMdbValue::from_mdb_value(&key),
+                    })
+                } else if key_cmp == 0 {
+                    let (mut pv, mut v) = (prev_value.value, value.value);
+                    let value_cmp = unsafe { ffi::mdb_dcmp(self.txn.handle, self.handle, &mut pv, &mut v) };
+                    if value_cmp >= 0 {
+                        Some(OrderViolation::Value {
+                            index: report.checked,
+                            key: FromMdbValue::from_mdb_value(&key),
+                            previous_value: FromMdbValue::from_mdb_value(&prev_value),
+                            value: FromMdbValue::from_mdb_value(&value),
+                        })
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                };
 
-/// Constructs environment with settigs which couldn't be
-/// changed after opening. By default it tries to create
-/// corresponding dir if it doesn't exist, use `autocreate_dir()`
-/// to override that behavior
-#[derive(Copy, Clone, Debug)]
-pub struct EnvBuilder {
-    flags: EnvCreateFlags,
-    max_readers: Option<usize>,
-    max_dbs: Option<usize>,
+                if violation.is_some() {
+                    report.violation = violation;
+                    return Ok(report);
+                }
+            }
+            report.checked += 1;
+            prev = Some((key, value));
+            res = cursor.navigate(ffi::MDB_cursor_op::MDB_NEXT);
+        }
+
+        match res {
+            Err(NotFound) => Ok(report),
+            Err(e) => Err(e),
+            Ok(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a> Database<'a, ReadWrite> {
+    /// Sets value for key. In case of DbAllowDups it will add a new item
+    pub fn set(&self, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<()> {
+        self.txn.set(self.handle, key, value)
+    }
+
+    /// Appends new key-value pair to database, starting a new page instead of splitting an
+    /// existing one if necessary. Requires that key be >= all existing keys in the database
+    /// (or will return KeyExists error).
+    pub fn append<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<()> {
+        self.txn.append(self.handle, key, value)
+    }
+
+    /// Appends new value for the given key (requires DbAllowDups), starting a new page instead
+    /// of splitting an existing one if necessary. Requires that value be >= all existing values
+    /// for the given key (or will return KeyExists error).
+    pub fn append_duplicate<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<()> {
+        self.txn.append_duplicate(self.handle, key, value)
+    }
+
+    /// Same as `append`, but falls back to a plain `set` if `key` is out
+    /// of order instead of failing with KeyExists. Returns which way
+    /// was used, which helps to monitor how sorted input really is.
+    pub fn append_or_set<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<AppendOutcome> {
+        match self.append(key, value) {
+            Ok(_) => Ok(AppendOutcome::Appended),
+            Err(KeyExists) => self.set(key, value).map(|_| AppendOutcome::Set),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Same as `append_duplicate`, but falls back to a plain `set` if
+    /// `value` is out of order, see `append_or_set`
+    pub fn append_duplicate_or_set<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V) -> MdbResult<AppendOutcome> {
+        match self.append_duplicate(key, value) {
+            Ok(_) => Ok(AppendOutcome::Appended),
+            Err(KeyExists) => self.set(key, value).map(|_| AppendOutcome::Set),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Set value for key. Fails if key already exists, even when duplicates are allowed.
+    pub fn insert(&self, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<()> {
+        self.txn.insert(self.handle, key, value)
+    }
+
+    /// Reserves `size` bytes for value of key without copying any
+    /// data and returns pointer to reserved space (`MDB_RESERVE`).
+    /// Not supported for databases with DbAllowDups.
+    ///
+    /// # Safety
+    ///
+    /// Caller must write exactly `size` bytes through the returned
+    /// pointer before the next write operation in this transaction
+    /// or its commit, otherwise garbage gets stored.
+    pub unsafe fn set_reserve(&self, key: &ToMdbValue, size: usize) -> MdbResult<*mut u8> {
+        self.txn.reserve_value(self.handle, key, size)
+    }
+
+    /// Read-modify-write of a single value. Closure receives current
+    /// value for key (`None` if key doesn't exist) and returns a new one,
+    /// which is stored and returned. In case of DbAllowDups it operates
+    /// on the first value and a new value must sort into the same place.
+    ///
+    /// Writing may move data of the current value, so `V` has to be an
+    /// owned type.
+    pub fn merge<V, F>(&'a self, key: &ToMdbValue, f: F) -> MdbResult<V>
+        where V: FromMdbValueOwned + ToMdbValue, F: FnOnce(Option<V>) -> V
+    {
+        let key = key.to_mdb_value();
+        let mut cursor = try!(self.new_cursor());
+        let existing = match cursor.to_key(&key) {
+            Ok(_) => Some(try!(cursor.get_value::<V>())),
+            Err(NotFound) => None,
+            Err(e) => return Err(e),
+        };
+
+        let is_present = existing.is_some();
+        let new_value = f(existing);
+        if is_present {
+            try!(cursor.replace(&new_value));
+        } else {
+            try!(cursor.set(&key, &new_value, 0));
+        }
+        Ok(new_value)
+    }
+
+    /// Sets a new value for key and returns the old one (`None` if
+    /// key didn't exist). Writing may reuse memory of the old value,
+    /// so `V` has to be an owned type. Intended for databases without
+    /// DbAllowDups.
+    pub fn swap<K: ToMdbValue, V: FromMdbValueOwned + ToMdbValue>(&'a self, key: &K, new_value: &V) -> MdbResult<Option<V>> {
+        let old = match self.get::<V>(key) {
+            Ok(v) => Some(v),
+            Err(NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        try!(self.set(key, new_value));
+        Ok(old)
+    }
+
+    /// Deletes value for key.
+    pub fn del(&self, key: &ToMdbValue) -> MdbResult<()> {
+        self.txn.del(self.handle, key)
+    }
+
+    /// Deletes all `keys` using a single cursor, with all their items
+    /// in case of DbAllowDups. Keys are visited in sorted order to
+    /// keep cursor moves local. Missing keys aren't an error, they are
+    /// just counted in the report. Repeated keys are counted once.
+    pub fn del_many<K: ToMdbValue + Ord>(&self, keys: &[K]) -> MdbResult<DelManyReport> {
+        let mut sorted: Vec<&K> = keys.iter().collect();
+        sorted.sort();
+        sorted.dedup();
+
+        let mut report = DelManyReport::default();
+        let mut cursor = try!(self.new_cursor());
+        for key in sorted {
+            match cursor.to_key(key) {
+                Ok(_) => {
+                    try!(cursor.del_all());
+                    report.deleted += 1;
+                },
+                Err(NotFound) => report.missing += 1,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Deletes value for key if it exists, returns whether it did
+    pub fn try_del<K: ToMdbValue>(&self, key: &K) -> MdbResult<bool> {
+        match self.txn.del(self.handle, key) {
+            Ok(_) => Ok(true),
+            Err(NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Should be used only with DbAllowDups. Deletes corresponding (key, value)
+    pub fn del_item(&self, key: &ToMdbValue, data: &ToMdbValue) -> MdbResult<()> {
+        self.txn.del_item(self.handle, key, data)
+    }
+
+    /// Returns a `fmt::Write` adapter which collects formatted text
+    /// and stores it as value of `key` on `flush`
+    pub fn writer<'d, K: ToMdbValue>(&'d self, key: &K) -> DatabaseWriter<'d, 'a> {
+        DatabaseWriter {
+            db: self,
+            key: key.to_mdb_value().as_slice().to_vec(),
+            buf: String::new(),
+        }
+    }
+
+    /// Returns an `io::Write` adapter which collects bytes and stores
+    /// them as value of `key` on `flush`
+    pub fn blob_writer<'d, K: ToMdbValue>(&'d self, key: &K) -> DatabaseBlobWriter<'d, 'a> {
+        DatabaseBlobWriter {
+            db: self,
+            key: key.to_mdb_value().as_slice().to_vec(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Deletes the first (minimum) item of `key` and returns its
+    /// value, `None` if there is no such key. Makes DbAllowDups
+    /// database usable as a set of priority queues.
+    ///
+    /// Deleting an item moves data within its page, so `V` has to be
+    /// an owned type which is copied out before that.
+    pub fn pop_min_item<K: ToMdbValue, V: FromMdbValueOwned>(&self, key: &K) -> MdbResult<Option<V>> {
+        let mut cursor = try!(self.txn.new_cursor(self.handle));
+        match cursor.to_key(key) {
+            Ok(_) => (),
+            Err(NotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let value = try!(cursor.get_value::<V>());
+        try!(cursor.del_item());
+        Ok(Some(value))
+    }
+
+    /// Deletes items of `key` with values in range `[from, to)`, as
+    /// ordered by the duplicate comparison of the database. Requires
+    /// DbAllowDups. Returns count of deleted items, 0 if key is missing
+    /// or none of its values falls into range.
+    pub fn del_items_range<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, from: &V, to: &V) -> MdbResult<u64> {
+        try!(self.require_flags(DbAllowDups, "del_items_range"));
+        let mut cursor = try!(self.new_cursor());
+        let mut to_val = to.to_mdb_value().value;
+        let mut deleted = 0;
+        loop {
+            // repositioning after each delete also stops once the
+            // last item of key is deleted and key is gone
+            match cursor.to_gte_item(key, from) {
+                Ok(_) => (),
+                Err(NotFound) => break,
+                Err(e) => return Err(e),
+            }
+            let (_, value) = try!(cursor.get_plain());
+            let mut value = value.value;
+            let cmp = unsafe { ffi::mdb_dcmp(self.txn.handle, self.handle, &mut value, &mut to_val) };
+            if cmp >= 0 {
+                break;
+            }
+            try!(cursor.del_item());
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes current db, also moves it out
+    pub fn del_db(self) -> MdbResult<()> {
+        self.txn.del_db(self)
+    }
+
+    /// Removes all key/values from db
+    pub fn clear(&self) -> MdbResult<()> {
+        self.txn.clear_db(self.handle)
+    }
+}
+
+/// Applies `convert` to each item catching panics, yields cursor
+/// error, if any, after the last item
+fn try_convert_iter<'c, I, T, F>(mut iter: CursorIterator<'c, I>, convert: F) -> impl Iterator<Item=MdbResult<T>> + 'c
+    where I: IterateCursor + 'c, F: Fn(&CursorValue<'c>) -> T + 'c
+{
+    let mut finished = false;
+    ::std::iter::from_fn(move || {
+        if finished {
+            return None;
+        }
+        match iter.next() {
+            Some(cv) => Some(panic::catch_unwind(AssertUnwindSafe(|| convert(&cv))).map_err(|payload| {
+                let msg = payload.downcast_ref::<&str>().map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_owned());
+                MdbError::Other(-1, format!("conversion failed: {}", msg))
+            })),
+            None => {
+                finished = true;
+                iter.take_error().map(Err)
+            }
+        }
+    })
+}
+
+const WRITE_CHUNKED_GROW_ATTEMPTS: usize = 4;
+
+/// Failure of [write_chunked](struct.Environment.html#method.write_chunked)
+#[derive(Debug)]
+pub struct ChunkedWriteError {
+    /// Count of items durably committed before the failed chunk
+    pub committed: u64,
+    /// Error which stopped processing
+    pub error: MdbError,
+}
+
+impl fmt::Display for ChunkedWriteError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "write_chunked stopped after {} committed items: {}", self.committed, self.error)
+    }
+}
+
+impl Error for ChunkedWriteError {
+    fn source(&self) -> Option<&(Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+fn check_chunk_size(chunk: usize) -> Result<(), ChunkedWriteError> {
+    if chunk == 0 {
+        return Err(ChunkedWriteError {
+            committed: 0,
+            error: MdbError::StateError("write_chunked chunk size must be positive".to_owned()),
+        });
+    }
+    Ok(())
+}
+
+/// Keeps LMDB code of the original error, see `MdbError::with_context`
+impl From<ChunkedWriteError> for MdbError {
+    fn from(e: ChunkedWriteError) -> MdbError {
+        let ctx = format!("write_chunked stopped after {} committed items", e.committed);
+        e.error.with_context(ctx)
+    }
+}
+
+/// Result of [del_many](struct.Database.html#method.del_many)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DelManyReport {
+    /// Count of deleted keys
+    pub deleted: usize,
+    /// Count of keys which weren't found
+    pub missing: usize,
+}
+
+/// Result of [append_or_set](struct.Database.html#method.append_or_set)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AppendOutcome {
+    /// Fast path, item was appended
+    Appended,
+    /// Item was out of order and was set instead
+    Set,
+}
+
+/// Result of [verify_order](struct.Database.html#method.verify_order)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderReport {
+    /// Number of entries checked before the first violation
+    pub checked: usize,
+    /// The first found violation, if any
+    pub violation: Option<OrderViolation>,
+}
+
+/// Ordering violation between two adjacent entries, `index` is the
+/// position of the offending entry in iteration order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderViolation {
+    /// Key isn't greater than the previous one
+    Key { index: usize, previous_key: Vec<u8>, key: Vec<u8> },
+    /// Value isn't greater than the previous value of the same key
+    Value { index: usize, key: Vec<u8>, previous_value: Vec<u8>, value: Vec<u8> },
+}
+
+
+/// Constructs environment with settigs which couldn't be
+/// changed after opening. By default it tries to create
+/// corresponding dir if it doesn't exist, use `autocreate_dir()`
+/// to override that behavior
+#[derive(Copy, Clone, Debug)]
+pub struct EnvBuilder {
+    flags: EnvCreateFlags,
+    max_readers: Option<usize>,
+    max_dbs: Option<usize>,
     map_size: Option<u64>,
     autocreate_dir: bool,
+    check_sizes: bool,
+    validate_at_open: bool,
 }
 
 impl EnvBuilder {
@@ -570,6 +1703,8 @@ impl EnvBuilder {
             max_dbs: None,
             map_size: None,
             autocreate_dir: true,
+            check_sizes: true,
+            validate_at_open: false,
         }
     }
 
@@ -605,14 +1740,30 @@ impl EnvBuilder {
         self
     }
 
+    /// Sets whether key and value sizes are checked against
+    /// `get_maxkeysize` before writes, reporting `BadValSize` (default
+    /// is true). Could be disabled for custom LMDB builds with
+    /// different limits.
+    pub fn check_sizes(mut self, check_sizes: bool) -> EnvBuilder {
+        self.check_sizes = check_sizes;
+        self
+    }
+
+    /// Sets whether stale reader slots left by crashed processes are
+    /// cleared right after opening with `reader_check` (default is
+    /// false). Count of cleared slots is available in `open_stats`.
+    pub fn validate_at_open(mut self, validate: bool) -> EnvBuilder {
+        self.validate_at_open = validate;
+        self
+    }
+
     /// Opens environment in specified path
     pub fn open<P: AsRef<Path>>(self, path: P, perms: u32) -> MdbResult<Environment> {
         let changeable_flags: EnvCreateFlags = EnvCreataMapAsync | EnvCreateNoMemInit | EnvCreateNoSync | EnvCreateNoMetaSync;
 
-        let env: *mut ffi::MDB_env = ptr::null_mut();
+        let mut env: *mut ffi::MDB_env = ptr::null_mut();
         unsafe {
-            let p_env: *mut *mut ffi::MDB_env = std::mem::transmute(&env);
-            let _ = try_mdb!(ffi::mdb_env_create(p_env));
+            let _ = try_mdb!(ffi::mdb_env_create(&mut env));
         }
 
         // Enable only flags which can be changed, otherwise it'll fail
@@ -635,6 +1786,9 @@ impl EnvBuilder {
         }
 
         let is_readonly = self.flags.contains(EnvCreateReadOnly);
+        let is_no_tls = self.flags.contains(EnvCreateNoTls);
+        let check_sizes = self.check_sizes;
+        let validate = self.validate_at_open;
 
         let res = unsafe {
             // FIXME: revert back once `convert` is stable
@@ -649,7 +1803,16 @@ impl EnvBuilder {
         drop(self);
         match res {
             ffi::MDB_SUCCESS => {
-                Ok(Environment::from_raw(env, is_readonly))
+                let mut env = Environment::from_raw(env, is_readonly, is_no_tls, check_sizes);
+                if validate {
+                    let dead = try!(env.reader_check());
+                    debug!("cleared {} stale reader slots at open", dead);
+                    env.open_stats = OpenStats {
+                        validated: true,
+                        dead_readers: dead as usize,
+                    };
+                }
+                Ok(env)
             },
             _ => {
                 unsafe { ffi::mdb_env_close(mem::transmute(env)); }
@@ -694,6 +1857,75 @@ impl EnvBuilder {
 #[derive(Debug)]
 struct EnvHandle(*mut ffi::MDB_env);
 
+/// Serializes opening of databases. From LMDB docs for mdb_dbi_open:
+///
+/// This function must not be called from multiple concurrent
+/// transactions. A transaction that uses this function must finish
+/// (either commit or abort) before any other transaction may use
+/// this function
+///
+/// So lock is held by the root of transaction tree from the first
+/// opened database until the root is finished, which rules out a
+/// `MutexGuard` borrowed from environment.
+#[derive(Debug, Default)]
+struct DbiOpenLock {
+    state: Mutex<DbiOpenState>,
+    released: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct DbiOpenState {
+    // root transaction holding the lock
+    owner: Option<usize>,
+    // databases opened while lock is held, with transaction which opened them
+    opened: Vec<(usize, String, ffi::MDB_dbi)>,
+}
+
+impl DbiOpenLock {
+    /// Waits until lock is free or already held by `owner` and takes it
+    fn acquire(&self, owner: usize) -> MdbResult<()> {
+        let mut state = try!(self.state.lock().map_err(|_| MdbError::CacheError));
+        while state.owner.map_or(false, |o| o != owner) {
+            state = try!(self.released.wait(state).map_err(|_| MdbError::CacheError));
+        }
+        state.owner = Some(owner);
+        Ok(())
+    }
+
+    /// Remembers database opened by `txn` while lock is held
+    fn record(&self, txn: usize, name: &str, db: ffi::MDB_dbi) -> MdbResult<()> {
+        let mut state = try!(self.state.lock().map_err(|_| MdbError::CacheError));
+        state.opened.push((txn, name.to_owned(), db));
+        Ok(())
+    }
+
+    /// Hands databases opened by nested `txn` over to `parent` if it
+    /// was committed, forgets them otherwise as LMDB closes them
+    fn child_finished(&self, txn: usize, parent: usize, committed: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if committed {
+            for opened in state.opened.iter_mut().filter(|o| o.0 == txn) {
+                opened.0 = parent;
+            }
+        } else {
+            state.opened.retain(|o| o.0 != txn);
+        }
+    }
+
+    /// Releases lock if it is held by `owner`, returns databases opened
+    /// by its transaction tree
+    fn release(&self, owner: usize) -> Vec<(String, ffi::MDB_dbi)> {
+        // never leave lock taken, even if some holder panicked
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.owner != Some(owner) {
+            return Vec::new();
+        }
+        state.owner = None;
+        self.released.notify_all();
+        state.opened.drain(..).map(|(_, name, db)| (name, db)).collect()
+    }
+}
+
 impl Drop for EnvHandle {
     fn drop(&mut self) {
         unsafe {
@@ -704,12 +1936,26 @@ impl Drop for EnvHandle {
     }
 }
 
+/// Result of checks performed at open,
+/// see [open_stats](struct.Environment.html#method.open_stats)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpenStats {
+    /// Whether reader table was checked
+    pub validated: bool,
+    /// Count of stale reader slots cleared
+    pub dead_readers: usize,
+}
+
 /// Represents LMDB Environment. Should be opened using `EnvBuilder`
 #[derive(Debug)]
 pub struct Environment {
     env: Arc<EnvHandle>,
     db_cache: Arc<Mutex<UnsafeCell<HashMap<String, ffi::MDB_dbi>>>>,
+    dbi_open_lock: Arc<DbiOpenLock>,
     is_readonly: bool, // true if opened in 'read-only' mode
+    is_no_tls: bool, // true if opened with EnvCreateNoTls
+    max_key_size: Option<usize>, // set if key/value sizes are checked
+    open_stats: OpenStats,
 }
 
 impl Environment {
@@ -717,14 +1963,29 @@ impl Environment {
         EnvBuilder::new()
     }
 
-    fn from_raw(env: *mut ffi::MDB_env, is_readonly: bool) -> Environment {
+    fn from_raw(env: *mut ffi::MDB_env, is_readonly: bool, is_no_tls: bool, check_sizes: bool) -> Environment {
+        let max_key_size = if check_sizes {
+            Some(unsafe { ffi::mdb_env_get_maxkeysize(env) } as usize)
+        } else {
+            None
+        };
         Environment {
             env: Arc::new(EnvHandle(env)),
             db_cache: Arc::new(Mutex::new(UnsafeCell::new(HashMap::new()))),
+            dbi_open_lock: Arc::new(DbiOpenLock::default()),
             is_readonly: is_readonly,
+            is_no_tls: is_no_tls,
+            max_key_size: max_key_size,
+            open_stats: OpenStats::default(),
         }
     }
 
+    /// Returns what was done while opening environment,
+    /// see [validate_at_open](struct.EnvBuilder.html#method.validate_at_open)
+    pub fn open_stats(&self) -> OpenStats {
+        self.open_stats
+    }
+
     /// Check for stale entries in the reader lock table.
     ///
     /// Returns the number of stale slots that were cleared.
@@ -733,6 +1994,40 @@ impl Environment {
         lift_mdb!(unsafe { ffi::mdb_reader_check(self.env.0, &mut dead as *mut c_int)}, dead)
     }
 
+    /// Dumps the reader lock table, one line per entry as formatted
+    /// by LMDB: a header followed by `pid thread txnid` lines, where
+    /// txnid is `-` for slots without an active transaction.
+    pub fn reader_list(&self) -> MdbResult<Vec<String>> {
+        let mut lines: Vec<String> = Vec::new();
+        lift_mdb!(unsafe {
+            ffi::mdb_reader_list(self.env.0, collect_reader_line,
+                                 &mut lines as *mut Vec<String> as *mut c_void)
+        }, lines)
+    }
+
+    /// Reports how far the oldest active reader lags behind the last
+    /// committed transaction. Pages freed after the reader's snapshot
+    /// can't be reused until it finishes, so large lag means growing
+    /// freelist and file.
+    pub fn reader_lag(&self) -> MdbResult<ReaderLag> {
+        let oldest = try!(self.reader_list()).iter()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() == 3 {
+                    fields[2].parse::<u64>().ok()
+                } else {
+                    None
+                }
+            })
+            .min();
+        let latest = try!(self.info()).me_last_txnid as u64;
+        Ok(ReaderLag {
+            oldest_reader_txnid: oldest,
+            latest_txnid: latest,
+            lag: oldest.map_or(0, |txnid| latest.saturating_sub(txnid)),
+        })
+    }
+
     /// Retrieve environment statistics
     pub fn stat(&self) -> MdbResult<ffi::MDB_stat> {
         let mut tmp: ffi::MDB_stat = unsafe { std::mem::zeroed() };
@@ -788,14 +2083,15 @@ impl Environment {
         unsafe {ffi::mdb_env_get_maxkeysize(self.env.0)}
     }
 
-    /// Creates a backup copy in specified file descriptor
+    /// Creates a backup copy in specified file descriptor (`HANDLE`
+    /// on Windows)
     pub fn copy_to_fd(&self, fd: ffi::mdb_filehandle_t) -> MdbResult<()> {
         lift_mdb!(unsafe { ffi::mdb_env_copyfd(self.env.0, fd) })
     }
 
-    /// Gets file descriptor of this environment
+    /// Gets file descriptor (`HANDLE` on Windows) of this environment
     pub fn get_fd(&self) -> MdbResult<ffi::mdb_filehandle_t> {
-        let mut fd = 0;
+        let mut fd: ffi::mdb_filehandle_t = unsafe { mem::zeroed() };
         lift_mdb!({ unsafe { ffi::mdb_env_get_fd(self.env.0, &mut fd) }}, fd)
     }
 
@@ -812,15 +2108,122 @@ impl Environment {
         }
     }
 
+    /// Same as `copy_to_path`, but allows compaction with `EnvCopyCompact`
+    pub fn copy_to_path_with_flags<P: AsRef<Path>>(&self, path: P, flags: EnvCopyFlags) -> MdbResult<()> {
+        let path_str = try!(path.as_ref().to_str().ok_or(MdbError::InvalidPath));
+        let c_path = try!(CString::new(path_str).map_err(|_| MdbError::InvalidPath));
+
+        unsafe {
+            lift_mdb!(ffi::mdb_env_copy2(self.env.0, c_path.as_ref().as_ptr(), flags.bits()))
+        }
+    }
+
+    /// Same as `copy_to_fd`, but allows compaction with `EnvCopyCompact`
+    pub fn copy_to_fd_with_flags(&self, fd: ffi::mdb_filehandle_t, flags: EnvCopyFlags) -> MdbResult<()> {
+        lift_mdb!(unsafe { ffi::mdb_env_copyfd2(self.env.0, fd, flags.bits()) })
+    }
+
+    /// Same as `copy_to_path`, but calls `cb` with progress every
+    /// `every` and once more when copy is finished. Copy runs on a worker
+    /// thread while the calling thread polls size of the destination
+    /// file, which is created here (`path` itself with `EnvCreateNoSubDir`,
+    /// `data.mdb` in directory `path` otherwise).
+    ///
+    /// `flags` are passed to `copy_to_fd_with_flags`, size of a
+    /// compacted copy isn't known in advance, so `expected_bytes` is
+    /// `None` with `EnvCopyCompact`.
+    ///
+    /// Returning `ControlFlow::Break` from `cb` cancels the copy, the
+    /// partial file is removed and `StateError` is returned.
+    #[cfg(unix)]
+    pub fn copy_to_path_with_progress<P, F>(&self, path: P, flags: EnvCopyFlags, every: Duration, mut cb: F) -> MdbResult<()>
+        where P: AsRef<Path>, F: FnMut(BackupProgress) -> ControlFlow<()>
+    {
+        use std::fs::{self, File, OpenOptions};
+        use std::os::unix::io::AsRawFd;
+        use std::sync::mpsc;
+
+        let io_err = |e: ::std::io::Error| MdbError::Other(e.raw_os_error().unwrap_or(-1), e.to_string());
+
+        let file_path = if try!(self.get_all_flags()).contains(EnvCreateNoSubDir) {
+            path.as_ref().to_path_buf()
+        } else {
+            path.as_ref().join("data.mdb")
+        };
+        let expected = if flags.contains(EnvCopyCompact) {
+            None
+        } else {
+            let info = try!(self.info());
+            let stat = try!(self.stat());
+            Some((info.me_last_pgno as u64 + 1) * stat.ms_psize as u64)
+        };
+
+        let file = try!(OpenOptions::new().write(true).create_new(true).open(&file_path).map_err(&io_err));
+        let fd = file.as_raw_fd();
+        let progress = |file: &File| -> MdbResult<BackupProgress> {
+            let written = try!(file.metadata().map_err(&io_err)).len();
+            Ok(BackupProgress { bytes_written: written, expected_bytes: expected })
+        };
+
+        let res = ::std::thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+            scope.spawn(move || {
+                let _ = tx.send(self.copy_to_fd_with_flags(fd, flags));
+            });
+
+            loop {
+                match rx.recv_timeout(every) {
+                    Ok(res) => {
+                        try!(res);
+                        // copy is finished, nothing to stop
+                        let _ = cb(try!(progress(&file)));
+                        return Ok(());
+                    },
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        return Err(StateError("backup worker terminated unexpectedly".to_owned()));
+                    },
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        if let ControlFlow::Break(_) = cb(try!(progress(&file))) {
+                            // replacing fd with a read-only one makes further
+                            // writes fail with EBADF, unlike closing it there
+                            // is no risk of fd number being reused meanwhile
+                            let null = try!(File::open("/dev/null").map_err(&io_err));
+                            if unsafe { libc::dup2(null.as_raw_fd(), fd) } == -1 {
+                                // copy can't be interrupted, wait for it
+                                // to finish before removing the file
+                                let err = io_err(::std::io::Error::last_os_error());
+                                let _ = rx.recv();
+                                return Err(err);
+                            }
+                            let _ = rx.recv();
+                            return Err(StateError("backup cancelled".to_owned()));
+                        }
+                    },
+                }
+            }
+        });
+
+        drop(file);
+        if res.is_err() {
+            let _ = fs::remove_file(&file_path);
+        }
+        res
+    }
+
     fn create_transaction(&self, parent: Option<NativeTransaction>, flags: c_uint) -> MdbResult<NativeTransaction> {
         let mut handle: *mut ffi::MDB_txn = ptr::null_mut();
-        let parent_handle = match parent {
-            Some(t) => t.handle,
-            _ => ptr::null_mut()
+        let (parent_handle, root) = match parent {
+            Some(t) => (t.handle, t.root),
+            _ => (ptr::null_mut(), ptr::null_mut())
         };
 
-        lift_mdb!(unsafe { ffi::mdb_txn_begin(self.env.0, parent_handle, flags, &mut handle) },
-                 NativeTransaction::new_with_handle(handle, flags as usize, self))
+        try_mdb!(unsafe { ffi::mdb_txn_begin(self.env.0, parent_handle, flags, &mut handle) });
+        let mut txn = NativeTransaction::new_with_handle(handle, flags as usize, self);
+        txn.parent = parent_handle;
+        if !root.is_null() {
+            txn.root = root;
+        }
+        Ok(txn)
     }
 
     /// Creates a new read-write transaction
@@ -840,86 +2243,355 @@ impl Environment {
             .and_then(|txn| Ok(ReadonlyTransaction::new_with_native(txn)))
     }
 
-    fn _open_db(&self, db_name: & str, flags: DbFlags, force_creation: bool) -> MdbResult<ffi::MDB_dbi> {
-        debug!("Opening {} (create={}, read_only={})", db_name, force_creation, self.is_readonly);
-        // From LMDB docs for mdb_dbi_open:
-        //
-        // This function must not be called from multiple concurrent
-        // transactions. A transaction that uses this function must finish
-        // (either commit or abort) before any other transaction may use
-        // this function
-        match self.db_cache.lock() {
-            Err(_) => Err(MdbError::CacheError),
-            Ok(guard) => {
-                let ref cell = *guard;
-                let cache = cell.get();
-
-                unsafe {
-                    if let Some(db) = (*cache).get(db_name) {
-                        debug!("Cached value for {}: {}", db_name, *db);
-                        return Ok(*db);
-                    }
-                }
-
-                let mut txn = {
-                    let txflags = if self.is_readonly { ffi::MDB_RDONLY } else { 0 };
-                    try!(self.create_transaction(None, txflags))
-                };
-                let opt_name = if db_name.len() > 0 {Some(db_name)} else {None};
-                let flags = if force_creation {flags | DbCreate} else {flags - DbCreate};
-
-                let mut db: ffi::MDB_dbi = 0;
-                let db_res = match opt_name {
-                    None => unsafe { ffi::mdb_dbi_open(txn.handle, ptr::null(), flags.bits(), &mut db) },
-                    Some(db_name) => {
-                        let db_name = CString::new(db_name.as_bytes()).unwrap();
-                        unsafe {
-                            ffi::mdb_dbi_open(txn.handle, db_name.as_ptr(), flags.bits(), &mut db)
-                        }
-                    }
-                };
-
-                try_mdb!(db_res);
-                try!(txn.commit());
-
-                debug!("Caching: {} -> {}", db_name, db);
-                unsafe {
-                    (*cache).insert(db_name.to_owned(), db);
+    /// Read-modify-write which doesn't hold the writer lock while
+    /// computing. `read` runs in a read-only transaction, all values
+    /// it reads through `OptimisticReader` are remembered. Then a write
+    /// transaction is started and, unless nothing was committed since
+    /// the snapshot, those values are read again and compared byte by
+    /// byte. If they are unchanged `write` is called with the result
+    /// of `read` and transaction is committed, otherwise everything
+    /// is retried. Fails with `StateError` after 10 conflicting attempts.
+    pub fn optimistic_update<F, G, R, T>(&self, mut read: F, mut write: G) -> MdbResult<T>
+        where F: FnMut(&mut OptimisticReader) -> MdbResult<R>,
+              G: FnMut(&Transaction, R) -> MdbResult<T>
+    {
+        for _ in 0..OPTIMISTIC_UPDATE_ATTEMPTS {
+            // reader is finished before taking the writer lock
+            let (input, reads, snapshot) = {
+                let mut reader = OptimisticReader {
+                    txn: try!(self.get_reader()),
+                    reads: Vec::new(),
                 };
+                let input = try!(read(&mut reader));
+                let snapshot = unsafe { ffi::mdb_txn_id(reader.txn.inner.handle) };
+                (input, reader.reads, snapshot)
+            };
 
-                Ok(db)
+            let txn = try!(self.new_transaction());
+            let id = unsafe { ffi::mdb_txn_id(txn.inner.handle) };
+            if id != snapshot + 1 && !try!(OptimisticReader::unchanged(&txn, &reads)) {
+                debug!("optimistic update conflict, retrying");
+                continue;
             }
+            let res = try!(write(&txn, input));
+            try!(txn.commit());
+            return Ok(res);
         }
+        Err(StateError(format!("optimistic update conflicted {} times", OPTIMISTIC_UPDATE_ATTEMPTS)))
     }
 
-    /// Opens existing DB
-    pub fn get_db(& self, db_name: &str, flags: DbFlags) -> MdbResult<DbHandle> {
-        let db = try!(self._open_db(db_name, flags, false));
-        Ok(DbHandle {handle: db, flags: flags})
+    /// Returns a view handing out values which live as long as the
+    /// environment instead of a transaction. Available only for
+    /// environments opened with `EnvCreateReadOnly`, fails with
+    /// `StateError` otherwise.
+    ///
+    /// Other processes must not write to environment while slices
+    /// are in use, as LMDB can't prevent it.
+    pub fn read_only_mapped_view(&self) -> MdbResult<MappedView<'_>> {
+        if !self.is_readonly {
+            return Err(MdbError::StateError("mapped view requires environment opened with EnvCreateReadOnly".to_owned()));
+        }
+        Ok(MappedView { env: self })
     }
 
-    /// Opens or creates a DB
-    pub fn create_db(&self, db_name: &str, flags: DbFlags) -> MdbResult<DbHandle> {
-        let db = try!(self._open_db(db_name, flags, true));
-        Ok(DbHandle {handle: db, flags: flags})
+    /// Runs `f` within a new write transaction, which is committed if
+    /// `f` succeeds and aborted if it fails. Transaction is available
+    /// to `f` only by reference, so it can't be committed or aborted
+    /// prematurely.
+    pub fn with_write_txn<T, F>(&self, f: F) -> MdbResult<T>
+        where F: FnOnce(BoundTransaction) -> MdbResult<T>
+    {
+        let txn = try!(self.new_transaction());
+        let res = try!(f(BoundTransaction { txn: &txn }));
+        try!(txn.commit());
+        Ok(res)
     }
 
-    /// Opens default DB with specified flags
-    pub fn get_default_db(&self, flags: DbFlags) -> MdbResult<DbHandle> {
-        self.get_db("", flags)
+    /// Same as `with_write_txn`, but passes transaction itself. Result
+    /// of `f` is discarded if commit fails.
+    pub fn with_transaction<T, F>(&self, f: F) -> MdbResult<T>
+        where F: FnOnce(&mut Transaction) -> MdbResult<T>
+    {
+        let mut txn = try!(self.new_transaction());
+        let res = try!(f(&mut txn));
+        try!(txn.commit());
+        Ok(res)
     }
 
-    fn drop_db_from_cache(&self, handle: ffi::MDB_dbi) {
-        match self.db_cache.lock() {
-            Err(_) => (),
-            Ok(guard) => {
-                let ref cell = *guard;
+    /// Runs `f` within a new read-only transaction, which is aborted
+    /// afterwards regardless of result
+    pub fn with_reader<T, F>(&self, f: F) -> MdbResult<T>
+        where F: FnOnce(&ReadonlyTransaction) -> MdbResult<T>
+    {
+        let mut reader = try!(self.get_reader());
+        let res = f(&reader);
+        reader.abort();
+        res
+    }
 
-                unsafe {
-                    let cache = cell.get();
+    /// Applies `f` to every item, committing a separate write
+    /// transaction per `chunk` items, so huge mutations don't hit
+    /// `TxnFull` and don't block other writers for too long.
+    ///
+    /// Returns count of applied items. Processing stops at the first
+    /// error, the failed chunk is aborted and the error carries count
+    /// of items committed before. Map is never grown, use
+    /// [write_chunked_growing](#method.write_chunked_growing) to
+    /// retry chunks which hit `MDB_MAP_FULL`.
+    pub fn write_chunked<I, F>(&self, items: I, chunk: usize, mut f: F) -> Result<u64, ChunkedWriteError>
+        where I: IntoIterator, F: FnMut(&Transaction, I::Item) -> MdbResult<()>
+    {
+        try!(check_chunk_size(chunk));
+
+        let mut committed: u64 = 0;
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            let mut applied = 0;
+            let res = self.new_transaction().and_then(|txn| {
+                for item in items.by_ref().take(chunk) {
+                    try!(f(&txn, item));
+                    applied += 1;
+                }
+                txn.commit()
+            });
+            match res {
+                Ok(_) => committed += applied,
+                Err(e) => return Err(ChunkedWriteError {
+                    committed: committed,
+                    error: e,
+                }),
+            }
+        }
+        Ok(committed)
+    }
 
-                    let mut key = None;
-                    for (k, v) in (*cache).iter() {
+    /// Same as [write_chunked](#method.write_chunked), but a chunk
+    /// which runs into `MDB_MAP_FULL` is retried from its first item
+    /// after doubling map size, up to 4 times per chunk. `f` consumes
+    /// items, so each chunk is buffered and its items are cloned for
+    /// every attempt.
+    ///
+    /// # Safety
+    ///
+    /// Growing calls [set_mapsize](#method.set_mapsize), which remaps
+    /// the file. No other transaction, cursor or value borrowed from
+    /// the map may be alive in this process, in any thread, while
+    /// this runs.
+    pub unsafe fn write_chunked_growing<I, F>(&self, items: I, chunk: usize, mut f: F) -> Result<u64, ChunkedWriteError>
+        where I: IntoIterator, I::Item: Clone, F: FnMut(&Transaction, I::Item) -> MdbResult<()>
+    {
+        try!(check_chunk_size(chunk));
+
+        let mut committed: u64 = 0;
+        let mut items = items.into_iter();
+        loop {
+            let batch: Vec<I::Item> = items.by_ref().take(chunk).collect();
+            if batch.is_empty() {
+                return Ok(committed);
+            }
+
+            let mut grown = 0;
+            loop {
+                let res = self.new_transaction().and_then(|txn| {
+                    for item in batch.iter().cloned() {
+                        try!(f(&txn, item));
+                    }
+                    txn.commit()
+                });
+
+                let error = match res {
+                    Ok(_) => break,
+                    Err(ref e) if e.is_map_full() && grown < WRITE_CHUNKED_GROW_ATTEMPTS => {
+                        grown += 1;
+                        match self.grow_map() {
+                            Ok(_) => continue,
+                            Err(e) => e,
+                        }
+                    },
+                    Err(e) => e,
+                };
+                return Err(ChunkedWriteError {
+                    committed: committed,
+                    error: error,
+                });
+            }
+            committed += batch.len() as u64;
+        }
+    }
+
+    /// Doubles map size, there must be no active transactions
+    fn grow_map(&self) -> MdbResult<()> {
+        let info = try!(self.info());
+        debug!("growing map from {} bytes", info.me_mapsize);
+        self.set_mapsize(info.me_mapsize as usize * 2)
+    }
+
+    /// Looks up cached handle of `db_name`
+    fn cached_dbi(&self, db_name: &str) -> MdbResult<Option<ffi::MDB_dbi>> {
+        match self.db_cache.lock() {
+            Err(_) => Err(MdbError::CacheError),
+            Ok(guard) => {
+                let ref cell = *guard;
+                unsafe { Ok((*cell.get()).get(db_name).cloned()) }
+            }
+        }
+    }
+
+    /// Stores handle of a database opened by a committed transaction
+    fn cache_dbi(&self, db_name: String, db: ffi::MDB_dbi) -> MdbResult<()> {
+        debug!("Caching: {} -> {}", db_name, db);
+        match self.db_cache.lock() {
+            Err(_) => Err(MdbError::CacheError),
+            Ok(guard) => {
+                let ref cell = *guard;
+                unsafe {
+                    (*cell.get()).insert(db_name, db);
+                };
+                Ok(())
+            }
+        }
+    }
+
+    fn _open_db(&self, db_name: & str, flags: DbFlags, force_creation: bool) -> MdbResult<ffi::MDB_dbi> {
+        debug!("Opening {} (create={}, read_only={})", db_name, force_creation, self.is_readonly);
+        if let Some(db) = try!(self.cached_dbi(db_name)) {
+            debug!("Cached value for {}: {}", db_name, db);
+            return Ok(db);
+        }
+
+        // Transaction is started before taking `dbi_open_lock` (see
+        // `NativeTransaction::open_db`), as write transactions holding
+        // it wait for the lock, not the other way round. Cache lock is
+        // only held for lookups and doesn't slow down cache hits.
+        let mut txn = {
+            let txflags = if self.is_readonly { ffi::MDB_RDONLY } else { 0 };
+            try!(self.create_transaction(None, txflags))
+        };
+        let flags = if force_creation {flags | DbCreate} else {flags - DbCreate};
+        let db = try!(txn.open_db(db_name, flags));
+        // handle is cached once committed
+        try!(txn.commit());
+        Ok(db)
+    }
+
+    /// Opens existing DB
+    pub fn get_db(& self, db_name: &str, flags: DbFlags) -> MdbResult<DbHandle> {
+        let db = try!(self._open_db(db_name, flags, false));
+        Ok(DbHandle {handle: db, flags: flags, name: intern_db_name(db_name)})
+    }
+
+    /// Opens or creates a DB
+    pub fn create_db(&self, db_name: &str, flags: DbFlags) -> MdbResult<DbHandle> {
+        let db = try!(self._open_db(db_name, flags, true));
+        Ok(DbHandle {handle: db, flags: flags, name: intern_db_name(db_name)})
+    }
+
+    /// Opens default DB with specified flags
+    pub fn get_default_db(&self, flags: DbFlags) -> MdbResult<DbHandle> {
+        self.get_db("", flags)
+    }
+
+    /// Checks whether existing database `db_name` was created with
+    /// (at least) `expected_flags`, so it could be safely reopened with
+    /// them. Returns an error if database doesn't exist.
+    pub fn check_db_flags_compatible(&self, db_name: &str, expected_flags: DbFlags) -> MdbResult<bool> {
+        // opening without flags never changes existing db
+        let db = try!(self.get_db(db_name, DbFlags::empty()));
+        let reader = try!(self.get_reader());
+        let flags = try!(reader.inner.dbi_flags(db.handle));
+        let expected = (expected_flags - DbCreate).bits();
+        Ok(flags & expected == expected)
+    }
+
+    /// Discovers all named databases by walking keys of the main
+    /// database, opens them and returns their handles by name. Handles
+    /// are cached as usual, so it could be used for warming up.
+    ///
+    /// Keys which aren't databases or valid UTF-8 are skipped.
+    pub fn open_all_databases(&self) -> MdbResult<HashMap<String, DbHandle>> {
+        let names: Vec<String> = {
+            let main = try!(self.get_default_db(DbFlags::empty()));
+            let reader = try!(self.get_reader());
+            let db = reader.bind(&main);
+            let mut iter = try!(db.iter());
+            let names = iter.by_ref()
+                .filter_map(|cv| str::from_utf8(cv.get_key::<&[u8]>()).ok().map(|s| s.to_owned()))
+                .filter(|name| !name.is_empty())
+                .collect();
+            if let Some(e) = iter.take_error() {
+                return Err(e);
+            }
+            names
+        };
+
+        let mut handles = HashMap::new();
+        for name in names {
+            match self.get_db(&name, DbFlags::empty()) {
+                Ok(handle) => { handles.insert(name, handle); },
+                Err(MdbError::Other(ffi::MDB_INCOMPATIBLE, _)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        // handles were opened with empty flags, fill in actual ones
+        let reader = try!(self.get_reader());
+        for handle in handles.values_mut() {
+            let flags = try!(reader.inner.dbi_flags(handle.handle));
+            handle.flags = DbFlags::from_bits_truncate(flags);
+        }
+        Ok(handles)
+    }
+
+    /// Removes cached handle of database `db_name`, so the next
+    /// `get_db`/`create_db` opens it again. Useful when database
+    /// could have been dropped by another process.
+    pub fn invalidate_db_cache(&self, db_name: &str) {
+        match self.db_cache.lock() {
+            Err(_) => (),
+            Ok(guard) => {
+                let ref cell = *guard;
+                unsafe {
+                    (*cell.get()).remove(db_name);
+                }
+            }
+        }
+    }
+
+    /// Returns true if handle of database `db_name` is cached
+    #[cfg(test)]
+    pub fn is_db_cached(&self, db_name: &str) -> bool {
+        match self.db_cache.lock() {
+            Err(_) => false,
+            Ok(guard) => {
+                let ref cell = *guard;
+                unsafe { (*cell.get()).contains_key(db_name) }
+            }
+        }
+    }
+
+    /// Returns count of cached database handles, i.e. databases
+    /// opened so far, which helps to see how close `max_dbs` is
+    pub fn num_opened_databases(&self) -> usize {
+        match self.db_cache.lock() {
+            Err(_) => 0,
+            Ok(guard) => {
+                let ref cell = *guard;
+                unsafe { (*cell.get()).len() }
+            }
+        }
+    }
+
+    fn drop_db_from_cache(&self, handle: ffi::MDB_dbi) {
+        match self.db_cache.lock() {
+            Err(_) => (),
+            Ok(guard) => {
+                let ref cell = *guard;
+
+                unsafe {
+                    let cache = cell.get();
+
+                    let mut key = None;
+                    for (k, v) in (*cache).iter() {
                         if *v == handle {
                             key = Some(k);
                             break;
@@ -943,8 +2615,140 @@ impl Clone for Environment {
         Environment {
             env: self.env.clone(),
             db_cache: self.db_cache.clone(),
+            dbi_open_lock: self.dbi_open_lock.clone(),
             is_readonly: self.is_readonly,
+            is_no_tls: self.is_no_tls,
+            max_key_size: self.max_key_size,
+            open_stats: self.open_stats,
+        }
+    }
+}
+
+/// Collects text written with `write!` and stores it in database,
+/// see [writer](struct.Database.html#method.writer)
+#[derive(Debug)]
+pub struct DatabaseWriter<'d, 'a: 'd> {
+    db: &'d Database<'a>,
+    key: Vec<u8>,
+    buf: String,
+}
+
+impl<'d, 'a: 'd> DatabaseWriter<'d, 'a> {
+    /// Stores all text written so far as value of the key, replacing
+    /// the previous one. Text is kept, so further writes are appended
+    /// to it on the next flush.
+    pub fn flush(&mut self) -> MdbResult<()> {
+        self.db.set(&self.key, &self.buf)
+    }
+}
+
+impl<'d, 'a: 'd> fmt::Write for DatabaseWriter<'d, 'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.buf.push_str(s);
+        Ok(())
+    }
+}
+
+/// Collects bytes and stores them in database on `flush`, see
+/// [blob_writer](struct.Database.html#method.blob_writer)
+#[derive(Debug)]
+pub struct DatabaseBlobWriter<'d, 'a: 'd> {
+    db: &'d Database<'a>,
+    key: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl<'d, 'a: 'd> io::Write for DatabaseBlobWriter<'d, 'a> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    /// Stores all bytes written so far as value of the key, replacing
+    /// the previous one. Bytes are kept, so further writes are
+    /// appended to them on the next flush.
+    fn flush(&mut self) -> io::Result<()> {
+        self.db.set(&self.key, &self.buf).map_err(io::Error::from)
+    }
+}
+
+/// Reads value stored in database as a stream, see
+/// [blob_reader](struct.Database.html#method.blob_reader)
+#[derive(Debug)]
+pub struct DatabaseBlobReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DatabaseBlobReader<'a> {
+    /// Returns count of bytes which weren't read yet
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+}
+
+impl<'a> io::Read for DatabaseBlobReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(buf.len(), self.remaining());
+        buf[..n].copy_from_slice(&self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+const OPTIMISTIC_UPDATE_ATTEMPTS: usize = 10;
+
+/// Reads of the first phase of
+/// [optimistic_update](struct.Environment.html#method.optimistic_update)
+#[derive(Debug)]
+pub struct OptimisticReader<'a> {
+    txn: ReadonlyTransaction<'a>,
+    reads: Vec<(DbHandle, Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl<'a> OptimisticReader<'a> {
+    /// Retrieves a copy of value by key, `None` if key is missing. In
+    /// case of DbAllowDups it is the first value
+    pub fn get(&mut self, db: &DbHandle, key: &ToMdbValue) -> MdbResult<Option<Vec<u8>>> {
+        let value = match self.txn.bind(db).get::<&[u8]>(key) {
+            Ok(value) => Some(value.to_vec()),
+            Err(NotFound) => None,
+            Err(e) => return Err(e),
+        };
+        self.reads.push((*db, key.to_mdb_value().as_slice().to_vec(), value.clone()));
+        Ok(value)
+    }
+
+    fn unchanged(txn: &Transaction, reads: &[(DbHandle, Vec<u8>, Option<Vec<u8>>)]) -> MdbResult<bool> {
+        for &(ref db, ref key, ref expected) in reads {
+            let current = match txn.bind(db).get::<&[u8]>(key) {
+                Ok(value) => Some(value),
+                Err(NotFound) => None,
+                Err(e) => return Err(e),
+            };
+            if current != expected.as_ref().map(|v| &v[..]) {
+                return Ok(false);
+            }
         }
+        Ok(true)
+    }
+}
+
+/// Read-only access to values in the memory map, see
+/// [read_only_mapped_view](struct.Environment.html#method.read_only_mapped_view)
+#[derive(Copy, Clone, Debug)]
+pub struct MappedView<'env> {
+    env: &'env Environment,
+}
+
+impl<'env> MappedView<'env> {
+    /// Retrieves a value by key, the slice stays valid after the
+    /// internal reader is finished
+    pub fn get(&self, db: &DbHandle, key: &ToMdbValue) -> MdbResult<&'env [u8]> {
+        let reader = try!(self.env.get_reader());
+        let db = reader.bind(db);
+        // environment is read-only, so pages can't be reused by this process
+        unsafe { db.get_mapped(self.env, key) }
     }
 }
 
@@ -957,7 +2761,47 @@ impl Clone for Environment {
 /// can ask to drop it.
 pub struct DbHandle {
     handle: ffi::MDB_dbi,
-    flags: DbFlags
+    flags: DbFlags,
+    name: &'static str,
+}
+
+impl DbHandle {
+    /// Name database was opened with, empty for the default one.
+    ///
+    /// Names are interned for the whole process so handles stay
+    /// `Copy`: memory of every distinct name ever opened is never
+    /// freed, which matters only if names are generated unboundedly.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Flags database was opened with
+    pub fn flags(&self) -> DbFlags {
+        self.flags
+    }
+}
+
+/// Returns a copy of `name` living as long as the process, so that
+/// handles carrying it stay `Copy`. Each distinct name is leaked once.
+fn intern_db_name(name: &str) -> &'static str {
+    static NAMES: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+    let mut guard = NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    let names = guard.get_or_insert_with(HashSet::new);
+    if let Some(&interned) = names.get(name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(interned);
+    interned
+}
+
+/// Human readable database reference for error messages
+pub(crate) fn describe_db(name: &str) -> String {
+    if name.is_empty() {
+        "default database".to_owned()
+    } else {
+        format!("database '{}'", name)
+    }
 }
 
 unsafe impl Sync for DbHandle {}
@@ -970,12 +2814,70 @@ enum TransactionState {
     Invalid,  // Invalid, no further operation possible
 }
 
+/// Progress reported by
+/// [copy_to_path_with_progress](struct.Environment.html#method.copy_to_path_with_progress)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BackupProgress {
+    /// Size of the destination file
+    pub bytes_written: u64,
+    /// Approximate size of the complete copy, if known
+    pub expected_bytes: Option<u64>,
+}
+
+/// Result of [reader_lag](struct.Environment.html#method.reader_lag)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReaderLag {
+    /// Snapshot txn id of the oldest active reader, if any
+    pub oldest_reader_txnid: Option<u64>,
+    /// Id of the last committed transaction
+    pub latest_txnid: u64,
+    /// Difference between the two, 0 if there are no readers
+    pub lag: u64,
+}
+
+extern "C" fn collect_reader_line(msg: *const c_char, ctx: *const c_void) -> c_int {
+    unsafe {
+        let lines = &mut *(ctx as *mut Vec<String>);
+        let line = CStr::from_ptr(msg).to_string_lossy();
+        lines.push(line.trim_end().to_owned());
+    }
+    0
+}
+
+/// Statistics of a committed transaction, see
+/// [commit_with_stats](struct.Transaction.html#method.commit_with_stats)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommitStats {
+    /// Number of successful puts, including cursor puts
+    pub puts: usize,
+    /// Number of successful deletes, including cursor deletes
+    pub deletes: usize,
+    /// Total size of keys and values passed to successful puts
+    pub bytes_written: usize,
+    /// Time spent inside `mdb_txn_commit`
+    pub commit_duration: Duration,
+    /// Id of the committed transaction
+    pub txn_id: usize,
+}
+
 #[derive(Debug)]
 struct NativeTransaction<'a> {
     handle: *mut ffi::MDB_txn,
+    parent: *mut ffi::MDB_txn,
+    // root of nested transactions, handle itself for top level one
+    root: *mut ffi::MDB_txn,
+    // set once database is opened, only tracked for read-only ones
+    opened_dbs: Cell<bool>,
+    // stats of parent, updated once nested transaction is committed
+    parent_stats: Option<&'a Cell<CommitStats>>,
     env: &'a Environment,
     flags: usize,
     state: TransactionState,
+    stats: Cell<CommitStats>,
+    // count of open cursors per db, LMDB doesn't expose it. Locked as
+    // SendCursor may be dropped on another thread
+    #[cfg(debug_assertions)]
+    open_cursors: Mutex<HashMap<ffi::MDB_dbi, usize>>,
 }
 
 impl<'a> NativeTransaction<'a> {
@@ -983,26 +2885,118 @@ impl<'a> NativeTransaction<'a> {
         // debug!("new native txn");
         NativeTransaction {
             handle: h,
+            parent: ptr::null_mut(),
+            root: h,
+            opened_dbs: Cell::new(false),
+            parent_stats: None,
             flags: flags,
             state: TransactionState::Normal,
             env: env,
+            stats: Cell::new(CommitStats::default()),
+            #[cfg(debug_assertions)]
+            open_cursors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Describes transaction state, handles and open cursors.
+    /// Doesn't touch LMDB, so it is safe to call in any state.
+    #[cfg(debug_assertions)]
+    fn debug_info(&self) -> String {
+        use std::fmt::Write;
+
+        let mut info = String::new();
+        let _ = writeln!(info, "lmdb-rs transaction {:p}: state {:?}, {}", self.handle, self.state,
+                         if self.is_readonly() { "read-only" } else { "read-write" });
+        let _ = writeln!(info, "  env: {:p}", self.env.env.0);
+        if !self.parent.is_null() {
+            let _ = writeln!(info, "  parent: {:p}", self.parent);
+        }
+        let cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let mut dbs: Vec<_> = cursors.iter().filter(|&(_, &count)| count > 0).collect();
+        dbs.sort();
+        if dbs.is_empty() {
+            let _ = writeln!(info, "  no open cursors");
+        }
+        for (db, count) in dbs {
+            let _ = writeln!(info, "  db {}: {} open cursor(s)", db, count);
+        }
+        info
+    }
+
+    #[cfg(debug_assertions)]
+    fn track_cursor(&self, db: ffi::MDB_dbi, opened: bool) {
+        let mut cursors = self.open_cursors.lock().unwrap_or_else(|e| e.into_inner());
+        let count = cursors.entry(db).or_insert(0);
+        if opened {
+            *count += 1;
+        } else {
+            *count = count.saturating_sub(1);
         }
     }
 
+    #[cfg(not(debug_assertions))]
+    fn track_cursor(&self, _db: ffi::MDB_dbi, _opened: bool) {
+    }
+
     fn is_readonly(&self) -> bool {
         (self.flags as u32 & ffi::MDB_RDONLY) == ffi::MDB_RDONLY
     }
 
     fn commit(&mut self) -> MdbResult<()> {
+        self.commit_with_stats().map(|_| ())
+    }
+
+    fn commit_with_stats(&mut self) -> MdbResult<CommitStats> {
         assert_state_eq!(txn, self.state, TransactionState::Normal);
         debug!("commit txn");
+        let mut stats = self.stats.get();
+        // Id has to be captured before commit as handle is freed by it
+        stats.txn_id = unsafe { ffi::mdb_txn_id(self.handle) } as usize;
         self.state = if self.is_readonly() {
             TransactionState::Released
         } else {
             TransactionState::Invalid
         };
-        try_mdb!(unsafe { ffi::mdb_txn_commit(self.handle) } );
-        Ok(())
+        let started = Instant::now();
+        let res = unsafe { ffi::mdb_txn_commit(self.handle) };
+        stats.commit_duration = started.elapsed();
+        // failed commit aborts transaction
+        self.finish_dbi_opens(res == ffi::MDB_SUCCESS);
+        try_mdb!(res);
+        // changes of nested transaction become part of parent
+        if let Some(parent) = self.parent_stats {
+            let mut total = parent.get();
+            total.puts += stats.puts;
+            total.deletes += stats.deletes;
+            total.bytes_written += stats.bytes_written;
+            parent.set(total);
+        }
+        Ok(stats)
+    }
+
+    /// Commits transaction and begins a fresh one with the same flags
+    /// in its place, returns id of the committed transaction
+    fn commit_and_renew(&mut self) -> MdbResult<u64> {
+        if !self.parent.is_null() {
+            return Err(StateError("nested transaction can't be checkpointed".to_owned()));
+        }
+        let stats = try!(self.commit_with_stats());
+        let env = self.env;
+        *self = try!(env.create_transaction(None, self.flags as c_uint));
+        Ok(stats.txn_id as u64)
+    }
+
+    fn record_put(&self, key_size: usize, data_size: usize) {
+        let mut stats = self.stats.get();
+        stats.puts += 1;
+        stats.bytes_written += key_size + data_size;
+        self.stats.set(stats);
+    }
+
+    fn record_del(&self) {
+        let mut stats = self.stats.get();
+        stats.deletes += 1;
+        self.stats.set(stats);
     }
 
     fn abort(&mut self) {
@@ -1011,6 +3005,7 @@ impl<'a> NativeTransaction<'a> {
         } else {
             debug!("abort txn");
             unsafe { ffi::mdb_txn_abort(self.handle); }
+            self.finish_dbi_opens(false);
             self.state = if self.is_readonly() {
                 TransactionState::Released
             } else {
@@ -1041,7 +3036,11 @@ impl<'a> NativeTransaction<'a> {
     fn new_child(&self, flags: c_uint) -> MdbResult<NativeTransaction> {
         let mut out: *mut ffi::MDB_txn = ptr::null_mut();
         try_mdb!(unsafe { ffi::mdb_txn_begin(ffi::mdb_txn_env(self.handle), self.handle, flags, &mut out) });
-        Ok(NativeTransaction::new_with_handle(out, flags as usize, self.env))
+        let mut txn = NativeTransaction::new_with_handle(out, flags as usize, self.env);
+        txn.parent = self.handle;
+        txn.root = self.root;
+        txn.parent_stats = Some(&self.stats);
+        Ok(txn)
     }
 
     /// Used in Drop to switch state
@@ -1049,6 +3048,7 @@ impl<'a> NativeTransaction<'a> {
         if self.state == TransactionState::Normal {
             debug!("silent abort");
             unsafe {ffi::mdb_txn_abort(self.handle);}
+            self.finish_dbi_opens(false);
             self.state = TransactionState::Invalid;
         }
     }
@@ -1071,12 +3071,35 @@ impl<'a> NativeTransaction<'a> {
         self.set_value_with_flags(db, key, value, 0)
     }
 
+    /// Checks key size and, for DbAllowDups, value size, unless
+    /// disabled for environment
+    fn check_put_sizes(&self, db: ffi::MDB_dbi, key_size: usize, value_size: Option<usize>) -> MdbResult<()> {
+        let max = match self.env.max_key_size {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        if key_size == 0 || key_size > max {
+            return Err(MdbError::BadValSize { what: KeyOrValue::Key, size: key_size, max: max });
+        }
+        if let Some(value_size) = value_size {
+            // duplicates are stored as keys of a sub-database, so
+            // flags are only looked up for suspiciously large values
+            if value_size > max && try!(self.dbi_flags(db)) & ffi::MDB_DUPSORT != 0 {
+                return Err(MdbError::BadValSize { what: KeyOrValue::Value, size: value_size, max: max });
+            }
+        }
+        Ok(())
+    }
+
     fn set_value_with_flags(&self, db: ffi::MDB_dbi, key: &ToMdbValue, value: &ToMdbValue, flags: c_uint) -> MdbResult<()> {
         unsafe {
             let mut key_val = key.to_mdb_value();
             let mut data_val = value.to_mdb_value();
+            try!(self.check_put_sizes(db, key_val.len(), Some(data_val.len())));
 
-            lift_mdb!(ffi::mdb_put(self.handle, db, &mut key_val.value, &mut data_val.value, flags))
+            try_mdb!(ffi::mdb_put(self.handle, db, &mut key_val.value, &mut data_val.value, flags));
+            self.record_put(key_val.len(), data_val.len());
+            Ok(())
         }
     }
 
@@ -1099,6 +3122,21 @@ impl<'a> NativeTransaction<'a> {
         self.set_value_with_flags(db, key, value, ffi::MDB_APPENDDUP)
     }
 
+    /// Reserves space of `size` bytes for value of key and returns
+    /// pointer to it, caller is responsible for filling it
+    fn reserve_value(&self, db: ffi::MDB_dbi, key: &ToMdbValue, size: usize) -> MdbResult<*mut u8> {
+        assert_state_eq!(txn, self.state, TransactionState::Normal);
+        unsafe {
+            let mut key_val = key.to_mdb_value();
+            let mut data_val: MdbValue = MdbValue::new(ptr::null(), size);
+            try!(self.check_put_sizes(db, key_val.len(), Some(size)));
+
+            try_mdb!(ffi::mdb_put(self.handle, db, &mut key_val.value, &mut data_val.value, ffi::MDB_RESERVE));
+            self.record_put(key_val.len(), size);
+            Ok(data_val.value.mv_data as *mut u8)
+        }
+    }
+
     /// Set the value for key only if the key does not exist in the database,
     /// even if the database supports duplicates.
     fn insert(&self, db: ffi::MDB_dbi, key: &ToMdbValue, value: &ToMdbValue) -> MdbResult<()> {
@@ -1110,7 +3148,9 @@ impl<'a> NativeTransaction<'a> {
     fn del_value(&self, db: ffi::MDB_dbi, key: &ToMdbValue) -> MdbResult<()> {
         unsafe {
             let mut key_val = key.to_mdb_value();
-            lift_mdb!(ffi::mdb_del(self.handle, db, &mut key_val.value, ptr::null_mut()))
+            try_mdb!(ffi::mdb_del(self.handle, db, &mut key_val.value, ptr::null_mut()));
+            self.record_del();
+            Ok(())
         }
     }
 
@@ -1121,7 +3161,9 @@ impl<'a> NativeTransaction<'a> {
             let mut key_val = key.to_mdb_value();
             let mut data_val = data.to_mdb_value();
 
-            lift_mdb!(ffi::mdb_del(self.handle, db, &mut key_val.value, &mut data_val.value))
+            try_mdb!(ffi::mdb_del(self.handle, db, &mut key_val.value, &mut data_val.value));
+            self.record_del();
+            Ok(())
         }
     }
 
@@ -1153,12 +3195,69 @@ impl<'a> NativeTransaction<'a> {
         }
     }
 
+    /// Opens a database within this transaction instead of a separate
+    /// one, going through cache and `dbi_open_lock` of environment
+    fn open_db(&self, name: &str, flags: DbFlags) -> MdbResult<ffi::MDB_dbi> {
+        if let Some(db) = try!(self.env.cached_dbi(name)) {
+            return Ok(db);
+        }
+        let lock = &self.env.dbi_open_lock;
+        try!(lock.acquire(self.root as usize));
+        self.opened_dbs.set(true);
+        // might have been opened while waiting for the lock
+        if let Some(db) = try!(self.env.cached_dbi(name)) {
+            return Ok(db);
+        }
+        let db = try!(self.open_dbi(name, flags));
+        try!(lock.record(self.handle as usize, name, db));
+        Ok(db)
+    }
+
+    /// Publishes databases opened by top level transaction to cache of
+    /// environment once it is committed, nested ones pass them to parent
+    fn finish_dbi_opens(&self, committed: bool) {
+        // read-only transactions open databases only on their own
+        if self.is_readonly() && !self.opened_dbs.get() {
+            return;
+        }
+        let lock = &self.env.dbi_open_lock;
+        if !self.parent.is_null() {
+            lock.child_finished(self.handle as usize, self.parent as usize, committed);
+            return;
+        }
+        for (name, db) in lock.release(self.handle as usize) {
+            if committed {
+                let _ = self.env.cache_dbi(name, db);
+            }
+        }
+    }
+
+    fn open_dbi(&self, name: &str, flags: DbFlags) -> MdbResult<ffi::MDB_dbi> {
+        assert_state_eq!(txn, self.state, TransactionState::Normal);
+        debug!("Opening {} in txn", name);
+        let mut db: ffi::MDB_dbi = 0;
+        let db_res = if name.len() > 0 {
+            let c_name = try!(CString::new(name.as_bytes())
+                              .map_err(|_| StateError(format!("invalid database name {:?}", name))));
+            unsafe { ffi::mdb_dbi_open(self.handle, c_name.as_ptr(), flags.bits(), &mut db) }
+        } else {
+            unsafe { ffi::mdb_dbi_open(self.handle, ptr::null(), flags.bits(), &mut db) }
+        };
+        lift_mdb!(db_res, db)
+    }
+
     /// Retrieves provided database's statistics
     fn stat(&self, db: ffi::MDB_dbi) -> MdbResult<ffi::MDB_stat> {
         let mut tmp: ffi::MDB_stat = unsafe { std::mem::zeroed() };
         lift_mdb!(unsafe { ffi::mdb_stat(self.handle, db, &mut tmp)}, tmp)
     }
 
+    /// Retrieves flags database was created with
+    fn dbi_flags(&self, db: ffi::MDB_dbi) -> MdbResult<c_uint> {
+        let mut flags: c_uint = 0;
+        lift_mdb!(unsafe { ffi::mdb_dbi_flags(self.handle, db, &mut flags) }, flags)
+    }
+
     /*
     fn get_db(&self, name: &str, flags: DbFlags) -> MdbResult<Database> {
         self.env.get_db(name, flags)
@@ -1180,11 +3279,59 @@ impl<'a> Drop for NativeTransaction<'a> {
     }
 }
 
+/// Tuple of handles which could be bound at once,
+/// see [bind_tuple](struct.Transaction.html#method.bind_tuple)
+pub trait BindHandles<'t> {
+    type Output;
+
+    fn bind_with(self, txn: &'t Transaction<'t>) -> Self::Output;
+}
+
+macro_rules! bind_handles_for_tuple {
+    ($($h:ident),+) => (
+        impl<'t, 'h> BindHandles<'t> for ($(bind_handles_for_tuple!(@handle $h),)+) {
+            type Output = ($(bind_handles_for_tuple!(@db $h),)+);
+
+            fn bind_with(self, txn: &'t Transaction<'t>) -> Self::Output {
+                let ($($h,)+) = self;
+                ($(txn.bind($h),)+)
+            }
+        }
+    );
+    (@handle $h:ident) => (&'h DbHandle);
+    (@db $h:ident) => (Database<'t>);
+}
+
+bind_handles_for_tuple!(a, b);
+bind_handles_for_tuple!(a, b, c);
+bind_handles_for_tuple!(a, b, c, d);
+
+/// Binds db handle to transaction of either kind
+fn bind_db<'a, M>(txn: &'a NativeTransaction<'a>, db_handle: &DbHandle) -> Database<'a, M> {
+    Database::new_with_handle(db_handle.handle, db_handle.name, txn)
+}
+
 #[derive(Debug)]
 pub struct Transaction<'a> {
     inner: NativeTransaction<'a>,
 }
 
+/// Write transaction managed by
+/// [with_write_txn](struct.Environment.html#method.with_write_txn),
+/// provides all `Transaction` operations except commit and abort
+#[derive(Copy, Clone, Debug)]
+pub struct BoundTransaction<'t> {
+    txn: &'t Transaction<'t>,
+}
+
+impl<'t> Deref for BoundTransaction<'t> {
+    type Target = Transaction<'t>;
+
+    fn deref(&self) -> &Transaction<'t> {
+        self.txn
+    }
+}
+
 impl<'a> Transaction<'a> {
     fn new_with_native(txn: NativeTransaction<'a>) -> Transaction<'a> {
         Transaction {
@@ -1209,17 +3356,104 @@ impl<'a> Transaction<'a> {
         t.inner.commit()
     }
 
+    /// Commits transaction like `commit` and returns statistics
+    /// about operations performed in it, including ones of committed
+    /// nested transactions
+    pub fn commit_with_stats(self) -> MdbResult<CommitStats> {
+        let mut t = self;
+        t.inner.commit_with_stats()
+    }
+
+    /// Commits transaction and begins a new write transaction on the
+    /// same environment, useful for bounding transaction size in long
+    /// write streams. Databases have to be bound again.
+    ///
+    /// Commit errors are returned as is, while failure to begin the
+    /// new transaction (after a successful commit) is decorated with
+    /// `"begin after commit"` context.
+    pub fn commit_and_continue(self) -> MdbResult<Transaction<'a>> {
+        if !self.inner.parent.is_null() {
+            return Err(StateError("nested transaction can't be continued".to_owned()));
+        }
+        let mut t = self;
+        let env = t.inner.env;
+        try!(t.inner.commit());
+        env.new_transaction()
+            .map_err(|e| e.with_context("begin after commit"))
+    }
+
+    /// Commits all changes made so far, forces environment sync to
+    /// disk and returns id of the committed transaction.
+    ///
+    /// LMDB can't commit a transaction without finishing it, so a new
+    /// write transaction takes its place and `self` stays usable for
+    /// further writes. Exclusive borrow ensures no databases or
+    /// cursors bound to the finished transaction are alive.
+    ///
+    /// Not available for nested transactions, as their commit isn't
+    /// durable until parent is committed.
+    pub fn checkpoint(&mut self) -> MdbResult<u64> {
+        let txn_id = try!(self.inner.commit_and_renew());
+        try!(self.inner.env.sync(true));
+        Ok(txn_id)
+    }
+
     /// Aborts transaction, moves it out
     pub fn abort(self) {
         let mut t = self;
         t.inner.abort();
     }
 
-    pub fn bind(&self, db_handle: &DbHandle) -> Database {
-        Database::new_with_handle(db_handle.handle, &self.inner)
+    /// Describes transaction state and open cursors, useful for
+    /// debugging stuck transactions and cursor leaks
+    #[cfg(debug_assertions)]
+    pub fn debug_info(&self) -> String {
+        self.inner.debug_info()
     }
-}
 
+    /// Prints `debug_info` to stderr
+    #[cfg(debug_assertions)]
+    pub fn print_debug_info(&self) {
+        eprint!("{}", self.debug_info())
+    }
+
+    pub fn bind(&self, db_handle: &DbHandle) -> Database {
+        bind_db(self.native(), db_handle)
+    }
+
+    /// Binds several handles at once
+    pub fn bind_many<const N: usize>(&self, handles: [&DbHandle; N]) -> [Database<'_>; N] {
+        handles.map(|h| self.bind(h))
+    }
+
+    /// Binds a tuple of handles (up to 4) at once, which is easier
+    /// to destructure into differently named databases
+    pub fn bind_tuple<'t, T: BindHandles<'t>>(&'t self, handles: T) -> T::Output {
+        handles.bind_with(self)
+    }
+
+    /// Opens or creates a DB directly in this transaction and binds it.
+    ///
+    /// Unlike `Environment::create_db` it doesn't use a separate
+    /// internal transaction, so it is safe to use within nested
+    /// transactions. Handles cached by environment are reused. A newly
+    /// opened handle is cached and becomes available to other
+    /// transactions only after the top level transaction is committed,
+    /// and it is closed if the transaction is aborted. Meanwhile
+    /// opening databases in other transactions waits for it to finish.
+    pub fn open_db(&self, name: &str, flags: DbFlags) -> MdbResult<Database<'_>> {
+        let db = try!(self.inner.open_db(name, flags | DbCreate));
+        Ok(Database::new_with_handle(db, intern_db_name(name), &self.inner))
+    }
+}
+
+
+impl<'a> Transaction<'a> {
+    /// Underlying transaction, for helpers shared by both kinds
+    fn native(&self) -> &NativeTransaction<'a> {
+        &self.inner
+    }
+}
 
 #[derive(Debug)]
 pub struct ReadonlyTransaction<'a> {
@@ -1246,6 +3480,19 @@ impl<'a> ReadonlyTransaction<'a> {
         self.inner.abort();
     }
 
+    /// Describes transaction state and open cursors, callable in
+    /// any state
+    #[cfg(debug_assertions)]
+    pub fn debug_info(&self) -> String {
+        self.inner.debug_info()
+    }
+
+    /// Prints `debug_info` to stderr
+    #[cfg(debug_assertions)]
+    pub fn print_debug_info(&self) {
+        eprint!("{}", self.debug_info())
+    }
+
     /// Resets read only transaction, handle is kept. Must be followed
     /// by call to `renew`
     pub fn reset(&mut self) {
@@ -1258,8 +3505,24 @@ impl<'a> ReadonlyTransaction<'a> {
         self.inner.renew()
     }
 
-    pub fn bind(&self, db_handle: &DbHandle) -> Database {
-        Database::new_with_handle(db_handle.handle, &self.inner)
+    /// Moves transaction to the latest committed snapshot, i.e. does
+    /// `reset` followed by `renew`. Reader slot is released for a
+    /// moment, so writers can reuse pages held by the old snapshot;
+    /// long-lived readers should call it regularly.
+    pub fn refresh(&mut self) -> MdbResult<()> {
+        self.reset();
+        self.renew()
+    }
+
+    pub fn bind(&self, db_handle: &DbHandle) -> ReadonlyDatabase<'_> {
+        bind_db(self.native(), db_handle)
+    }
+}
+
+impl<'a> ReadonlyTransaction<'a> {
+    /// Underlying transaction, for helpers shared by both kinds
+    fn native(&self) -> &NativeTransaction<'a> {
+        &self.inner
     }
 }
 
@@ -1289,21 +3552,25 @@ impl IsLess for MdbResult<Ordering> {
 }
 
 #[derive(Debug)]
-pub struct Cursor<'txn> {
+pub struct Cursor<'txn, M = ReadWrite> {
     handle: *mut ffi::MDB_cursor,
     data_val: ffi::MDB_val,
     key_val: ffi::MDB_val,
     txn: &'txn NativeTransaction<'txn>,
     db: ffi::MDB_dbi,
     valid_key: bool,
+    // error code of the last failed navigation except MDB_NOTFOUND
+    last_err: c_int,
+    mode: ::std::marker::PhantomData<M>,
 }
 
 
-impl<'txn> Cursor<'txn> {
-    fn new(txn: &'txn NativeTransaction, db: ffi::MDB_dbi) -> MdbResult<Cursor<'txn>> {
+impl<'txn, M> Cursor<'txn, M> {
+    fn new(txn: &'txn NativeTransaction, db: ffi::MDB_dbi) -> MdbResult<Cursor<'txn, M>> {
         debug!("Opening cursor in {}", db);
         let mut tmp: *mut ffi::MDB_cursor = std::ptr::null_mut();
         try_mdb!(unsafe { ffi::mdb_cursor_open(txn.handle, db, &mut tmp) });
+        txn.track_cursor(db, true);
         Ok(Cursor {
             handle: tmp,
             data_val: unsafe { std::mem::zeroed() },
@@ -1311,27 +3578,69 @@ impl<'txn> Cursor<'txn> {
             txn: txn,
             db: db,
             valid_key: false,
+            last_err: 0,
+            mode: ::std::marker::PhantomData,
         })
     }
 
+    /// Changes mode of cursor, it's up to caller to ensure
+    /// it isn't used for writes in read-only transaction
+    fn into_mode<N>(self) -> Cursor<'txn, N> {
+        let cursor = Cursor {
+            handle: self.handle,
+            data_val: self.data_val,
+            key_val: self.key_val,
+            txn: self.txn,
+            db: self.db,
+            valid_key: self.valid_key,
+            last_err: self.last_err,
+            mode: ::std::marker::PhantomData,
+        };
+        // handle is owned by the new cursor now
+        mem::forget(self);
+        cursor
+    }
+
     fn navigate(&mut self, op: ffi::MDB_cursor_op) -> MdbResult<()> {
         self.valid_key = false;
+        self.last_err = 0;
 
         let res = unsafe {
             ffi::mdb_cursor_get(self.handle, &mut self.key_val, &mut self.data_val, op)
         };
         match res {
             ffi::MDB_SUCCESS => {
-                // MDB_SET is the only cursor operation which doesn't
-                // writes back a new value. In this case any access to
-                // cursor key value should cause a cursor retrieval
-                // to get back pointer to database owned memory instead
-                // of value used to set the cursor as it might be
-                // already destroyed and there is no need to borrow it
-                self.valid_key = op != ffi::MDB_cursor_op::MDB_SET;
+                // MDB_SET doesn't write back a new key value and
+                // *_MULTIPLE ones don't touch key at all. In this
+                // case any access to cursor key value should cause a
+                // cursor retrieval to get back pointer to database
+                // owned memory instead of value used to set the
+                // cursor as it might be already destroyed and there
+                // is no need to borrow it
+                self.valid_key = match op {
+                    ffi::MDB_cursor_op::MDB_SET |
+                    ffi::MDB_cursor_op::MDB_GET_MULTIPLE |
+                    ffi::MDB_cursor_op::MDB_NEXT_MULTIPLE |
+                    ffi::MDB_cursor_op::MDB_PREV_MULTIPLE => false,
+                    _ => true,
+                };
                 Ok(())
             },
-            e => Err(MdbError::new_with_code(e))
+            e => {
+                if e != ffi::MDB_NOTFOUND {
+                    self.last_err = e;
+                }
+                Err(MdbError::new_with_code(e))
+            }
+        }
+    }
+
+    /// Returns error of the last navigation if it failed for
+    /// any reason other than reaching the end of data
+    fn take_last_error(&mut self) -> Option<MdbError> {
+        match mem::replace(&mut self.last_err, 0) {
+            0 => None,
+            e => Some(MdbError::new_with_code(e)),
         }
     }
 
@@ -1367,6 +3676,28 @@ impl<'txn> Cursor<'txn> {
         self.move_to(key, None::<&MdbValue<'k>>, ffi::MDB_cursor_op::MDB_SET_RANGE)
     }
 
+    /// Moves cursor to first entry for key greater than or equal to key
+    /// like `to_gte_key` does, but also tells whether exact key was
+    /// found (`true`) or cursor is positioned at the next one (`false`)
+    pub fn to_key_or_next<K: ToMdbValue>(&mut self, key: &K) -> MdbResult<bool> {
+        try!(self.to_gte_key(key));
+        let ord = try!(self.cmp_key(&key.to_mdb_value()));
+        Ok(ord == Ordering::Equal)
+    }
+
+    /// Moves cursor to the first key which doesn't start with
+    /// `prefix` and is greater than it, i.e. skips all keys with
+    /// `prefix`. Fails with `NotFound` if there is no such key,
+    /// including the case of prefix consisting only of `0xFF` bytes.
+    ///
+    /// Assumes default lexicographic key ordering.
+    pub fn to_after_prefix(&mut self, prefix: &[u8]) -> MdbResult<()> {
+        match prefix_successor(prefix) {
+            Some(successor) => self.to_gte_key(&successor),
+            None => Err(NotFound),
+        }
+    }
+
     /// Moves cursor to specific item (for example, if cursor
     /// already points to a correct key and you need to delete
     /// a specific item through cursor)
@@ -1411,6 +3742,46 @@ impl<'txn> Cursor<'txn> {
         self.navigate(ffi::MDB_cursor_op::MDB_LAST_DUP)
     }
 
+    /// Returns the whole page of items of current key which cursor
+    /// is positioned at, as raw bytes of fixed-size values. Requires
+    /// DbDupFixed. Items are ordered as in database.
+    ///
+    /// Page is valid only until cursor is used again:
+    ///
+    /// ```compile_fail
+    /// # use lmdb_rs::core::{EnvBuilder, DbAllowDups, DbDupFixed};
+    /// # let env = EnvBuilder::new().open("unused", 0o777).unwrap();
+    /// # let handle = env.get_default_db(DbAllowDups | DbDupFixed).unwrap();
+    /// let txn = env.new_transaction().unwrap();
+    /// let db = txn.bind(&handle);
+    /// let mut cursor = db.new_cursor().unwrap();
+    /// let page = cursor.get_multiple().unwrap();
+    /// cursor.del_all().unwrap();
+    /// println!("{:?}", page);
+    /// ```
+    pub fn get_multiple(&mut self) -> MdbResult<&[u8]> {
+        self.multiple_slice(ffi::MDB_cursor_op::MDB_GET_MULTIPLE)
+    }
+
+    /// Moves cursor to the next page of items of current key and
+    /// returns it, see `get_multiple`
+    pub fn next_multiple(&mut self) -> MdbResult<&[u8]> {
+        self.multiple_slice(ffi::MDB_cursor_op::MDB_NEXT_MULTIPLE)
+    }
+
+    /// Moves cursor to the previous page of items of current key and
+    /// returns it, see `get_multiple`. Requires LMDB 0.9.19+.
+    pub fn prev_multiple(&mut self) -> MdbResult<&[u8]> {
+        self.multiple_slice(ffi::MDB_cursor_op::MDB_PREV_MULTIPLE)
+    }
+
+    // Like `get_plain` result isn't tied to cursor borrow, public
+    // accessors narrow it
+    fn multiple_slice(&mut self, op: ffi::MDB_cursor_op) -> MdbResult<&'txn [u8]> {
+        try!(self.navigate(op));
+        Ok(unsafe { MdbValue::<'txn>::from_raw(&self.data_val).as_slice() })
+    }
+
     /// Retrieves current key/value as tuple
     pub fn get<'a, T: FromMdbValue + 'a, U: FromMdbValue + 'a>(&'a mut self) -> MdbResult<(T, U)> {
         let (k, v) = try!(self.get_plain());
@@ -1488,10 +3859,104 @@ impl<'txn> Cursor<'txn> {
         }
     }
 
+    /// Returns count of items for `key` (0 if it doesn't exist) keeping
+    /// cursor at the current position. If cursor wasn't positioned
+    /// it is left at `key`.
+    pub fn duplicate_count_for_key<K: ToMdbValue>(&mut self, key: &K) -> MdbResult<usize> {
+        let saved = self.get_plain().ok();
+
+        let count = match self.to_key(key) {
+            Ok(_) => Ok(positioned_item_count(self)),
+            Err(NotFound) => Ok(0),
+            Err(e) => Err(e),
+        };
+
+        if let Some((k, v)) = saved {
+            try!(self.restore_position(&k, &v));
+        }
+        count
+    }
+
+    /// Advances cursor by up to `n` keys, returns count of actual
+    /// moves which is less than `n` if the last key was reached.
+    /// Cursor then stays at the last key.
+    pub fn skip(&mut self, n: usize) -> MdbResult<usize> {
+        for skipped in 0..n {
+            match self.to_next_key() {
+                Ok(_) => (),
+                Err(NotFound) => return Ok(skipped),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(n)
+    }
+
+    /// Counts items from the current one (included) up to the last
+    /// item with key `<= end_key`, then returns cursor back to the
+    /// current position. Returns 0 if current key is already greater.
+    pub fn count_forward_to<K: ToMdbValue>(&mut self, end_key: &K) -> MdbResult<usize> {
+        let (k, v) = try!(self.get_plain());
+        let end = end_key.to_mdb_value();
+
+        let mut count = 0;
+        loop {
+            if try!(self.cmp_key(&end)) == Ordering::Greater {
+                break;
+            }
+            count += 1;
+            match self.navigate(ffi::MDB_cursor_op::MDB_NEXT) {
+                Ok(_) => (),
+                Err(NotFound) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        try!(self.restore_position(&k, &v));
+        Ok(count)
+    }
+
+    /// Moves cursor back to an item saved by `get_plain`
+    fn restore_position(&mut self, k: &MdbValue<'txn>, v: &MdbValue<'txn>) -> MdbResult<()> {
+        // GET_BOTH is incompatible with databases without duplicates
+        if try!(self.txn.dbi_flags(self.db)) & ffi::MDB_DUPSORT != 0 {
+            self.to_item(k, v)
+        } else {
+            self.to_key(k)
+        }
+    }
+
+    /// Turns cursor into an iterator which yields current item first
+    /// and then advances in direction specified by `step`
+    pub fn into_iter_from_current(self, step: Step) -> CursorIterator<'txn, CursorFromCurrentIter> {
+        // iterators never write
+        CursorIterator::wrap(self.into_mode(), CursorFromCurrentIter::new(step))
+    }
+
+    /// Returns count of items with the same key as current
+    #[deprecated(since = "0.7.7", note = "use `count_items` instead")]
+    pub fn item_count(&self) -> MdbResult<size_t> {
+        let mut tmp: size_t = 0;
+        lift_mdb!(unsafe {ffi::mdb_cursor_count(self.handle, &mut tmp)}, tmp)
+    }
+
+    /// Returns count of items with the same key as current
+    pub fn count_items(&self) -> MdbResult<usize> {
+        #[allow(deprecated)]
+        let count = try!(self.item_count());
+        usize::try_from(count).map_err(|_| MdbError::Other(-1, "overflow".into()))
+    }
+
+}
+
+impl<'txn> Cursor<'txn, ReadWrite> {
     fn set_value<V: ToMdbValue>(&mut self, value: &V, flags: c_uint) -> MdbResult<()> {
         try!(self.ensure_key_valid());
         self.data_val = value.to_mdb_value().value;
-        lift_mdb!(unsafe {ffi::mdb_cursor_put(self.handle, &mut self.key_val, &mut self.data_val, flags)})
+        let data_size = self.data_val.mv_size as usize;
+        try!(self.txn.check_put_sizes(self.db, self.key_val.mv_size as usize, Some(data_size)));
+        try_mdb!(unsafe {ffi::mdb_cursor_put(self.handle, &mut self.key_val, &mut self.data_val, flags)});
+        self.txn.record_put(self.key_val.mv_size as usize, data_size);
+        Ok(())
     }
 
     pub fn set<K: ToMdbValue, V: ToMdbValue>(&mut self, key: &K, value: &V, flags: c_uint) -> MdbResult<()> {
@@ -1517,8 +3982,61 @@ impl<'txn> Cursor<'txn> {
         res
     }
 
+    /// Adds all `values` to `key` using `MDB_MULTIPLE`, which is much
+    /// faster than adding them one by one. Values are stored as their
+    /// in-memory representation, so database must be created with
+    /// DbDupFixed and hold values of `V` only.
+    pub fn put_multiple<K: ToMdbValue, V: Copy>(&mut self, key: &K, values: &[V]) -> MdbResult<()> {
+        let flags = DbFlags::from_bits_truncate(try!(self.txn.dbi_flags(self.db)));
+        if !flags.contains(DbAllowDups | DbDupFixed) {
+            return Err(StateError(format!("put_multiple requires {} flags, database has {}",
+                                          DbAllowDups | DbDupFixed, flags)));
+        }
+        let stride = mem::size_of::<V>();
+        if stride == 0 {
+            return Err(StateError("put_multiple requires values of non-zero size".to_owned()));
+        }
+
+        self.key_val = key.to_mdb_value().value;
+        self.valid_key = false;
+        let key_size = self.key_val.mv_size as usize;
+        try!(self.txn.check_put_sizes(self.db, key_size, Some(stride)));
+        let mut written = 0;
+        while written < values.len() {
+            let rest = &values[written..];
+            // first value describes a single item and points to the
+            // array, second one holds count of items and gets count
+            // of actually written ones
+            let mut data = [
+                ffi::MDB_val { mv_size: stride as size_t, mv_data: rest.as_ptr() as *const c_void },
+                ffi::MDB_val { mv_size: rest.len() as size_t, mv_data: ptr::null() },
+            ];
+            try_mdb!(unsafe { ffi::mdb_cursor_put(self.handle, &mut self.key_val, data.as_mut_ptr(), ffi::MDB_MULTIPLE) });
+            let count = data[1].mv_size as usize;
+            if count == 0 {
+                return Err(StateError("put_multiple made no progress".to_owned()));
+            }
+            self.txn.record_put(key_size, count * stride);
+            written += count;
+        }
+        Ok(())
+    }
+
+    /// Adds an item to database created with allowed duplicates
+    /// unless exactly the same key/value pair already exists.
+    /// Returns `false` if item was already present.
+    pub fn insert_nodup<K: ToMdbValue, V: ToMdbValue>(&mut self, key: &K, value: &V) -> MdbResult<bool> {
+        match self.set(key, value, ffi::MDB_NODUPDATA) {
+            Ok(_) => Ok(true),
+            Err(KeyExists) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     fn del_value(&mut self, flags: c_uint) -> MdbResult<()> {
-        lift_mdb!(unsafe { ffi::mdb_cursor_del(self.handle, flags) })
+        try_mdb!(unsafe { ffi::mdb_cursor_del(self.handle, flags) });
+        self.txn.record_del();
+        Ok(())
     }
 
     /// Deletes current key
@@ -1542,10 +4060,23 @@ impl<'txn> Cursor<'txn> {
         self.del_value(ffi::MDB_NODUPDATA)
     }
 
-    /// Returns count of items with the same key as current
-    pub fn item_count(&self) -> MdbResult<size_t> {
-        let mut tmp: size_t = 0;
-        lift_mdb!(unsafe {ffi::mdb_cursor_count(self.handle, &mut tmp)}, tmp)
+    /// Deletes items of current key with given `values`, missing ones
+    /// are skipped. Returns count of deleted items. Should be used
+    /// only with DbAllowDups.
+    pub fn del_items<V: ToMdbValue>(&mut self, values: &[V]) -> MdbResult<usize> {
+        // deletes may move key memory, so keep a copy
+        let key: Vec<u8> = try!(self.get_key::<&[u8]>()).to_vec();
+        let mut deleted = 0;
+        for value in values {
+            match self.to_item(&key, value) {
+                Ok(_) => (),
+                Err(NotFound) => continue,
+                Err(e) => return Err(e),
+            }
+            try!(self.del_item());
+            deleted += 1;
+        }
+        Ok(deleted)
     }
 
     pub fn get_item<'k, K: ToMdbValue>(self, k: &'k K) -> CursorItemAccessor<'txn, 'k, K> {
@@ -1556,9 +4087,64 @@ impl<'txn> Cursor<'txn> {
     }
 }
 
-impl<'txn> Drop for Cursor<'txn> {
+impl<'txn, M> Drop for Cursor<'txn, M> {
     fn drop(&mut self) {
         unsafe { ffi::mdb_cursor_close(self.handle) };
+        self.txn.track_cursor(self.db, false);
+    }
+}
+
+impl<'txn> Cursor<'txn, ReadOnly> {
+    /// Wraps cursor so it could be moved to another thread, e.g.
+    /// a scoped one. Fails with `StateError` unless environment was
+    /// opened with `EnvCreateNoTls`, as otherwise read transactions
+    /// are bound to the thread which created them.
+    ///
+    /// Only read-only cursors are supported, write transactions are
+    /// always bound to their thread.
+    ///
+    /// # Safety
+    ///
+    /// LMDB transactions aren't thread safe: the transaction and
+    /// its other cursors must not be used while the cursor is used
+    /// by another thread.
+    pub unsafe fn into_send(self) -> MdbResult<SendCursor<'txn>> {
+        if !self.txn.env.is_no_tls {
+            return Err(StateError("moving cursors between threads requires EnvCreateNoTls".to_owned()));
+        }
+        Ok(SendCursor { cursor: self })
+    }
+}
+
+/// Read-only cursor which could be sent to another thread, see
+/// [into_send](struct.Cursor.html#method.into_send)
+#[derive(Debug)]
+pub struct SendCursor<'txn> {
+    cursor: Cursor<'txn, ReadOnly>,
+}
+
+// Invariant: only created for NoTls environments, exclusive use
+// of the transaction is guaranteed by caller of `into_send`
+unsafe impl<'txn> Send for SendCursor<'txn> {}
+
+impl<'txn> SendCursor<'txn> {
+    /// Returns wrapped cursor
+    pub fn into_inner(self) -> Cursor<'txn, ReadOnly> {
+        self.cursor
+    }
+}
+
+impl<'txn> Deref for SendCursor<'txn> {
+    type Target = Cursor<'txn, ReadOnly>;
+
+    fn deref(&self) -> &Cursor<'txn, ReadOnly> {
+        &self.cursor
+    }
+}
+
+impl<'txn> DerefMut for SendCursor<'txn> {
+    fn deref_mut(&mut self) -> &mut Cursor<'txn, ReadOnly> {
+        &mut self.cursor
     }
 }
 
@@ -1620,6 +4206,25 @@ impl<'cursor> CursorValue<'cursor> {
     }
 }
 
+/// Copies key and value out of database memory
+impl<'cursor> From<CursorValue<'cursor>> for (Vec<u8>, Vec<u8>) {
+    fn from(cv: CursorValue<'cursor>) -> (Vec<u8>, Vec<u8>) {
+        (FromMdbValue::from_mdb_value(&cv.key),
+         FromMdbValue::from_mdb_value(&cv.value))
+    }
+}
+
+/// Copies key and value out of database memory, invalid UTF-8
+/// sequences are replaced with `U+FFFD`
+impl<'cursor> From<CursorValue<'cursor>> for (String, String) {
+    fn from(cv: CursorValue<'cursor>) -> (String, String) {
+        let key: &[u8] = FromMdbValue::from_mdb_value(&cv.key);
+        let value: &[u8] = FromMdbValue::from_mdb_value(&cv.value);
+        (String::from_utf8_lossy(key).into_owned(),
+         String::from_utf8_lossy(value).into_owned())
+    }
+}
+
 /// Allows the cration of custom cursor iteration behaviours.
 pub trait IterateCursor {
     /// Returns true if initialization successful, for example that
@@ -1633,14 +4238,33 @@ pub trait IterateCursor {
     fn get_size_hint(&self, _cursor: &Cursor) -> (usize, Option<usize>) {
         (0, None)
     }
+
+    /// Moves cursor straight to the last item iterator would yield,
+    /// returns false if there is none. `None` means it isn't supported
+    /// and iterator has to be walked till the end instead.
+    fn move_to_last<'a, 'b: 'a>(&'a self, _cursor: &mut Cursor<'b>) -> Option<bool> {
+        None
+    }
 }
 
 
+/// Iterator over items visited by cursor according to `I`.
+///
+/// Cursor stays at the item returned last and moves on only when the
+/// next one is requested, so writing to the same database within the
+/// transaction during iteration is well defined: items written after
+/// the current one are visited, items written before it aren't, and
+/// the current item may be overwritten or deleted without skipping
+/// or repeating anything. Note that any write may move data within
+/// pages, so convert values returned before it to owned ones first.
 #[derive(Debug)]
 pub struct CursorIterator<'c, I> {
     inner: I,
     has_data: bool,
+    // cursor points to already returned item
+    pending: bool,
     cursor: Cursor<'c>,
+    error: Option<MdbError>,
     marker: ::std::marker::PhantomData<&'c ()>,
 }
 
@@ -1648,31 +4272,79 @@ impl<'c, I: IterateCursor + 'c> CursorIterator<'c, I> {
     fn wrap(cursor: Cursor<'c>, inner: I) -> CursorIterator<'c, I> {
         let mut cursor = cursor;
         let has_data = inner.init_cursor(&mut cursor);
+        let error = if has_data { None } else { cursor.take_last_error() };
         CursorIterator {
             inner: inner,
             has_data: has_data,
+            pending: false,
             cursor: cursor,
+            error: error,
             marker: ::std::marker::PhantomData,
         }
     }
 
+    /// Returns an error which terminated iteration, if any.
+    ///
+    /// Iterator stops on errors the same way it does on reaching
+    /// the end of data, so check it after the loop to tell
+    /// a short dataset from a failure.
+    pub fn take_error(&mut self) -> Option<MdbError> {
+        self.error.take()
+    }
+
     #[allow(dead_code)]
     fn unwrap(self) -> Cursor<'c> {
         self.cursor
     }
+
+    /// Returns an iterator over keys which skips keys failing to
+    /// convert, i.e. if `FromMdbValue` panics on them.
+    ///
+    /// It is intended as a recovery mechanism for databases with
+    /// heterogeneous keys. Note that panic message is still reported
+    /// by the panic hook.
+    pub fn map_keys_skip_errors<K: FromMdbValue + 'c>(self) -> impl Iterator<Item=K> + 'c {
+        self.filter_map(|cv| {
+            panic::catch_unwind(AssertUnwindSafe(|| cv.get_key::<K>())).ok()
+        })
+    }
+
+    /// Wraps iterator to report `n` items in `size_hint`, which lets
+    /// `collect` allocate upfront when the count is known in advance
+    /// (e.g. from database stats). Hint decreases as items are
+    /// returned; it doesn't limit iteration.
+    pub fn with_capacity_hint(self, n: usize) -> CapacityHintedIter<'c, I> {
+        CapacityHintedIter {
+            inner: self,
+            remaining: n,
+        }
+    }
+
+    /// Converts into an iterator yielding `(K, V)` pairs
+    pub fn typed<K: FromMdbValue + 'c, V: FromMdbValue + 'c>(self) -> TypedIter<'c, I, K, V> {
+        TypedIter {
+            inner: self,
+            marker: ::std::marker::PhantomData,
+        }
+    }
 }
 
 impl<'c, I: IterateCursor + 'c> Iterator for CursorIterator<'c, I> {
     type Item = CursorValue<'c>;
 
     fn next(&mut self) -> Option<CursorValue<'c>> {
+        self.advance_pending();
         if !self.has_data {
             None
         } else {
             match self.cursor.get_plain() {
-                Err(_) => None,
+                Err(e) => {
+                    self.has_data = false;
+                    self.error = Some(e);
+                    None
+                },
                 Ok((k, v)) => {
-                    self.has_data = unsafe { self.inner.move_to_next(mem::transmute(&mut self.cursor)) };
+                    self.pending = true;
                     Some(CursorValue {
                         key: k,
                         value: v,
@@ -1684,7 +4356,423 @@ impl<'c, I: IterateCursor + 'c> Iterator for CursorIterator<'c, I> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.inner.get_size_hint(&self.cursor)
+        let (lower, upper) = self.inner.get_size_hint(&self.cursor);
+        if self.pending {
+            (lower.saturating_sub(1), upper.map(|n| n.saturating_sub(1)))
+        } else {
+            (lower, upper)
+        }
+    }
+
+    /// Skips items by moving cursor only, without reading them
+    fn nth(&mut self, n: usize) -> Option<CursorValue<'c>> {
+        self.advance_pending();
+        for _ in 0..n {
+            if !self.has_data {
+                return None;
+            }
+            self.advance();
+        }
+        self.next()
+    }
+
+    fn last(mut self) -> Option<CursorValue<'c>> {
+        self.advance_pending();
+        if !self.has_data {
+            return None;
+        }
+
+        match self.inner.move_to_last(&mut self.cursor) {
+            None => {
+                let mut last = None;
+                while let Some(cv) = self.next() {
+                    last = Some(cv);
+                }
+                last
+            },
+            Some(false) => None,
+            Some(true) => {
+                self.has_data = false;
+                self.cursor.get_plain().ok().map(|(k, v)| CursorValue {
+                    key: k,
+                    value: v,
+                    marker: ::std::marker::PhantomData
+                })
+            }
+        }
+    }
+}
+
+impl<'c, I: IterateCursor + 'c> CursorIterator<'c, I> {
+    /// Moves cursor to the next item, keeping `has_data` and
+    /// `error` in sync with cursor state
+    fn advance(&mut self) {
+        self.has_data = unsafe { self.inner.move_to_next(mem::transmute(&mut self.cursor)) };
+        if !self.has_data {
+            self.error = self.cursor.take_last_error();
+        }
+    }
+
+    /// Moves away from already returned item, if any
+    fn advance_pending(&mut self) {
+        if self.pending {
+            self.pending = false;
+            if self.has_data {
+                self.advance();
+            }
+        }
+    }
+}
+
+/// Iterator with externally provided size hint,
+/// see [with_capacity_hint](struct.CursorIterator.html#method.with_capacity_hint)
+#[derive(Debug)]
+pub struct CapacityHintedIter<'c, I> {
+    inner: CursorIterator<'c, I>,
+    remaining: usize,
+}
+
+impl<'c, I: IterateCursor + 'c> Iterator for CapacityHintedIter<'c, I> {
+    type Item = CursorValue<'c>;
+
+    fn next(&mut self) -> Option<CursorValue<'c>> {
+        let next = self.inner.next();
+        if next.is_some() {
+            self.remaining = self.remaining.saturating_sub(1);
+        }
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// Iterator which converts keys and values on the fly,
+/// see [typed](struct.CursorIterator.html#method.typed)
+#[derive(Debug)]
+pub struct TypedIter<'c, I, K, V> {
+    inner: CursorIterator<'c, I>,
+    marker: ::std::marker::PhantomData<(K, V)>,
+}
+
+impl<'c, I: IterateCursor + 'c, K, V> TypedIter<'c, I, K, V> {
+    /// Returns an error which terminated iteration, see
+    /// [CursorIterator::take_error](struct.CursorIterator.html#method.take_error)
+    pub fn take_error(&mut self) -> Option<MdbError> {
+        self.inner.take_error()
+    }
+}
+
+impl<'c, I, K, V> Iterator for TypedIter<'c, I, K, V>
+    where I: IterateCursor + 'c, K: FromMdbValue + 'c, V: FromMdbValue + 'c
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.inner.next().map(|cv| {
+            (FromMdbValue::from_mdb_value(&cv.key),
+             FromMdbValue::from_mdb_value(&cv.value))
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Walks database key by key, see [groups](struct.Database.html#method.groups)
+///
+/// It isn't an `Iterator` as every group borrows the underlying cursor,
+/// use `next_group` in a loop instead.
+#[derive(Debug)]
+pub struct GroupIter<'c> {
+    cursor: Cursor<'c>,
+    started: bool,
+    dup_sort: bool,
+}
+
+impl<'c> GroupIter<'c> {
+    /// Advances to the next key and returns it together with its values.
+    /// Values which weren't consumed are skipped.
+    pub fn next_group<'g>(&'g mut self) -> MdbResult<Option<(&'c [u8], GroupValues<'g, 'c>)>> {
+        let res = if self.started {
+            self.cursor.to_next_key()
+        } else {
+            self.started = true;
+            self.cursor.to_first()
+        };
+
+        match res {
+            Ok(_) => (),
+            Err(NotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let (key, _) = try!(self.cursor.get_plain());
+        let values = GroupValues {
+            cursor: &mut self.cursor,
+            started: false,
+            done: false,
+            dup_sort: self.dup_sort,
+        };
+        Ok(Some((FromMdbValue::from_mdb_value(&key), values)))
+    }
+}
+
+/// Distinct key components after prefix,
+/// see [child_prefixes](struct.Database.html#method.child_prefixes)
+#[derive(Debug)]
+pub struct ChildPrefixes<'c, M = ReadWrite> {
+    cursor: Cursor<'c, M>,
+    prefix: Vec<u8>,
+    delimiter: u8,
+    started: bool,
+    done: bool,
+    // prefix of the last component if it had keys below it
+    skip: Option<Vec<u8>>,
+    last: Option<Vec<u8>>,
+    visited: usize,
+}
+
+impl<'c, M> ChildPrefixes<'c, M> {
+    /// Returns count of keys cursor was positioned at so far
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+
+    fn next_child(&mut self) -> MdbResult<Option<Vec<u8>>> {
+        loop {
+            let moved = if !self.started {
+                self.started = true;
+                if self.prefix.is_empty() {
+                    self.cursor.to_first()
+                } else {
+                    self.cursor.to_gte_key(&self.prefix)
+                }
+            } else {
+                match self.skip.take() {
+                    Some(p) => self.cursor.to_after_prefix(&p),
+                    None => self.cursor.to_next_key(),
+                }
+            };
+            match moved {
+                Ok(_) => (),
+                Err(NotFound) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+            self.visited += 1;
+
+            let key: &[u8] = try!(self.cursor.get_key());
+            if !key.starts_with(&self.prefix) {
+                return Ok(None);
+            }
+
+            let rest = &key[self.prefix.len()..];
+            let child = match rest.iter().position(|&b| b == self.delimiter) {
+                Some(pos) => {
+                    self.skip = Some(key[..self.prefix.len() + pos + 1].to_vec());
+                    &rest[..pos]
+                },
+                None => rest,
+            };
+
+            // prefix itself and a leaf followed by the same component
+            // with children below it aren't reported separately
+            if child.is_empty() || self.last.as_ref().map(|l| &l[..]) == Some(child) {
+                continue;
+            }
+            self.last = Some(child.to_vec());
+            return Ok(Some(child.to_vec()));
+        }
+    }
+}
+
+/// Items of a key in page-sized chunks, see
+/// [item_chunks](struct.Database.html#method.item_chunks)
+#[derive(Debug)]
+pub struct ItemChunks<'c, M = ReadWrite> {
+    cursor: Cursor<'c, M>,
+    reverse: bool,
+    started: bool,
+    done: bool,
+}
+
+impl<'c, M> ItemChunks<'c, M> {
+    fn next_chunk(&mut self) -> MdbResult<&'c [u8]> {
+        if self.started {
+            return if self.reverse {
+                self.cursor.multiple_slice(ffi::MDB_cursor_op::MDB_PREV_MULTIPLE)
+            } else {
+                self.cursor.multiple_slice(ffi::MDB_cursor_op::MDB_NEXT_MULTIPLE)
+            };
+        }
+
+        self.started = true;
+        if self.reverse {
+            // MDB_LAST_DUP marks cursor as being at the end, which
+            // makes MDB_GET_MULTIPLE skip the page, so reposition at
+            // the same item explicitly
+            try!(self.cursor.to_last_item());
+            let (key, value) = try!(self.cursor.get_plain());
+            try!(self.cursor.to_item(&key, &value));
+        }
+        self.cursor.multiple_slice(ffi::MDB_cursor_op::MDB_GET_MULTIPLE)
+    }
+}
+
+impl<'c, M> Iterator for ItemChunks<'c, M> {
+    type Item = MdbResult<&'c [u8]>;
+
+    fn next(&mut self) -> Option<MdbResult<&'c [u8]>> {
+        if self.done {
+            return None;
+        }
+        match self.next_chunk() {
+            Ok(chunk) => Some(Ok(chunk)),
+            Err(NotFound) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+impl<'c, M> Iterator for ChildPrefixes<'c, M> {
+    type Item = MdbResult<Vec<u8>>;
+
+    fn next(&mut self) -> Option<MdbResult<Vec<u8>>> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_child();
+        match res {
+            Ok(Some(child)) => Some(Ok(child)),
+            Ok(None) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            },
+        }
+    }
+}
+
+/// Values of a single key, see [GroupIter](struct.GroupIter.html)
+#[derive(Debug)]
+pub struct GroupValues<'g, 'c: 'g> {
+    cursor: &'g mut Cursor<'c>,
+    started: bool,
+    done: bool,
+    dup_sort: bool,
+}
+
+impl<'g, 'c: 'g> GroupValues<'g, 'c> {
+    /// Returns total count of values in group, including
+    /// already consumed ones
+    pub fn count(&self) -> MdbResult<usize> {
+        if self.dup_sort {
+            self.cursor.count_items()
+        } else {
+            // mdb_cursor_count works with DUPSORT only
+            Ok(1)
+        }
+    }
+
+    /// Returns the next value of group or `None` if it is exhausted
+    pub fn next_value<V: FromMdbValue + 'c>(&mut self) -> MdbResult<Option<V>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        if self.started {
+            if !self.dup_sort {
+                // MDB_NEXT_DUP would step to the next key
+                self.done = true;
+                return Ok(None);
+            }
+            match self.cursor.to_next_item() {
+                Ok(_) => (),
+                Err(NotFound) => {
+                    self.done = true;
+                    return Ok(None);
+                },
+                Err(e) => return Err(e),
+            }
+        } else {
+            self.started = true;
+        }
+
+        let (_, value) = try!(self.cursor.get_plain());
+        Ok(Some(FromMdbValue::from_mdb_value(&value)))
+    }
+
+    /// Calls `f` on every remaining value of group
+    pub fn for_each_value<V, F>(mut self, mut f: F) -> MdbResult<()>
+        where V: FromMdbValue + 'c, F: FnMut(V)
+    {
+        while let Some(value) = try!(self.next_value()) {
+            f(value);
+        }
+        Ok(())
+    }
+
+    /// Collects remaining values of group
+    pub fn collect<V: FromMdbValue + 'c>(mut self) -> MdbResult<Vec<V>> {
+        let mut res = Vec::with_capacity(try!(self.count()));
+        while let Some(value) = try!(self.next_value()) {
+            res.push(value);
+        }
+        Ok(res)
+    }
+}
+
+/// Direction of [into_iter_from_current](struct.Cursor.html#method.into_iter_from_current)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// Next item, including duplicates of the same key
+    Next,
+    /// Next item of the same key only
+    NextDup,
+    /// First item of the next key
+    NextNoDup,
+    /// Previous item, including duplicates of the same key
+    Prev,
+}
+
+#[derive(Debug)]
+pub struct CursorFromCurrentIter {
+    step: Step,
+}
+
+impl CursorFromCurrentIter {
+    pub fn new(step: Step) -> CursorFromCurrentIter {
+        CursorFromCurrentIter {
+            step: step,
+        }
+    }
+}
+
+impl IterateCursor for CursorFromCurrentIter {
+    fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
+        // cursor is already positioned by the caller
+        cursor.get_plain().is_ok()
+    }
+
+    fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        let op = match self.step {
+            Step::Next => ffi::MDB_cursor_op::MDB_NEXT,
+            Step::NextDup => ffi::MDB_cursor_op::MDB_NEXT_DUP,
+            Step::NextNoDup => ffi::MDB_cursor_op::MDB_NEXT_NODUP,
+            Step::Prev => ffi::MDB_cursor_op::MDB_PREV,
+        };
+        cursor.navigate(op).is_ok()
     }
 }
 
@@ -1697,7 +4785,9 @@ pub struct CursorKeyRangeIter<'a> {
 }
 
 impl<'a> CursorKeyRangeIter<'a> {
-    pub fn new<K: ToMdbValue+'a>(start_key: &'a K, end_key: &'a K, end_inclusive: bool) -> CursorKeyRangeIter<'a> {
+    pub fn new<K1, K2>(start_key: &'a K1, end_key: &'a K2, end_inclusive: bool) -> CursorKeyRangeIter<'a>
+        where K1: ToMdbValue + 'a, K2: ToMdbValue + 'a
+    {
         CursorKeyRangeIter {
             start_key: start_key.to_mdb_value(),
             end_key: end_key.to_mdb_value(),
@@ -1709,9 +4799,7 @@ impl<'a> CursorKeyRangeIter<'a> {
 
 impl<'iter> IterateCursor for CursorKeyRangeIter<'iter> {
     fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
-        let ok = unsafe {
-            cursor.to_gte_key(mem::transmute::<&'a MdbValue<'a>, &'b MdbValue<'b>>(&self.start_key)).is_ok()
-        };
+        let ok = cursor.to_gte_key(&self.start_key).is_ok();
         ok && cursor.cmp_key(&self.end_key).is_less(self.end_inclusive)
     }
 
@@ -1723,19 +4811,31 @@ impl<'iter> IterateCursor for CursorKeyRangeIter<'iter> {
             cursor.cmp_key(&self.end_key).is_less(self.end_inclusive)
         }
     }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(to_last_key_before(cursor, &self.end_key, self.end_inclusive) &&
+             !cursor.cmp_key(&self.start_key).is_less(false))
+    }
 }
 
 #[derive(Debug)]
 pub struct CursorFromKeyIter<'a> {
     start_key: MdbValue<'a>,
+    inclusive: bool,
     marker: ::std::marker::PhantomData<&'a ()>,
 }
 
 
 impl<'a> CursorFromKeyIter<'a> {
     pub fn new<K: ToMdbValue+'a>(start_key: &'a K) -> CursorFromKeyIter<'a> {
+        CursorFromKeyIter::new_with_inclusive(start_key, true)
+    }
+
+    /// Skips `start_key` itself if `inclusive` is false
+    pub fn new_with_inclusive<K: ToMdbValue+'a>(start_key: &'a K, inclusive: bool) -> CursorFromKeyIter<'a> {
         CursorFromKeyIter {
             start_key: start_key.to_mdb_value(),
+            inclusive: inclusive,
             marker: ::std::marker::PhantomData
         }
     }
@@ -1743,14 +4843,21 @@ impl<'a> CursorFromKeyIter<'a> {
 
 impl<'iter> IterateCursor for CursorFromKeyIter<'iter> {
     fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
-        unsafe {
-            cursor.to_gte_key(mem::transmute::<&'a MdbValue<'a>, &'b MdbValue<'b>>(&self.start_key)).is_ok()
+        match cursor.to_key_or_next(&self.start_key) {
+            Ok(true) if !self.inclusive => cursor.to_next_key().is_ok(),
+            Ok(_) => true,
+            Err(_) => false,
         }
     }
 
     fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
         cursor.to_next_key().is_ok()
     }
+
+    // iterator has data, so the last key is never before start one
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(cursor.to_last().is_ok() && to_first_item_of_key(cursor))
+    }
 }
 
 
@@ -1784,6 +4891,10 @@ impl<'iter> IterateCursor for CursorToKeyIter<'iter> {
             cursor.cmp_key(&self.end_key).is_less(false)
         }
     }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(to_last_key_before(cursor, &self.end_key, false))
+    }
 }
 
 #[allow(missing_copy_implementations)]
@@ -1799,12 +4910,17 @@ impl<'iter> IterateCursor for CursorIter {
     fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
         cursor.to_next_key().is_ok()
     }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(cursor.to_last().is_ok() && to_first_item_of_key(cursor))
+    }
 }
 
 
 #[derive(Debug)]
 pub struct CursorItemIter<'a> {
     key: MdbValue<'a>,
+    remaining: Cell<usize>,
     marker: ::std::marker::PhantomData<&'a ()>,
 }
 
@@ -1813,30 +4929,249 @@ impl<'a> CursorItemIter<'a> {
     pub fn new<K: ToMdbValue+'a>(key: &'a K) -> CursorItemIter<'a> {
         CursorItemIter {
             key: key.to_mdb_value(),
+            remaining: Cell::new(0),
             marker: ::std::marker::PhantomData
         }
     }
 }
 
+/// Returns the smallest byte string greater than all strings
+/// starting with `prefix`, `None` if there is no such one
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+    while let Some(last) = successor.pop() {
+        if last < 0xFF {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+    None
+}
+
+/// Returns count of items for key cursor points to
+fn positioned_item_count<M>(cursor: &Cursor<M>) -> usize {
+    // count is unavailable without DbAllowDups, but then
+    // there is exactly one item
+    cursor.count_items().unwrap_or(1)
+}
+
+/// Moves cursor to the first item of key it points to
+fn to_first_item_of_key<M>(cursor: &mut Cursor<M>) -> bool {
+    positioned_item_count(cursor) == 1 || cursor.to_first_item().is_ok()
+}
+
+/// Moves cursor to the first item of the last key which is less
+/// than `end` (or equal to it if `inclusive`), returns false if
+/// there is no such key
+fn to_last_key_before<M>(cursor: &mut Cursor<M>, end: &MdbValue, inclusive: bool) -> bool {
+    let found = match cursor.to_gte_key(end) {
+        Ok(_) if cursor.cmp_key(end).is_less(inclusive) => true,
+        Ok(_) => cursor.to_prev_key().is_ok(),
+        Err(NotFound) => cursor.to_last().is_ok(),
+        Err(_) => false,
+    };
+    found && to_first_item_of_key(cursor)
+}
+
 impl<'iter> IterateCursor for CursorItemIter<'iter> {
     fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
-        unsafe {
-            cursor.to_key(mem::transmute::<&MdbValue, &'b MdbValue<'b>>(&self.key)).is_ok()
+        let ok = cursor.to_key(&self.key).is_ok();
+        self.remaining.set(if ok { positioned_item_count(cursor) } else { 0 });
+        ok
+    }
+
+    fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        self.remaining.set(self.remaining.get().saturating_sub(1));
+        cursor.to_next_item().is_ok()
+    }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        self.remaining.set(1);
+        Some(positioned_item_count(cursor) == 1 || cursor.to_last_item().is_ok())
+    }
+
+    fn get_size_hint(&self, _: &Cursor) -> (usize, Option<usize>) {
+        let remaining = self.remaining.get();
+        (remaining, Some(remaining))
+    }
+}
+
+/// `len` is the count of items of the key taken when iterator was
+/// positioned, decremented as items are consumed. Items added to or
+/// deleted from the key during iteration (e.g. through another cursor
+/// of the same transaction) aren't reflected, so `len` may be stale
+/// in that case.
+impl<'c> ExactSizeIterator for CursorIterator<'c, CursorItemIter<'c>> {}
+
+/// Same as `CursorItemIter`, but starts from the first item which is
+/// greater than or equal to a value. Only upper bound of size is
+/// known, as LMDB can't tell position of an item.
+#[derive(Debug)]
+pub struct CursorItemFromIter<'a> {
+    key: MdbValue<'a>,
+    start: MdbValue<'a>,
+    total: Cell<usize>,
+}
+
+impl<'a> CursorItemFromIter<'a> {
+    pub fn new<K: ToMdbValue+'a, V: ToMdbValue+'a>(key: &'a K, start: &'a V) -> CursorItemFromIter<'a> {
+        CursorItemFromIter {
+            key: key.to_mdb_value(),
+            start: start.to_mdb_value(),
+            total: Cell::new(0),
         }
     }
+}
+
+impl<'iter> IterateCursor for CursorItemFromIter<'iter> {
+    fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
+        let ok = cursor.to_gte_item(&self.key, &self.start).is_ok();
+        self.total.set(if ok { positioned_item_count(cursor) } else { 0 });
+        ok
+    }
 
     fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        self.total.set(self.total.get().saturating_sub(1));
         cursor.to_next_item().is_ok()
     }
 
-    fn get_size_hint(&self, c: &Cursor) -> (usize, Option<usize>) {
-        match c.item_count() {
-            Err(_) => (0, None),
-            Ok(cnt) => (0, Some(cnt as usize))
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(positioned_item_count(cursor) == 1 || cursor.to_last_item().is_ok())
+    }
+
+    fn get_size_hint(&self, _: &Cursor) -> (usize, Option<usize>) {
+        (0, Some(self.total.get()))
+    }
+}
+
+#[derive(Debug)]
+pub struct CursorItemRevIter<'a> {
+    key: MdbValue<'a>,
+    remaining: Cell<usize>,
+    marker: ::std::marker::PhantomData<&'a ()>,
+}
+
+
+impl<'a> CursorItemRevIter<'a> {
+    pub fn new<K: ToMdbValue+'a>(key: &'a K) -> CursorItemRevIter<'a> {
+        CursorItemRevIter {
+            key: key.to_mdb_value(),
+            remaining: Cell::new(0),
+            marker: ::std::marker::PhantomData
+        }
+    }
+}
+
+impl<'iter> IterateCursor for CursorItemRevIter<'iter> {
+    fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
+        if cursor.to_key(&self.key).is_err() {
+            return false;
+        }
+
+        let count = positioned_item_count(cursor);
+        self.remaining.set(count);
+        // there is nothing to skip for a single item, which also
+        // works for databases without DbAllowDups
+        count == 1 || cursor.to_last_item().is_ok()
+    }
+
+    fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        self.remaining.set(self.remaining.get().saturating_sub(1));
+        cursor.to_prev_item().is_ok()
+    }
+
+    fn get_size_hint(&self, _: &Cursor) -> (usize, Option<usize>) {
+        let remaining = self.remaining.get();
+        (remaining, Some(remaining))
+    }
+}
+
+/// See caveat on `CursorItemIter` implementation
+impl<'c> ExactSizeIterator for CursorIterator<'c, CursorItemRevIter<'c>> {}
+
+impl<'c, K, V> ExactSizeIterator for TypedIter<'c, CursorItemIter<'c>, K, V>
+    where K: FromMdbValue + 'c, V: FromMdbValue + 'c {}
+
+#[derive(Debug)]
+pub struct CursorOwnedKeyRangeIter {
+    start_key: Vec<u8>,
+    end_key: Vec<u8>,
+    end_inclusive: bool,
+}
+
+impl CursorOwnedKeyRangeIter {
+    pub fn new(start_key: Vec<u8>, end_key: Vec<u8>, end_inclusive: bool) -> CursorOwnedKeyRangeIter {
+        CursorOwnedKeyRangeIter {
+            start_key: start_key,
+            end_key: end_key,
+            end_inclusive: end_inclusive,
         }
     }
 }
 
+impl IterateCursor for CursorOwnedKeyRangeIter {
+    fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
+        let ok = cursor.to_gte_key(&self.start_key).is_ok();
+        ok && cursor.cmp_key(&self.end_key.to_mdb_value()).is_less(self.end_inclusive)
+    }
+
+    fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        let moved = cursor.to_next_key().is_ok();
+        if !moved {
+            false
+        } else {
+            cursor.cmp_key(&self.end_key.to_mdb_value()).is_less(self.end_inclusive)
+        }
+    }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        Some(to_last_key_before(cursor, &self.end_key.to_mdb_value(), self.end_inclusive) &&
+             !cursor.cmp_key(&self.start_key.to_mdb_value()).is_less(false))
+    }
+}
+
+
+#[derive(Debug)]
+pub struct CursorOwnedItemIter {
+    key: Vec<u8>,
+    remaining: Cell<usize>,
+}
+
+impl CursorOwnedItemIter {
+    pub fn new(key: Vec<u8>) -> CursorOwnedItemIter {
+        CursorOwnedItemIter {
+            key: key,
+            remaining: Cell::new(0),
+        }
+    }
+}
+
+impl IterateCursor for CursorOwnedItemIter {
+    fn init_cursor<'a, 'b: 'a>(&'a self, cursor: & mut Cursor<'b>) -> bool {
+        let ok = cursor.to_key(&self.key).is_ok();
+        self.remaining.set(if ok { positioned_item_count(cursor) } else { 0 });
+        ok
+    }
+
+    fn move_to_next<'i, 'c: 'i>(&'i self, cursor: &'c mut Cursor<'c>) -> bool {
+        self.remaining.set(self.remaining.get().saturating_sub(1));
+        cursor.to_next_item().is_ok()
+    }
+
+    fn move_to_last<'a, 'b: 'a>(&'a self, cursor: &mut Cursor<'b>) -> Option<bool> {
+        self.remaining.set(1);
+        Some(positioned_item_count(cursor) == 1 || cursor.to_last_item().is_ok())
+    }
+
+    fn get_size_hint(&self, _: &Cursor) -> (usize, Option<usize>) {
+        let remaining = self.remaining.get();
+        (remaining, Some(remaining))
+    }
+}
+
+/// See caveat on `CursorItemIter` implementation
+impl<'c> ExactSizeIterator for CursorIterator<'c, CursorOwnedItemIter> {}
+
 
 #[derive(Copy, Clone, Debug)]
 pub struct MdbValue<'a> {
@@ -1845,6 +5180,12 @@ pub struct MdbValue<'a> {
 }
 
 impl<'a> MdbValue<'a> {
+    /// Wraps `len` bytes at `data`
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to `len` readable bytes which stay valid
+    /// and unchanged for `'a`.
     #[inline]
     pub unsafe fn new(data: *const c_void, len: usize) -> MdbValue<'a> {
         MdbValue {
@@ -1856,6 +5197,12 @@ impl<'a> MdbValue<'a> {
         }
     }
 
+    /// Wraps bytes described by `mdb_val`
+    ///
+    /// # Safety
+    ///
+    /// `mdb_val` must point to a valid `MDB_val`, its data must
+    /// satisfy requirements of [new](#method.new).
     #[inline]
     pub unsafe fn from_raw(mdb_val: *const ffi::MDB_val) -> MdbValue<'a> {
         MdbValue::new((*mdb_val).mv_data, (*mdb_val).mv_size as usize)
@@ -1868,13 +5215,57 @@ impl<'a> MdbValue<'a> {
         }
     }
 
+    /// Returns pointer to wrapped bytes
+    ///
+    /// # Safety
+    ///
+    /// Pointer is valid only for `len` bytes and only while `'a`
+    /// lasts, data must not be written through it.
     #[inline]
     pub unsafe fn get_ref(&'a self) -> *const c_void {
         self.value.mv_data
     }
 
     #[inline]
+    #[deprecated(since = "0.7.7", note = "use `len` instead")]
     pub fn get_size(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns size of value in bytes
+    #[inline]
+    pub fn len(&self) -> usize {
         self.value.mv_size as usize
     }
+
+    /// Returns true if value is zero-sized
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns value contents as a byte slice
+    #[inline]
+    pub fn as_slice(&self) -> &'a [u8] {
+        if self.is_empty() {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(self.value.mv_data as *const u8, self.len()) }
+        }
+    }
+}
+
+/// Values are compared by contents, not by address
+impl<'a> PartialEq for MdbValue<'a> {
+    fn eq(&self, other: &MdbValue<'a>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<'a> Eq for MdbValue<'a> {}
+
+impl<'a> Hash for MdbValue<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
 }
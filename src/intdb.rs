@@ -0,0 +1,78 @@
+//! Databases with native integer keys (`DbIntKey`)
+//!
+//! LMDB requires every key of such database to be a native-endian
+//! `unsigned int` or `size_t`, passing anything else silently breaks
+//! ordering. `IntDatabase` is a `TypedDatabase` with keys of a single
+//! `IntKey` type, so it is checked at compile time.
+
+use core::{DbIntKey, Environment, MdbError, MdbResult, NotFound, ReadWrite};
+use traits::{FromMdbValue, ToMdbValue};
+use typed::{TypedDatabase, TypedDb};
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+    impl Sealed for usize {}
+}
+
+/// Integer types usable as `DbIntKey` keys, can't be implemented
+/// outside of this crate
+pub trait IntKey: private::Sealed + ToMdbValue + FromMdbValue + Copy {
+    /// Size of key in bytes
+    fn width() -> usize;
+}
+
+macro_rules! int_key {
+    ($t:ty) => (
+        impl IntKey for $t {
+            fn width() -> usize {
+                ::std::mem::size_of::<$t>()
+            }
+        }
+    )
+}
+
+int_key!(u32);
+int_key!(u64);
+int_key!(usize);
+
+/// Handle of integer-keyed database, see
+/// [create_int_db](../core/struct.Environment.html#method.create_int_db)
+pub type IntDbHandle<K, V> = TypedDb<K, V>;
+
+/// Integer-keyed database bound to a transaction, all typed
+/// adapters of `TypedDatabase` are available
+pub type IntDatabase<'a, K, V, M = ReadWrite> = TypedDatabase<'a, K, V, M>;
+
+impl Environment {
+    /// Opens or creates a `DbIntKey` database with keys of type `K`
+    /// and values of type `V`, bind it with `bind_typed`.
+    ///
+    /// LMDB doesn't store key width, so it is checked against the first
+    /// key of existing database; mismatch is reported as `StateError`.
+    pub fn create_int_db<K, V>(&self, db_name: &str) -> MdbResult<IntDbHandle<K, V>>
+        where K: IntKey, V: ToMdbValue + FromMdbValue
+    {
+        let handle = try!(self.create_db(db_name, DbIntKey));
+        let width = {
+            let reader = try!(self.get_reader());
+            let db = reader.bind(&handle);
+            let mut cursor = try!(db.new_cursor());
+            match cursor.to_first() {
+                Ok(_) => Some(try!(cursor.get_key::<&[u8]>()).len()),
+                Err(NotFound) => None,
+                Err(e) => return Err(e),
+            }
+        };
+
+        match width {
+            Some(width) if width != K::width() => {
+                Err(MdbError::StateError(format!("database '{}' has {}-byte integer keys, requested {}-byte ones",
+                                                 db_name, width, K::width())))
+            },
+            _ => Ok(handle.typed()),
+        }
+    }
+}
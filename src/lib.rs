@@ -5,19 +5,35 @@ extern crate libc;
 
 #[macro_use] extern crate bitflags;
 #[macro_use] extern crate log;
+#[cfg(feature = "serde")]
+#[macro_use] extern crate serde;
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+#[cfg(test)]
+extern crate toml;
 
 extern crate liblmdb_sys as ffi;
 
 pub use libc::c_int;
 pub use ffi::{mdb_filehandle_t, MDB_stat, MDB_envinfo, MDB_val};
-pub use core::{EnvBuilder, Environment, EnvFlags, EnvCreateFlags};
-pub use core::{Database, DbFlags, DbHandle};
-pub use core::{Transaction, ReadonlyTransaction, MdbError, MdbValue};
-pub use core::{Cursor, CursorValue, CursorIter, CursorKeyRangeIter};
-pub use traits::{FromMdbValue, ToMdbValue};
+pub use core::{EnvBuilder, Environment, EnvFlags, EnvCreateFlags, EnvCopyFlags, BackupProgress, MappedView, OpenStats, ChunkedWriteError};
+pub use core::{Database, ReadonlyDatabase, DbFlags, DbHandle, AppendOutcome, DelManyReport, OrderReport, OrderViolation};
+pub use core::{Transaction, BoundTransaction, ReadonlyTransaction, MdbError, KeyOrValue, MdbValue, CommitStats, ReaderLag, OptimisticReader};
+pub use core::{Cursor, CursorValue, CursorIter, CursorKeyRangeIter, SendCursor};
+pub use core::version;
+pub use traits::{FromMdbValue, FromMdbValueOwned, ToMdbValue};
 
 pub mod core;
 pub mod traits;
+pub mod queue;
+pub mod tools;
+pub mod intdb;
+pub mod typed;
+pub mod multimap;
+pub mod migrations;
+pub mod ttl;
+#[cfg(feature = "serde")]
+pub mod config;
 mod utils;
 
 #[cfg(test)]
@@ -0,0 +1,134 @@
+//! Schema version migrations (`Migrator`)
+//!
+//! Current schema version is kept in a reserved named database
+//! `__lmdb_rs_meta`, so environment has to be opened with room for
+//! one more named database (see `EnvBuilder::max_dbs`). Fresh
+//! environment has version 0.
+//!
+//! Each pending migration runs in its own write transaction which
+//! also stores its version, so a failed migration leaves environment
+//! at the version of the last successful one.
+
+use core::{Database, DbFlags, DbHandle, Environment, MdbError, MdbResult, NotFound, Transaction};
+
+/// Name of database which keeps schema version
+pub const META_DB_NAME: &'static str = "__lmdb_rs_meta";
+
+const VERSION_KEY: &'static str = "schema_version";
+
+/// Single schema change
+#[derive(Clone, Copy)]
+pub struct Migration {
+    /// Version of schema after migration is applied
+    pub version: u32,
+    /// Name used in logs and errors
+    pub name: &'static str,
+    /// Applies migration
+    pub run: fn(&Transaction) -> MdbResult<()>,
+}
+
+/// Ordered list of migrations
+#[derive(Clone, Default)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    /// Creates migrator without migrations
+    pub fn new() -> Migrator {
+        Migrator {
+            migrations: Vec::new(),
+        }
+    }
+
+    /// Adds a migration, versions must be added in increasing order
+    pub fn add(mut self, migration: Migration) -> Migrator {
+        self.migrations.push(migration);
+        self
+    }
+
+    /// Returns version the last known migration leads to
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.last().map_or(0, |m| m.version)
+    }
+
+    /// Returns schema version stored in environment
+    pub fn current_version(&self, env: &Environment) -> MdbResult<u32> {
+        let meta = try!(meta_db(env));
+        let reader = try!(env.get_reader());
+        let db = reader.bind(&meta);
+        version_in(&db)
+    }
+
+    /// Applies all migrations newer than stored version and returns
+    /// resulting version. Refuses to run with `StateError` if stored
+    /// version is newer than any known migration.
+    ///
+    /// Version is checked again in each write transaction, so
+    /// migrations applied meanwhile by another process or thread are
+    /// skipped rather than run twice.
+    pub fn run(&self, env: &Environment) -> MdbResult<u32> {
+        try!(self.check_order());
+
+        let meta = try!(meta_db(env));
+        let mut version = try!(self.current_version(env));
+        try!(self.check_known(version));
+        for migration in &self.migrations {
+            if migration.version <= version {
+                continue;
+            }
+            let txn = try!(env.new_transaction());
+            version = try!(stored_version(&txn, &meta));
+            try!(self.check_known(version));
+            if migration.version <= version {
+                continue;
+            }
+
+            debug!("applying migration {} '{}'", migration.version, migration.name);
+            if let Err(e) = (migration.run)(&txn) {
+                warn!("migration {} '{}' failed: {}", migration.version, migration.name, e);
+                return Err(e);
+            }
+            try!(txn.bind(&meta).set(&VERSION_KEY, &migration.version));
+            try!(txn.commit());
+            version = migration.version;
+        }
+        Ok(version)
+    }
+
+    fn check_known(&self, stored: u32) -> MdbResult<()> {
+        if stored > self.latest_version() {
+            return Err(MdbError::StateError(format!("stored schema version {} is newer than latest known {}",
+                                                    stored, self.latest_version())));
+        }
+        Ok(())
+    }
+
+    fn check_order(&self) -> MdbResult<()> {
+        let mut prev = 0;
+        for migration in &self.migrations {
+            if migration.version <= prev {
+                return Err(MdbError::StateError(format!("migration {} '{}' is out of order",
+                                                        migration.version, migration.name)));
+            }
+            prev = migration.version;
+        }
+        Ok(())
+    }
+}
+
+fn meta_db(env: &Environment) -> MdbResult<DbHandle> {
+    env.create_db(META_DB_NAME, DbFlags::empty())
+}
+
+fn stored_version(txn: &Transaction, meta: &DbHandle) -> MdbResult<u32> {
+    version_in(&txn.bind(meta))
+}
+
+fn version_in<M>(db: &Database<M>) -> MdbResult<u32> {
+    match db.get::<u32>(&VERSION_KEY) {
+        Ok(version) => Ok(version),
+        Err(NotFound) => Ok(0),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,140 @@
+//! Multimaps over `DbAllowDups` databases (`MultiMap`)
+//!
+//! Duplicate items make LMDB database a sorted multimap, but
+//! `Database` API mixes both views: `set` adds an item while `del`
+//! drops all of them, `del_item` needs a value and so on. `MultiMap`
+//! exposes only set-like operations on values of a key.
+
+use std::marker::PhantomData;
+
+use core::{describe_db, Database, DbAllowDups, DbDupFixed, DbHandle, MdbError, MdbResult, MdbValue, NotFound};
+use core::{ReadOnly, ReadWrite, ReadonlyTransaction, Transaction};
+use traits::{FromMdbValue, ToMdbValue};
+
+/// Handle of a multimap database with keys of type `K` and values
+/// of type `V`
+#[derive(Clone, Debug)]
+pub struct MultiMap<K, V> {
+    handle: DbHandle,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K: ToMdbValue, V: ToMdbValue + FromMdbValue> MultiMap<K, V> {
+    /// Wraps handle of database opened with `DbAllowDups`, fails
+    /// with `StateError` otherwise
+    pub fn new(handle: &DbHandle) -> MdbResult<MultiMap<K, V>> {
+        if !handle.flags().contains(DbAllowDups) {
+            return Err(MdbError::StateError(format!("multimap requires DUPSORT flag, {} has {}",
+                                                    describe_db(handle.name()), handle.flags())));
+        }
+        Ok(MultiMap {
+            handle: *handle,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns underlying untyped handle
+    pub fn handle(&self) -> &DbHandle {
+        &self.handle
+    }
+
+    /// Binds multimap to a read-write transaction
+    pub fn bind<'t>(&self, txn: &'t Transaction) -> MultiMapDatabase<'t, K, V> {
+        MultiMapDatabase {
+            db: txn.bind(&self.handle),
+            dup_fixed: self.handle.flags().contains(DbDupFixed),
+            marker: PhantomData,
+        }
+    }
+
+    /// Binds multimap to a read-only transaction
+    pub fn bind_reader<'t>(&self, txn: &'t ReadonlyTransaction) -> MultiMapDatabase<'t, K, V, ReadOnly> {
+        MultiMapDatabase {
+            db: txn.bind(&self.handle),
+            dup_fixed: self.handle.flags().contains(DbDupFixed),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Multimap bound to a transaction
+#[derive(Debug)]
+pub struct MultiMapDatabase<'a, K, V, M = ReadWrite> {
+    db: Database<'a, M>,
+    dup_fixed: bool,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<'a, K: ToMdbValue, V: ToMdbValue + FromMdbValue + 'a, M> MultiMapDatabase<'a, K, V, M> {
+    /// Returns underlying untyped database
+    pub fn inner(&self) -> &Database<'a, M> {
+        &self.db
+    }
+
+    /// Returns all values of key in database order, none if key
+    /// is missing. With DbDupFixed values are read page by page.
+    pub fn values(&self, key: &K) -> MdbResult<impl Iterator<Item = V>> {
+        let mut values = Vec::new();
+        if self.dup_fixed {
+            let size = match self.db.get::<&[u8]>(key) {
+                Ok(first) => first.len(),
+                Err(NotFound) => return Ok(values.into_iter()),
+                Err(e) => return Err(e),
+            };
+            for chunk in try!(self.db.item_chunks(key)) {
+                for item in try!(chunk).chunks(size) {
+                    let item = unsafe { MdbValue::new(item.as_ptr() as *const _, item.len()) };
+                    values.push(V::from_mdb_value(&item));
+                }
+            }
+        } else {
+            values.extend(try!(self.db.item_iter(key)).map(|cv| cv.get_value::<V>()));
+        }
+        Ok(values.into_iter())
+    }
+
+    /// Returns true if key has value
+    pub fn contains(&self, key: &K, value: &V) -> MdbResult<bool> {
+        let mut cursor = try!(self.db.new_cursor());
+        match cursor.to_item(key, value) {
+            Ok(_) => Ok(true),
+            Err(NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns count of values of key, 0 if key is missing
+    pub fn count(&self, key: &K) -> MdbResult<u64> {
+        let mut cursor = try!(self.db.new_cursor());
+        match cursor.to_key(key) {
+            Ok(_) => cursor.count_items().map(|n| n as u64),
+            Err(NotFound) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'a, K: ToMdbValue, V: ToMdbValue + FromMdbValue + 'a> MultiMapDatabase<'a, K, V, ReadWrite> {
+    /// Adds value to key, does nothing if key already has it
+    pub fn add(&self, key: &K, value: &V) -> MdbResult<()> {
+        self.db.set(key, value)
+    }
+
+    /// Removes value of key, returns false if there was no such value
+    pub fn remove(&self, key: &K, value: &V) -> MdbResult<bool> {
+        match self.db.del_item(key, value) {
+            Ok(_) => Ok(true),
+            Err(NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes key with all its values, returns count of removed values
+    pub fn remove_all(&self, key: &K) -> MdbResult<u64> {
+        let count = try!(self.count(key));
+        if count > 0 {
+            try!(self.db.del(key));
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,99 @@
+//! Persistent FIFO queue on top of a database
+//!
+//! Items are stored under big-endian `u64` sequence keys, so default
+//! lexicographic key ordering matches insertion order. Next sequence is
+//! derived from the last stored key, so it survives environment
+//! reopening. Note that once a queue is fully drained sequence numbering
+//! starts over from 0.
+//!
+//! Each queue should live in its own named database, otherwise items
+//! of different queues would be mixed.
+
+use core::{Cursor, DbHandle, MdbError, MdbResult, NotFound, Transaction};
+
+/// FIFO queue bound to a database handle
+#[derive(Copy, Clone, Debug)]
+pub struct Queue {
+    handle: DbHandle,
+}
+
+fn seq_to_key(seq: u64) -> [u8; 8] {
+    seq.to_be_bytes()
+}
+
+fn key_to_seq(key: &[u8]) -> MdbResult<u64> {
+    if key.len() != 8 {
+        return Err(MdbError::StateError(format!("unexpected queue key size {}", key.len())));
+    }
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(key);
+    Ok(u64::from_be_bytes(buf))
+}
+
+/// Reads sequence and data of the item cursor points to
+fn current_item(cursor: &mut Cursor) -> MdbResult<(u64, Vec<u8>)> {
+    let (key, data) = try!(cursor.get::<&[u8], Vec<u8>>());
+    let seq = try!(key_to_seq(key));
+    Ok((seq, data))
+}
+
+impl Queue {
+    /// Creates a queue stored in database specified by `handle`
+    pub fn new(handle: DbHandle) -> Queue {
+        Queue {
+            handle: handle
+        }
+    }
+
+    /// Appends data to the end of the queue, returns assigned sequence
+    pub fn push(&self, txn: &Transaction, data: &[u8]) -> MdbResult<u64> {
+        let db = txn.bind(&self.handle);
+        let seq = {
+            let mut cursor = try!(db.new_cursor());
+            match cursor.to_last() {
+                Ok(_) => {
+                    let key = try!(cursor.get_key::<&[u8]>());
+                    try!(key_to_seq(key)) + 1
+                },
+                Err(NotFound) => 0,
+                Err(e) => return Err(e),
+            }
+        };
+
+        let key = seq_to_key(seq);
+        try!(db.append(&&key[..], &data));
+        Ok(seq)
+    }
+
+    /// Removes and returns the first item of the queue
+    pub fn pop(&self, txn: &Transaction) -> MdbResult<Option<(u64, Vec<u8>)>> {
+        let db = txn.bind(&self.handle);
+        let mut cursor = try!(db.new_cursor());
+        match cursor.to_first() {
+            Ok(_) => (),
+            Err(NotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let item = try!(current_item(&mut cursor));
+        try!(cursor.del());
+        Ok(Some(item))
+    }
+
+    /// Returns the first item of the queue without removing it
+    pub fn peek(&self, txn: &Transaction) -> MdbResult<Option<(u64, Vec<u8>)>> {
+        let db = txn.bind(&self.handle);
+        let mut cursor = try!(db.new_cursor());
+        match cursor.to_first() {
+            Ok(_) => current_item(&mut cursor).map(Some),
+            Err(NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns count of items in the queue
+    pub fn len(&self, txn: &Transaction) -> MdbResult<usize> {
+        let db = txn.bind(&self.handle);
+        db.stat().map(|stat| stat.ms_entries as usize)
+    }
+}
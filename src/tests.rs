@@ -2,13 +2,15 @@ use std::env;
 use std::fs::{self};
 use std::path::{PathBuf};
 use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
-use std::sync::{Once, ONCE_INIT};
+use std::sync::{Arc, Barrier, Once, ONCE_INIT};
 use std::thread;
 
 use libc::c_int;
 
-use core::{self, EnvBuilder, DbFlags, MdbValue, EnvNoMemInit, EnvNoMetaSync, KeyExists, MdbError};
+use core::{self, EnvBuilder, DbFlags, MdbValue, EnvNoMemInit, EnvNoMetaSync, KeyExists, MdbError, KeyOrValue};
 use ffi::MDB_val;
+use queue::Queue;
+use tools::{self, ImportOptions};
 use traits::FromMdbValue;
 
 const USER_DIR: u32 = 0o777;
@@ -169,10 +171,7 @@ fn test_append_duplicate() {
     let v = db.get::<&str>(&test_key1).unwrap();
     assert!(v == test_data2, "It should return second value");
 
-    match db.append_duplicate(&test_key1, &test_data1).err().unwrap() {
-        KeyExists => (),
-        _ => panic!("Expected KeyExists error")
-    }
+    assert_eq!(db.append_duplicate(&test_key1, &test_data1), Err(KeyExists));
 }
 
 #[test]
@@ -339,6 +338,34 @@ fn test_cursors() {
     assert!(cursor.to_key(&test_key2).is_ok());
 }
 
+#[test]
+fn test_cursor_count_items() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for &v in &["1", "2", "3", "4"] {
+            db.set(&"a", &v).unwrap();
+        }
+        db.set(&"b", &"5").unwrap();
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_key(&"a").unwrap();
+        assert_eq!(cursor.count_items().unwrap(), 4);
+        #[allow(deprecated)]
+        let raw = cursor.item_count().unwrap();
+        assert_eq!(raw as usize, 4);
+
+        cursor.del_item().unwrap();
+        assert_eq!(cursor.count_items().unwrap(), 3);
+
+        cursor.to_key(&"b").unwrap();
+        assert_eq!(cursor.count_items().unwrap(), 1);
+    }
+    txn.abort();
+}
+
 
 #[test]
 fn test_cursor_item_manip() {
@@ -924,6 +951,3349 @@ fn test_conversion_to_string() {
     tx.abort();
 }
 
+#[test]
+fn test_commit_with_stats() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"key1", &"value1").unwrap();
+        db.set(&"key2", &"value2").unwrap();
+        db.set(&"key3", &"v3").unwrap();
+        db.del(&"key2").unwrap();
+        // failed operations are not counted
+        assert!(db.del(&"missing").is_err());
+    }
+    let stats = txn.commit_with_stats().unwrap();
+    assert_eq!(stats.puts, 3);
+    assert_eq!(stats.deletes, 1);
+    assert_eq!(stats.bytes_written, (4 + 6) * 2 + 4 + 2);
+    assert!(stats.txn_id > 0);
+
+    let txn = env.new_transaction().unwrap();
+    let child_stats = {
+        let child = txn.new_child().unwrap();
+        child.commit_with_stats().unwrap()
+    };
+    assert_eq!(child_stats.puts, 0);
+    assert_eq!(child_stats.deletes, 0);
+    assert_eq!(child_stats.bytes_written, 0);
+
+    let next_stats = txn.commit_with_stats().unwrap();
+    assert_eq!(next_stats.txn_id, stats.txn_id + 1);
+}
+
+#[test]
+fn test_commit_stats_include_nested() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    txn.bind(&db).set(&"a", &"1").unwrap();
+    {
+        let child = txn.new_child().unwrap();
+        child.bind(&db).set(&"b", &"22").unwrap();
+        child.bind(&db).del(&"a").unwrap();
+        let stats = child.commit_with_stats().unwrap();
+        assert_eq!((stats.puts, stats.deletes, stats.bytes_written), (1, 1, 3));
+    }
+    {
+        // changes of aborted child are discarded
+        let child = txn.new_child().unwrap();
+        child.bind(&db).set(&"c", &"333").unwrap();
+        child.abort();
+    }
+    {
+        let child = txn.new_child().unwrap();
+        {
+            let grandchild = child.new_child().unwrap();
+            grandchild.bind(&db).set(&"d", &"4444").unwrap();
+            grandchild.commit().unwrap();
+        }
+        child.commit().unwrap();
+    }
+
+    let stats = txn.commit_with_stats().unwrap();
+    assert_eq!(stats.puts, 3);
+    assert_eq!(stats.deletes, 1);
+    assert_eq!(stats.bytes_written, 2 + 3 + 5);
+}
+
+#[test]
+fn test_open_db() {
+    let env = EnvBuilder::new()
+        .max_dbs(5)
+        .open(&next_path(), USER_DIR)
+        .unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let child = txn.new_child().unwrap();
+        {
+            let db = child.open_db("inline", DbFlags::empty()).unwrap();
+            db.set(&"key", &"value").unwrap();
+        }
+        child.commit().unwrap();
+    }
+    txn.commit().unwrap();
+
+    let db = env.get_db("inline", DbFlags::empty()).unwrap();
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.get::<&str>(&"key").unwrap(), "value");
+}
+
+#[test]
+fn test_merge() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let key = "counter";
+
+        let v = db.merge(&key, |cur: Option<u64>| cur.unwrap_or(0) + 1).unwrap();
+        assert_eq!(v, 1);
+        let v = db.merge(&key, |cur: Option<u64>| cur.unwrap_or(0) + 1).unwrap();
+        assert_eq!(v, 2);
+        assert_eq!(db.get::<u64>(&key).unwrap(), 2);
+
+        let key = "log";
+        for chunk in [b"ab", b"cd", b"ef"].iter() {
+            db.merge(&key, |cur: Option<Vec<u8>>| {
+                let mut acc = cur.unwrap_or(Vec::new());
+                acc.extend_from_slice(&chunk[..]);
+                acc
+            }).unwrap();
+        }
+        assert_eq!(db.get::<Vec<u8>>(&key).unwrap(), b"abcdef".to_vec());
+    }
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_bind_both_txn_kinds() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"key", &"value").unwrap();
+        assert_eq!(db.get::<&str>(&"key").unwrap(), "value");
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.get::<&str>(&"key").unwrap(), "value");
+}
+
+#[test]
+fn test_cursor_value_into_owned() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"a", &"1").unwrap();
+        db.set(&"b", &(&b"\xff2"[..])).unwrap();
+
+        let bytes: Vec<(Vec<u8>, Vec<u8>)> = db.iter().unwrap().map(Into::into).collect();
+        assert_eq!(bytes, vec![(b"a".to_vec(), b"1".to_vec()),
+                               (b"b".to_vec(), b"\xff2".to_vec())]);
+
+        let strings: Vec<(String, String)> = db.iter().unwrap().map(Into::into).collect();
+        assert_eq!(strings, vec![("a".to_owned(), "1".to_owned()),
+                                 ("b".to_owned(), "\u{fffd}2".to_owned())]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_queue_ordering() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let q1 = Queue::new(env.create_db("q1", DbFlags::empty()).unwrap());
+    let q2 = Queue::new(env.create_db("q2", DbFlags::empty()).unwrap());
+
+    let txn = env.new_transaction().unwrap();
+    assert_eq!(q1.pop(&txn).unwrap(), None);
+    assert_eq!(q1.push(&txn, b"a").unwrap(), 0);
+    assert_eq!(q1.push(&txn, b"b").unwrap(), 1);
+    assert_eq!(q2.push(&txn, b"x").unwrap(), 0);
+    assert_eq!(q1.peek(&txn).unwrap(), Some((0, b"a".to_vec())));
+    assert_eq!(q1.pop(&txn).unwrap(), Some((0, b"a".to_vec())));
+    assert_eq!(q1.push(&txn, b"c").unwrap(), 2);
+    assert_eq!(q1.len(&txn).unwrap(), 2);
+    assert_eq!(q1.pop(&txn).unwrap(), Some((1, b"b".to_vec())));
+    assert_eq!(q1.pop(&txn).unwrap(), Some((2, b"c".to_vec())));
+    assert_eq!(q1.pop(&txn).unwrap(), None);
+    assert_eq!(q2.pop(&txn).unwrap(), Some((0, b"x".to_vec())));
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_queue_restart() {
+    let path = next_path();
+    {
+        let env = EnvBuilder::new().max_dbs(5).open(&path, USER_DIR).unwrap();
+        let q = Queue::new(env.create_db("q", DbFlags::empty()).unwrap());
+        let txn = env.new_transaction().unwrap();
+        for i in 0..3u8 {
+            q.push(&txn, &[i]).unwrap();
+        }
+        q.pop(&txn).unwrap();
+        txn.commit().unwrap();
+    }
+
+    let env = EnvBuilder::new().max_dbs(5).open(&path, USER_DIR).unwrap();
+    let q = Queue::new(env.create_db("q", DbFlags::empty()).unwrap());
+    let txn = env.new_transaction().unwrap();
+    assert_eq!(q.push(&txn, b"next").unwrap(), 3);
+    assert_eq!(q.pop(&txn).unwrap(), Some((1, vec![1])));
+    assert_eq!(q.len(&txn).unwrap(), 2);
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_queue_concurrent_producers() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let q = Queue::new(env.create_db("q", DbFlags::empty()).unwrap());
+
+    let producers: Vec<_> = (0..4u8).map(|id| {
+        let env = env.clone();
+        thread::spawn(move || {
+            for i in 0..10u8 {
+                let txn = env.new_transaction().unwrap();
+                q.push(&txn, &[id, i]).unwrap();
+                txn.commit().unwrap();
+            }
+        })
+    }).collect();
+    for p in producers {
+        p.join().unwrap();
+    }
+
+    let txn = env.new_transaction().unwrap();
+    assert_eq!(q.len(&txn).unwrap(), 40);
+    let mut seqs = Vec::new();
+    let mut last_per_producer = [None; 4];
+    while let Some((seq, data)) = q.pop(&txn).unwrap() {
+        seqs.push(seq);
+        // items of each producer keep their order
+        let prev = last_per_producer[data[0] as usize];
+        assert!(prev.map(|p| p < data[1]).unwrap_or(true));
+        last_per_producer[data[0] as usize] = Some(data[1]);
+    }
+    assert_eq!(seqs, (0..40).collect::<Vec<u64>>());
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_map_keys_skip_errors() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"a", &1u32).unwrap();
+        db.set(&(&b"b\xff"[..]), &2u32).unwrap();
+        db.set(&"c", &3u32).unwrap();
+
+        let keys: Vec<String> = db.iter().unwrap().map_keys_skip_errors().collect();
+        assert_eq!(keys, vec!["a".to_owned(), "c".to_owned()]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_verify_order() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db_handle = env.get_default_db(DbFlags::empty()).unwrap();
+    let val: i32 = 0;
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db_handle);
+        for i in 2..6i32 {
+            db.set(&i, &val).unwrap();
+        }
+        let report = db.verify_order().unwrap();
+        assert_eq!(report.checked, 4);
+        assert_eq!(report.violation, None);
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db_handle);
+    db.set_compare(negative_odd_cmp_fn).unwrap();
+    let report = db.verify_order().unwrap();
+    assert_eq!(report.checked, 1);
+    match report.violation {
+        Some(core::OrderViolation::Key { index, previous_key, key }) => {
+            assert_eq!(index, 1);
+            assert_eq!(previous_key, vec![2, 0, 0, 0]);
+            assert_eq!(key, vec![3, 0, 0, 0]);
+        },
+        v => panic!("Unexpected violation {:?}", v),
+    }
+}
+
+#[test]
+fn test_verify_dup_order() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db_handle = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db_handle);
+        for i in 2..6i32 {
+            db.set(&"key", &i).unwrap();
+        }
+        assert_eq!(db.verify_order().unwrap().violation, None);
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db_handle);
+    db.set_dupsort(negative_odd_cmp_fn).unwrap();
+    match db.verify_order().unwrap().violation {
+        Some(core::OrderViolation::Value { index, key, previous_value, value }) => {
+            assert_eq!(index, 1);
+            assert_eq!(key, b"key".to_vec());
+            assert_eq!(previous_value, vec![2, 0, 0, 0]);
+            assert_eq!(value, vec![3, 0, 0, 0]);
+        },
+        v => panic!("Unexpected violation {:?}", v),
+    }
+}
+
+#[test]
+fn test_swap() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let key = "key";
+
+        assert_eq!(db.swap(&key, &"first".to_owned()).unwrap(), None);
+        assert_eq!(db.swap(&key, &"second".to_owned()).unwrap(), Some("first".to_owned()));
+        assert_eq!(db.get::<String>(&key).unwrap(), "second");
+    }
+    txn.commit().unwrap();
+}
+
+#[test]
+fn test_import_chunked() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let env = EnvBuilder::new().map_size(64 * 1024 * 1024).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbIntKey).unwrap();
+
+    let progress = Rc::new(RefCell::new(Vec::new()));
+    let progress_log = progress.clone();
+    let opts = ImportOptions {
+        txn_chunk_size: 1000,
+        use_append: true,
+        progress: Some(Box::new(move |n| progress_log.borrow_mut().push(n))),
+    };
+    let rows = (0..100000u64).map(|i| {
+        if i == 50500 {
+            Err(MdbError::StateError("broken input".to_owned()))
+        } else {
+            Ok((i, i * 2))
+        }
+    });
+    let report = tools::import(&env, &db, rows, opts).unwrap();
+    assert_eq!(report.committed, 50000);
+    assert!(report.error.is_some());
+    assert_eq!(progress.borrow().len(), 50);
+    assert_eq!(progress.borrow().last(), Some(&50000));
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.stat().unwrap().ms_entries as u64, report.committed);
+    assert_eq!(db.get::<u64>(&49999u64).unwrap(), 99998);
+}
+
+#[test]
+fn test_import_complete() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbIntKey).unwrap();
+
+    let opts = ImportOptions { txn_chunk_size: 10, ..ImportOptions::default() };
+    let rows = (0..95u64).rev().map(|i| Ok((i, i)));
+    let report = tools::import(&env, &db, rows, opts).unwrap();
+    assert_eq!(report.committed, 95);
+    assert!(report.error.is_none());
+}
+
+#[test]
+fn test_cursor_to_key_or_next() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let mut cursor = db.new_cursor().unwrap();
+        match cursor.to_key_or_next(&"a") {
+            Err(MdbError::NotFound) => (),
+            _ => panic!("Expected NotFound on empty db")
+        }
+
+        db.set(&"b", &"1").unwrap();
+        db.set(&"d", &"2").unwrap();
+
+        let mut cursor = db.new_cursor().unwrap();
+        assert_eq!(cursor.to_key_or_next(&"b").unwrap(), true);
+        assert_eq!(cursor.get_key::<&str>().unwrap(), "b");
+        assert_eq!(cursor.to_key_or_next(&"c").unwrap(), false);
+        assert_eq!(cursor.get_key::<&str>().unwrap(), "d");
+        assert_eq!(cursor.to_key_or_next(&"a").unwrap(), false);
+        assert_eq!(cursor.get_key::<&str>().unwrap(), "b");
+        match cursor.to_key_or_next(&"e") {
+            Err(MdbError::NotFound) => (),
+            _ => panic!("Expected NotFound past the last key")
+        }
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_keyrange_mixed_key_types() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for k in ["a1", "a2", "b1", "b2", "c1"].iter() {
+            db.set(k, k).unwrap();
+        }
+
+        let end = "b2".to_owned();
+        let keys: Vec<&str> = db.keyrange_from_to(&"a2", &end).unwrap()
+            .map(|cv| cv.get_key::<&str>()).collect();
+        assert_eq!(keys, vec!["a2", "b1"]);
+
+        let start: &[u8] = b"b";
+        let mut end = start.to_vec();
+        end[0] += 1;
+        let keys: Vec<&str> = db.keyrange_from_to(&start, &end).unwrap()
+            .map(|cv| cv.get_key::<&str>()).collect();
+        assert_eq!(keys, vec!["b1", "b2"]);
+
+        let keys: Vec<&str> = db.keyrange(&start, &"b2".to_owned()).unwrap()
+            .map(|cv| cv.get_key::<&str>()).collect();
+        assert_eq!(keys, vec!["b1", "b2"]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_get_page() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbIntKey).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for k in 1..8u64 {
+            db.set(&(k * 10), &k).unwrap();
+        }
+
+        let page: Vec<(u64, u64)> = db.get_page(None, 3).unwrap();
+        assert_eq!(page, vec![(10, 1), (20, 2), (30, 3)]);
+
+        let page: Vec<(u64, u64)> = db.get_page(Some(&30u64), 3).unwrap();
+        assert_eq!(page, vec![(40, 4), (50, 5), (60, 6)]);
+
+        // missing key positions at the next one
+        let page: Vec<(u64, u64)> = db.get_page(Some(&55u64), 3).unwrap();
+        assert_eq!(page, vec![(60, 6), (70, 7)]);
+
+        let page: Vec<(u64, u64)> = db.get_page(Some(&70u64), 3).unwrap();
+        assert!(page.is_empty());
+        let page: Vec<(u64, u64)> = db.get_page(Some(&100u64), 3).unwrap();
+        assert!(page.is_empty());
+    }
+    txn.abort();
+}
+
+fn users_range<'db>(db: &'db core::Database<'db>, from: u32, to: u32)
+                    -> core::CursorIterator<'db, core::CursorOwnedKeyRangeIter> {
+    let start = format!("user:{:03}", from).into_bytes();
+    let end = format!("user:{:03}", to).into_bytes();
+    db.keyrange_owned(start, end).unwrap()
+}
+
+fn user_items<'db>(db: &'db core::Database<'db>, id: u32)
+                   -> core::CursorIterator<'db, core::CursorOwnedItemIter> {
+    db.item_iter_owned(format!("user:{:03}", id).into_bytes()).unwrap()
+}
+
+#[test]
+fn test_owned_key_iterators() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for i in 0..5u32 {
+            let key = format!("user:{:03}", i);
+            db.set(&key, &"a").unwrap();
+            db.set(&key, &"b").unwrap();
+        }
+
+        let keys: Vec<String> = users_range(&db, 1, 3).map(|cv| cv.get_key()).collect();
+        assert_eq!(keys, vec!["user:001", "user:002", "user:003"]);
+
+        let values: Vec<String> = user_items(&db, 4).map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec!["a", "b"]);
+        assert_eq!(user_items(&db, 7).count(), 0);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_check_db_flags_compatible() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    env.create_db("dups", core::DbAllowDups | core::DbIntKey).unwrap();
+    env.create_db("plain", DbFlags::empty()).unwrap();
+
+    assert!(env.check_db_flags_compatible("dups", core::DbAllowDups).unwrap());
+    assert!(env.check_db_flags_compatible("dups", core::DbAllowDups | core::DbIntKey).unwrap());
+    assert!(env.check_db_flags_compatible("dups", DbFlags::empty()).unwrap());
+    assert!(!env.check_db_flags_compatible("dups", core::DbReverseKey).unwrap());
+
+    assert!(env.check_db_flags_compatible("plain", core::DbCreate).unwrap());
+    assert!(!env.check_db_flags_compatible("plain", core::DbAllowDups).unwrap());
+
+    assert!(env.check_db_flags_compatible("missing", DbFlags::empty()).is_err());
+}
+
+#[test]
+fn test_typed_iterators() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbIntKey | core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for i in 0..5u64 {
+            db.set(&i, &format!("value {}", i)).unwrap();
+        }
+        db.set(&2u64, &"another".to_owned()).unwrap();
+
+        let manual: Vec<(u64, String)> = db.iter().unwrap()
+            .map(|cv| (cv.get_key::<u64>(), cv.get_value::<String>()))
+            .collect();
+        let typed: Vec<(u64, String)> = db.iter_typed().unwrap().collect();
+        assert_eq!(typed, manual);
+        assert_eq!(typed.len(), 5);
+
+        let range: Vec<(u64, String)> = db.keyrange_typed(&1u64, &3u64).unwrap().collect();
+        assert_eq!(range, vec![(1, "value 1".to_owned()),
+                               (2, "another".to_owned()),
+                               (3, "value 3".to_owned())]);
+
+        let items = db.item_iter_typed::<_, u64, String>(&2u64).unwrap();
+        assert_eq!(items.size_hint(), (2, Some(2)));
+        let items: Vec<(u64, String)> = items.collect();
+        assert_eq!(items, vec![(2, "another".to_owned()), (2, "value 2".to_owned())]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_iter_values_and_keys() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"b", &"second").unwrap();
+        db.set(&"a", &"first").unwrap();
+        db.set(&"c", &"third").unwrap();
+
+        let values: Vec<String> = db.iter_values().unwrap().collect();
+        assert_eq!(values, vec!["first", "second", "third"]);
+
+        let keys: Vec<&str> = db.iter_keys().unwrap().collect();
+        assert_eq!(keys, vec!["a", "b", "c"]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_groups() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for &v in &[1u32, 2, 3] {
+            db.set(&"a", &v.to_string()).unwrap();
+        }
+        db.set(&"b", &"4").unwrap();
+
+        let mut groups = db.groups().unwrap();
+        {
+            let (key, values) = groups.next_group().unwrap().unwrap();
+            assert_eq!(key, b"a");
+            assert_eq!(values.count().unwrap(), 3);
+            assert_eq!(values.collect::<String>().unwrap(), vec!["1", "2", "3"]);
+        }
+        {
+            let (key, values) = groups.next_group().unwrap().unwrap();
+            assert_eq!(key, b"b");
+            let mut seen = Vec::new();
+            values.for_each_value(|v: String| seen.push(v)).unwrap();
+            assert_eq!(seen, vec!["4"]);
+        }
+        assert!(groups.next_group().unwrap().is_none());
+
+        // partially consumed and skipped groups
+        let mut groups = db.groups().unwrap();
+        {
+            let (_, mut values) = groups.next_group().unwrap().unwrap();
+            assert_eq!(values.next_value::<String>().unwrap(), Some("1".to_owned()));
+        }
+        assert_eq!(groups.next_group().unwrap().unwrap().0, b"b");
+        assert!(groups.next_group().unwrap().is_none());
+
+        let mut groups = db.groups().unwrap();
+        groups.next_group().unwrap().unwrap();
+        let (key, values) = groups.next_group().unwrap().unwrap();
+        assert_eq!(key, b"b");
+        assert_eq!(values.count().unwrap(), 1);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_groups_without_dups() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"a", &"1").unwrap();
+        db.set(&"b", &"2").unwrap();
+
+        let mut groups = db.groups().unwrap();
+        {
+            let (key, values) = groups.next_group().unwrap().unwrap();
+            assert_eq!(key, b"a");
+            assert_eq!(values.count().unwrap(), 1);
+            assert_eq!(values.collect::<String>().unwrap(), vec!["1"]);
+        }
+        {
+            let (key, values) = groups.next_group().unwrap().unwrap();
+            assert_eq!(key, b"b");
+            assert_eq!(values.collect::<String>().unwrap(), vec!["2"]);
+        }
+        assert!(groups.next_group().unwrap().is_none());
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_cursor_insert_nodup() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let mut cursor = db.new_cursor().unwrap();
+        assert!(cursor.insert_nodup(&"key", &"a").unwrap());
+        assert!(cursor.insert_nodup(&"key", &"b").unwrap());
+        assert!(!cursor.insert_nodup(&"key", &"a").unwrap());
+        assert!(cursor.insert_nodup(&"other", &"a").unwrap());
+
+        let values: Vec<&str> = db.item_iter(&"key").unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec!["a", "b"]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_item_iter_exact_size() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for v in &["a", "b", "c"] {
+            db.set(&"key", v).unwrap();
+        }
+        db.set(&"other", &"d").unwrap();
+
+        let mut iter = db.item_iter(&"key").unwrap();
+        assert_eq!(iter.len(), 3);
+        iter.next().unwrap();
+        assert_eq!(iter.len(), 2);
+        iter.next().unwrap();
+        iter.next().unwrap();
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+
+        assert_eq!(db.item_iter(&"other").unwrap().len(), 1);
+        assert_eq!(db.item_iter(&"missing").unwrap().len(), 0);
+        assert_eq!(db.item_iter_owned(b"key".to_vec()).unwrap().len(), 3);
+    }
+    txn.abort();
+
+}
+
+#[test]
+fn test_item_iter_exact_size_without_dups() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"key", &"a").unwrap();
+        let mut iter = db.item_iter(&"key").unwrap();
+        assert_eq!(iter.len(), 1);
+        iter.next().unwrap();
+        assert_eq!(iter.len(), 0);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_mdb_value_len() {
+    use traits::ToMdbValue;
+
+    let data = "hello".to_owned();
+    let value = data.to_mdb_value();
+    assert_eq!(value.len(), 5);
+    assert!(!value.is_empty());
+
+    let empty = Vec::<u8>::new();
+    let value = empty.to_mdb_value();
+    assert_eq!(value.len(), 0);
+    assert!(value.is_empty());
+
+    let num = 42u64;
+    assert_eq!(MdbValue::new_from_sized(&num).len(), 8);
+}
+
+#[test]
+fn test_iteration_error_is_reported() {
+    use ffi::MDB_MAP_FULL;
+
+    let env = EnvBuilder::new().map_size(0x10000u64).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbIntKey).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for i in 0..10u64 {
+            db.set(&i, &i).unwrap();
+        }
+
+        let mut iter = db.iter().unwrap();
+        assert_eq!(iter.by_ref().take(3).count(), 3);
+        assert!(iter.take_error().is_none());
+
+        // failed write puts transaction into error state
+        let test_data = [0x5Au8; 0xFF];
+        let mut key = 100u64;
+        loop {
+            match db.set(&key, &(&test_data[..])) {
+                Ok(_) => key += 1,
+                Err(MdbError::Other(MDB_MAP_FULL, _)) => break,
+                Err(e) => panic!("Unexpected error {:?}", e),
+            }
+        }
+
+        let rest = iter.by_ref().count();
+        assert!(rest < 7);
+        match iter.take_error() {
+            Some(MdbError::Other(code, _)) => assert_eq!(code, ffi::MDB_BAD_TXN),
+            other => panic!("Unexpected error {:?}", other),
+        }
+        assert!(iter.take_error().is_none());
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_complete_iteration_has_no_error() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"a", &"1").unwrap();
+        db.set(&"b", &"2").unwrap();
+
+        let mut iter = db.iter().unwrap();
+        assert_eq!(iter.by_ref().count(), 2);
+        assert!(iter.take_error().is_none());
+
+        let mut iter = db.item_iter(&"missing").unwrap();
+        assert_eq!(iter.by_ref().count(), 0);
+        assert!(iter.take_error().is_none());
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_set_reserve() {
+    use std::ptr;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let data = b"reserved value";
+    {
+        let txn = env.new_transaction().unwrap();
+        {
+            let db = txn.bind(&db);
+            unsafe {
+                let p = db.set_reserve(&"key", data.len()).unwrap();
+                ptr::copy_nonoverlapping(data.as_ptr(), p, data.len());
+            }
+        }
+        txn.commit().unwrap();
+    }
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    let value: &[u8] = db.get(&"key").unwrap();
+    assert_eq!(value, &data[..]);
+}
+
+#[test]
+fn test_cursor_into_iter_from_current() {
+    use core::Step;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for k in &["a", "c", "e", "g"] {
+            db.set(k, &"1").unwrap();
+            db.set(k, &"2").unwrap();
+        }
+
+        let expected: Vec<String> = db.keyrange_from(&"b").unwrap().map(|cv| cv.get_key()).collect();
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_gte_key(&"b").unwrap();
+        let keys: Vec<String> = cursor.into_iter_from_current(Step::NextNoDup)
+            .map(|cv| cv.get_key()).collect();
+        assert_eq!(keys, expected);
+        assert_eq!(keys, vec!["c", "e", "g"]);
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_gte_key(&"f").unwrap();
+        let items: Vec<(String, String)> = cursor.into_iter_from_current(Step::Next)
+            .map(|cv| cv.get()).collect();
+        assert_eq!(items, vec![("g".to_owned(), "1".to_owned()), ("g".to_owned(), "2".to_owned())]);
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_gte_item(&"c", &"2").unwrap();
+        let values: Vec<String> = cursor.into_iter_from_current(Step::NextDup)
+            .map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec!["2"]);
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_key(&"c").unwrap();
+        let keys: Vec<String> = cursor.into_iter_from_current(Step::Prev)
+            .map(|cv| cv.get_key()).collect();
+        assert_eq!(keys, vec!["c", "a", "a"]);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_flags_raw_conversions() {
+    use std::convert::TryFrom;
+    use core::{EnvFlags, EnvCreateFlags};
+
+    let flags = core::DbAllowDups | core::DbIntKey;
+    let bits: u32 = flags.into();
+    assert_eq!(bits, ffi::MDB_DUPSORT | ffi::MDB_INTEGERKEY);
+    assert_eq!(DbFlags::try_from(bits), Ok(flags));
+    assert_eq!(DbFlags::try_from(0x8000_0000), Err(()));
+
+    let bits: u32 = EnvNoMetaSync.into();
+    assert_eq!(EnvFlags::try_from(bits), Ok(EnvNoMetaSync));
+    assert!(EnvFlags::try_from(ffi::MDB_RDONLY).is_err());
+
+    let bits = u32::from(core::EnvCreateReadOnly | core::EnvCreateNoTls);
+    assert_eq!(EnvCreateFlags::try_from(bits), Ok(core::EnvCreateReadOnly | core::EnvCreateNoTls));
+    assert_eq!(EnvCreateFlags::try_from(0x8000_0000), Err(()));
+}
+
+#[test]
+fn test_item_iter_rev() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups | core::DbAllowIntDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for v in 1..4u32 {
+            db.set(&"key", &v).unwrap();
+        }
+        db.set(&"other", &10u32).unwrap();
+
+        let iter = db.item_iter_rev(&"key").unwrap();
+        assert_eq!(iter.len(), 3);
+        let values: Vec<u32> = iter.map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec![3, 2, 1]);
+
+        let values: Vec<u32> = db.item_iter_rev(&"other").unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec![10]);
+
+        let mut iter = db.item_iter_rev(&"missing").unwrap();
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_cursor_duplicate_count_for_key() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for &(k, cnt) in &[("a", 3), ("b", 1), ("c", 2)] {
+            for i in 0..cnt {
+                db.set(&k, &format!("v{}", i)).unwrap();
+            }
+        }
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_item(&"a", &"v1").unwrap();
+
+        assert_eq!(cursor.duplicate_count_for_key(&"a").unwrap(), 3);
+        assert_eq!(cursor.duplicate_count_for_key(&"b").unwrap(), 1);
+        assert_eq!(cursor.duplicate_count_for_key(&"c").unwrap(), 2);
+        assert_eq!(cursor.duplicate_count_for_key(&"missing").unwrap(), 0);
+
+        let (k, v) = cursor.get::<&str, &str>().unwrap();
+        assert_eq!((k, v), ("a", "v1"));
+        cursor.to_next_item().unwrap();
+        assert_eq!(cursor.get_value::<&str>().unwrap(), "v2");
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_cursor_duplicate_count_for_key_no_dups() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for k in &["a", "b", "c"] {
+            db.set(k, k).unwrap();
+        }
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_key(&"b").unwrap();
+
+        assert_eq!(cursor.duplicate_count_for_key(&"a").unwrap(), 1);
+        assert_eq!(cursor.duplicate_count_for_key(&"missing").unwrap(), 0);
+        assert_eq!(cursor.get::<&str, &str>().unwrap(), ("b", "b"));
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_readonly_database_reads() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        {
+            let db = txn.bind(&db);
+            for k in &["a", "b", "c"] {
+                db.set(k, &"1").unwrap();
+                db.set(k, &"2").unwrap();
+            }
+        }
+        txn.commit().unwrap();
+    }
+
+    let reader = env.get_reader().unwrap();
+    let db: core::ReadonlyDatabase = reader.bind(&db);
+    assert_eq!(db.get::<&str>(&"b").unwrap(), "1");
+    assert_eq!(db.stat().unwrap().ms_entries, 6);
+    assert_eq!(db.iter().unwrap().count(), 3);
+    assert_eq!(db.keyrange(&"b", &"c").unwrap().count(), 2);
+    assert_eq!(db.item_iter(&"a").unwrap().count(), 2);
+    assert_eq!(db.get_page::<&str, &str>(Some(&"a"), 10).unwrap().len(), 2);
+
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_last().unwrap();
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("c", "2"));
+    assert_eq!(cursor.count_items().unwrap(), 2);
+    let keys: Vec<String> = cursor.into_iter_from_current(core::Step::Prev)
+        .map(|cv| cv.get_key()).collect();
+    assert_eq!(keys, vec!["c", "c", "b", "b", "a", "a"]);
+}
+
+#[test]
+fn test_page_utilization() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        {
+            let db = txn.bind(&db);
+            for i in 0..2000u32 {
+                db.set(&format!("key{:05}", i), &vec![0u8; 100]).unwrap();
+            }
+        }
+        txn.commit().unwrap();
+    }
+
+    let reader = env.get_reader().unwrap();
+    let full = reader.bind(&db).page_utilization().unwrap();
+    assert!(full > 0.0 && full <= 1.0, "utilization {}", full);
+    drop(reader);
+
+    {
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).clear().unwrap();
+        txn.commit().unwrap();
+    }
+    let reader = env.get_reader().unwrap();
+    let empty = reader.bind(&db).page_utilization().unwrap();
+    assert_eq!(empty, 0.0);
+}
+
+#[test]
+fn test_bind_many() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let users = env.create_db("users", DbFlags::empty()).unwrap();
+    let posts = env.create_db("posts", DbFlags::empty()).unwrap();
+    let tags = env.create_db("tags", core::DbAllowDups).unwrap();
+
+    {
+        let txn = env.new_transaction().unwrap();
+        {
+            let [u, p, t] = txn.bind_many([&users, &posts, &tags]);
+            u.set(&"alice", &"Alice").unwrap();
+            p.set(&"post1", &"alice").unwrap();
+            t.set(&"post1", &"rust").unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    {
+        // aborted writes to all of them are discarded
+        let txn = env.new_transaction().unwrap();
+        {
+            let (u, p, t) = txn.bind_tuple((&users, &posts, &tags));
+            u.set(&"bob", &"Bob").unwrap();
+            p.set(&"post2", &"bob").unwrap();
+            t.set(&"post1", &"lmdb").unwrap();
+        }
+        txn.abort();
+    }
+
+    let reader = env.get_reader().unwrap();
+    let (u, p, t) = (reader.bind(&users), reader.bind(&posts), reader.bind(&tags));
+    assert_eq!(u.get::<&str>(&"alice").unwrap(), "Alice");
+    assert_eq!(p.get::<&str>(&"post1").unwrap(), "alice");
+    assert_eq!(t.item_iter(&"post1").unwrap().len(), 1);
+    assert!(u.get::<&str>(&"bob").is_err());
+    assert!(p.get::<&str>(&"post2").is_err());
+}
+
+#[test]
+fn test_open_all_databases() {
+    let path = next_path();
+    {
+        let env = EnvBuilder::new().max_dbs(5).open(&path, USER_DIR).unwrap();
+        let a = env.create_db("a", core::DbAllowDups).unwrap();
+        env.create_db("b", DbFlags::empty()).unwrap();
+        let main = env.get_default_db(DbFlags::empty()).unwrap();
+        let txn = env.new_transaction().unwrap();
+        {
+            txn.bind(&main).set(&"plain", &"value").unwrap();
+            let a = txn.bind(&a);
+            a.set(&"k", &"1").unwrap();
+            a.set(&"k", &"2").unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let env = EnvBuilder::new().max_dbs(5).open(&path, USER_DIR).unwrap();
+    let handles = env.open_all_databases().unwrap();
+    let mut names: Vec<&String> = handles.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["a", "b"]);
+
+    let reader = env.get_reader().unwrap();
+    let a = reader.bind(&handles["a"]);
+    assert_eq!(a.item_iter(&"k").unwrap().len(), 2);
+}
+
+#[test]
+fn test_flags_display_and_parse() {
+    use std::str::FromStr;
+    use core::{EnvFlags, EnvCreateFlags};
+
+    for &flag in &[core::DbReverseKey, core::DbAllowDups, core::DbIntKey, core::DbDupFixed,
+                   core::DbAllowIntDups, core::DbReversedDups, core::DbCreate] {
+        assert_eq!(DbFlags::from_str(&flag.to_string()).unwrap(), flag);
+    }
+    for &flag in &[core::EnvNoSync, EnvNoMetaSync, core::EnvMapAsync, EnvNoMemInit] {
+        assert_eq!(EnvFlags::from_str(&flag.to_string()).unwrap(), flag);
+    }
+    for &flag in &[core::EnvCreateFixedMap, core::EnvCreateNoSubDir, core::EnvCreateNoSync,
+                   core::EnvCreateReadOnly, core::EnvCreateNoMetaSync, core::EnvCreateWriteMap,
+                   core::EnvCreataMapAsync, core::EnvCreateNoTls, core::EnvCreateNoLock,
+                   core::EnvCreateNoReadAhead, core::EnvCreateNoMemInit] {
+        assert_eq!(EnvCreateFlags::from_str(&flag.to_string()).unwrap(), flag);
+    }
+
+    let flags = core::DbAllowDups | core::DbIntKey;
+    assert_eq!(flags.to_string(), "DUPSORT|INTEGERKEY");
+    assert_eq!(DbFlags::from_str("integerkey | DupSort").unwrap(), flags);
+    assert_eq!(DbFlags::empty().to_string(), "");
+    assert_eq!(DbFlags::from_str("").unwrap(), DbFlags::empty());
+
+    let env_flags = core::EnvCreateNoTls | core::EnvCreateReadOnly | core::EnvCreateNoSubDir;
+    assert_eq!(env_flags.to_string(), "NOSUBDIR|RDONLY|NOTLS");
+    assert_eq!(EnvCreateFlags::from_str(&env_flags.to_string()).unwrap(), env_flags);
+}
+
+#[test]
+fn test_flags_debug() {
+    use core::{EnvFlags, EnvCopyFlags};
+
+    let flags = core::DbAllowDups | core::DbIntKey;
+    assert_eq!(format!("{:?}", flags), "DbFlags(DUPSORT|INTEGERKEY, 0xc)");
+    assert_eq!(format!("{:?}", DbFlags::empty()), "DbFlags(0x0)");
+    assert_eq!(format!("{:?}", EnvFlags::empty() | core::EnvNoSync), "EnvFlags(NOSYNC, 0x10000)");
+    assert_eq!(format!("{:?}", core::EnvCreateNoTls), "EnvCreateFlags(NOTLS, 0x200000)");
+    assert_eq!(format!("{:?}", core::EnvCopyCompact), "EnvCopyFlags(CP_COMPACT, 0x1)");
+    assert_eq!(format!("{:?}", EnvCopyFlags::empty()), "EnvCopyFlags(0x0)");
+}
+
+#[test]
+fn test_flags_parse_errors() {
+    use std::str::FromStr;
+    use core::EnvFlags;
+
+    match DbFlags::from_str("DUPSORT|BOGUS") {
+        Err(MdbError::StateError(msg)) => assert!(msg.contains("BOGUS"), "{}", msg),
+        other => panic!("Unexpected result {:?}", other),
+    }
+    assert!(DbFlags::from_str("DUPSORT||INTEGERKEY").is_err());
+    assert!(EnvFlags::from_str("RDONLY").is_err());
+}
+
+#[test]
+fn test_invalidate_db_cache() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let db = env.create_db("a", DbFlags::empty()).unwrap();
+    assert!(env.is_db_cached("a"));
+
+    env.invalidate_db_cache("a");
+    assert!(!env.is_db_cached("a"));
+    // unknown names are ignored
+    env.invalidate_db_cache("missing");
+
+    // next access opens it again and the handle is usable
+    let reopened = env.get_db("a", DbFlags::empty()).unwrap();
+    assert!(env.is_db_cached("a"));
+    let txn = env.new_transaction().unwrap();
+    {
+        txn.bind(&db).set(&"key", &"old").unwrap();
+        assert_eq!(txn.bind(&reopened).get::<&str>(&"key").unwrap(), "old");
+    }
+    txn.abort();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_env_config_from_toml() {
+    use config::EnvConfig;
+
+    let path = next_path();
+    let src = format!("path = {:?}\nmap_size = 10485760\nmax_dbs = 4\nflags = [\"NOTLS\", \"nometasync\"]\n",
+                      path.to_str().unwrap());
+    let cfg: EnvConfig = toml::from_str(&src).unwrap();
+    assert_eq!(cfg.mode, 0o644);
+    assert!(!cfg.read_only);
+
+    let env = cfg.open().unwrap();
+    let flags = env.get_all_flags().unwrap();
+    assert!(flags.contains(core::EnvCreateNoTls));
+    assert!(flags.contains(core::EnvCreateNoMetaSync));
+    assert!(!flags.contains(core::EnvCreateReadOnly));
+    env.create_db("first", DbFlags::empty()).unwrap();
+
+    let bad: EnvConfig = toml::from_str(&format!("path = {:?}\nflags = [\"NOPE\"]\n",
+                                                 next_path().to_str().unwrap())).unwrap();
+    match bad.open() {
+        Err(MdbError::StateError(_)) => (),
+        _ => panic!("unknown flag name should be rejected"),
+    }
+}
+
+#[test]
+fn test_get_or_default() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"num", &42u64).unwrap();
+        db.set(&"str", &"hello").unwrap();
+
+        assert_eq!(db.get_or_default::<_, u64>(&"num").unwrap(), 42);
+        assert_eq!(db.get_or_default::<_, u64>(&"missing").unwrap(), 0);
+        assert_eq!(db.get_or_default::<_, String>(&"str").unwrap(), "hello");
+        assert_eq!(db.get_or_default::<_, String>(&"missing").unwrap(), "");
+        assert_eq!(db.get_with_default(&"missing", 7u32).unwrap(), 7);
+    }
+}
+
+#[test]
+fn test_db_name() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let default = env.get_default_db(DbFlags::empty()).unwrap();
+    assert_eq!(plain.name(), "plain");
+    assert_eq!(default.name(), "");
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&plain);
+        assert_eq!(db.name(), "plain");
+        assert_eq!(txn.bind(&default).name(), "");
+
+        match db.count_dups(&"key") {
+            Err(MdbError::StateError(msg)) => assert!(msg.contains("'plain'"), "unexpected message: {}", msg),
+            other => panic!("expected StateError, got {:?}", other),
+        }
+
+        let fresh = txn.open_db("fresh", DbFlags::empty()).unwrap();
+        assert_eq!(fresh.name(), "fresh");
+    }
+}
+
+#[test]
+fn test_error_with_context() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    let err = db.get::<&str>(&"missing").unwrap_err().with_context("loading config");
+    match err {
+        MdbError::Other(code, ref msg) => {
+            assert_eq!(code, ffi::MDB_NOTFOUND);
+            assert_eq!(msg, "loading config: not found");
+        },
+        ref e => panic!("unexpected error {:?}", e),
+    }
+
+    let nested = err.with_context(format!("db '{}'", db.name()));
+    assert_eq!(nested.to_string(), format!("{}: db '': loading config: not found", ffi::MDB_NOTFOUND));
+
+    let state = MdbError::StateError("bad state".to_owned()).with_context("op");
+    assert_eq!(state.to_string(), "0: op: bad state");
+}
+
+#[test]
+fn test_reader_lag() {
+    let env = EnvBuilder::new().flags(core::EnvCreateNoTls).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let lag = env.reader_lag().unwrap();
+    assert_eq!(lag.oldest_reader_txnid, None);
+    assert_eq!(lag.lag, 0);
+
+    let reader = env.get_reader().unwrap();
+    for i in 0..3u32 {
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&i, &i).unwrap();
+        txn.commit().unwrap();
+    }
+
+    let lag = env.reader_lag().unwrap();
+    assert!(lag.oldest_reader_txnid.is_some());
+    assert!(lag.lag >= 2, "unexpected lag {:?}", lag);
+    assert_eq!(lag.latest_txnid, env.info().unwrap().me_last_txnid as u64);
+
+    drop(reader);
+    env.reader_check().unwrap();
+    assert_eq!(env.reader_lag().unwrap().lag, 0);
+}
+
+#[test]
+fn test_write_chunked() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let before = env.info().unwrap().me_last_txnid;
+    let written = env.write_chunked(0..95u32, 10, |txn, i| txn.bind(&db).set(&i, &i)).unwrap();
+    assert_eq!(written, 95);
+    assert_eq!(env.info().unwrap().me_last_txnid - before, 10);
+
+    // failure in the middle of the third chunk keeps first two
+    let written = env.write_chunked(100..200u32, 10, |txn, i| {
+        if i == 125 {
+            Err(MdbError::StateError("injected".to_owned()))
+        } else {
+            txn.bind(&db).set(&i, &i)
+        }
+    });
+    match written {
+        Err(e) => {
+            assert_eq!(e.committed, 20);
+            assert_eq!(e.error, MdbError::StateError("injected".to_owned()));
+        },
+        other => panic!("expected failure, got {:?}", other),
+    }
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.stat().unwrap().ms_entries, 95 + 20);
+    assert!(db.get::<u32>(&119u32).is_ok());
+    assert!(db.get::<u32>(&120u32).is_err());
+
+    assert!(env.write_chunked(0..1u32, 0, |_, _| Ok(())).is_err());
+}
+
+#[test]
+fn test_write_chunked_grows_map() {
+    let env = EnvBuilder::new().map_size(0x10000u64).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let initial = env.info().unwrap().me_mapsize;
+
+    let value = [0x5Au8; 0xFF];
+    let res = env.write_chunked(0..400u32, 100, |txn, i| txn.bind(&db).set(&i, &&value[..]));
+    assert!(res.unwrap_err().error.is_map_full());
+    assert_eq!(env.info().unwrap().me_mapsize, initial);
+
+    let written = unsafe {
+        env.write_chunked_growing(0..400u32, 100, |txn, i| txn.bind(&db).set(&i, &&value[..])).unwrap()
+    };
+    assert_eq!(written, 400);
+    assert!(env.info().unwrap().me_mapsize > initial);
+
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).stat().unwrap().ms_entries, 400);
+}
+
+#[test]
+fn test_cursor_send() {
+    let env = EnvBuilder::new().flags(core::EnvCreateNoTls).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        let db = txn.bind(&db);
+        for i in 0..5u32 {
+            db.set(&i, &(i * 10)).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let reader = env.get_reader().unwrap();
+    let rdb = reader.bind(&db);
+    let cursor = unsafe { rdb.new_cursor().unwrap().into_send().unwrap() };
+    let values = thread::scope(|s| {
+        s.spawn(move || {
+            let mut cursor = cursor;
+            let mut values = Vec::new();
+            let mut res = cursor.to_first();
+            while res.is_ok() {
+                values.push(cursor.get_value::<u32>().unwrap());
+                res = cursor.to_next_key();
+            }
+            values
+        }).join().unwrap()
+    });
+    assert_eq!(values, vec![0, 10, 20, 30, 40]);
+
+    // environments with TLS refuse
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let reader = env.get_reader().unwrap();
+    let rdb = reader.bind(&db);
+    assert!(unsafe { rdb.new_cursor().unwrap().into_send() }.is_err());
+}
+
+#[test]
+fn test_del_items_range() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let series = env.create_db("series", core::DbAllowDups | core::DbDupFixed | core::DbAllowIntDups).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&series);
+        for i in 0..10u32 {
+            db.set(&"a", &i).unwrap();
+            db.set(&"b", &i).unwrap();
+        }
+
+        assert_eq!(db.del_items_range(&"a", &3u32, &7u32).unwrap(), 4);
+        let left: Vec<u32> = db.item_iter(&"a").unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(left, vec![0, 1, 2, 7, 8, 9]);
+        // neighbouring key is intact
+        assert_eq!(db.item_iter(&"b").unwrap().count(), 10);
+
+        // empty intersection and missing key
+        assert_eq!(db.del_items_range(&"a", &3u32, &7u32).unwrap(), 0);
+        assert_eq!(db.del_items_range(&"a", &5u32, &1u32).unwrap(), 0);
+        assert_eq!(db.del_items_range(&"zzz", &0u32, &100u32).unwrap(), 0);
+
+        // deleting everything removes the key, but stops there
+        assert_eq!(db.del_items_range(&"a", &0u32, &100u32).unwrap(), 6);
+        assert!(db.get::<u32>(&"a").is_err());
+        assert_eq!(db.item_iter(&"b").unwrap().count(), 10);
+
+        assert!(txn.bind(&plain).del_items_range(&"a", &0u32, &1u32).is_err());
+    }
+}
+
+#[test]
+fn test_try_del() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    db.set(&"key", &"value").unwrap();
+    assert!(db.try_del(&"key").unwrap());
+    assert!(!db.try_del(&"key").unwrap());
+    assert!(db.get::<&str>(&"key").is_err());
+}
+
+#[test]
+fn test_keyrange_reverse_key() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbReverseKey).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for key in &["a1", "b1", "c1", "a2", "b2", "c2"] {
+            db.set(key, &"").unwrap();
+        }
+
+        // ordered by the last byte first
+        let all: Vec<&str> = db.iter().unwrap().map(|cv| cv.get_key()).collect();
+        assert_eq!(all, vec!["a1", "b1", "c1", "a2", "b2", "c2"]);
+
+        let keys: Vec<&str> = db.keyrange(&"b1", &"a2").unwrap().map(|cv| cv.get_key()).collect();
+        assert_eq!(keys, vec!["b1", "c1", "a2"]);
+        let keys: Vec<&str> = db.keyrange_from_to(&"b1", &"a2").unwrap().map(|cv| cv.get_key()).collect();
+        assert_eq!(keys, vec!["b1", "c1"]);
+
+        // natural order bounds are reversed for this db
+        match db.keyrange(&"a2", &"b1") {
+            Err(MdbError::StateError(_)) => (),
+            Err(e) => panic!("unexpected error {:?}", e),
+            Ok(_) => panic!("reversed bounds should be rejected"),
+        }
+        assert!(db.keyrange_owned(b"a2".to_vec(), b"b1".to_vec()).is_err());
+    }
+}
+
+#[test]
+fn test_count_forward_to() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for key in &["a", "b", "c", "d", "e"] {
+        db.set(key, &"1").unwrap();
+    }
+    db.set(&"c", &"2").unwrap();
+
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_key(&"b").unwrap();
+    // b, c/1, c/2, d
+    assert_eq!(cursor.count_forward_to(&"d").unwrap(), 4);
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("b", "1"));
+    // past the last key
+    assert_eq!(cursor.count_forward_to(&"z").unwrap(), 5);
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("b", "1"));
+    assert_eq!(cursor.count_forward_to(&"a").unwrap(), 0);
+
+    cursor.to_gte_item(&"c", &"2").unwrap();
+    assert_eq!(cursor.count_forward_to(&"c").unwrap(), 1);
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("c", "2"));
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let plain = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&plain);
+    for key in &["a", "b", "c", "d", "e"] {
+        db.set(key, key).unwrap();
+    }
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_key(&"b").unwrap();
+    assert_eq!(cursor.count_forward_to(&"d").unwrap(), 3);
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("b", "b"));
+    assert_eq!(cursor.count_forward_to(&"z").unwrap(), 4);
+    assert_eq!(cursor.get::<&str, &str>().unwrap(), ("b", "b"));
+}
+
+#[test]
+fn test_int_database() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let ints = env.create_int_db::<u64, u64>("ints").unwrap();
+
+    let keys: Vec<u64> = vec![300, 2, 70000, 1, 256, 65536, 9];
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind_typed(&ints);
+        for &k in &keys {
+            db.set(&k, &(k * 2)).unwrap();
+        }
+        db.del(&9).unwrap();
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    {
+        let db = reader.bind_typed(&ints);
+        assert_eq!(db.get(&300).unwrap(), Some(600));
+
+        let all: Vec<u64> = db.keys().unwrap().collect();
+        assert_eq!(all, vec![1, 2, 256, 300, 65536, 70000]);
+
+        let range: Vec<(u64, u64)> = db.range(&256, &65536).unwrap().collect();
+        assert_eq!(range, vec![(256, 512), (300, 600), (65536, 131072)]);
+    }
+    drop(reader);
+
+    // same db reopened with narrower keys
+    match env.create_int_db::<u32, u64>("ints") {
+        Err(MdbError::StateError(msg)) => assert!(msg.contains("8-byte"), "unexpected message: {}", msg),
+        other => panic!("expected width mismatch, got {:?}", other.map(|_| ())),
+    }
+    assert!(env.create_int_db::<u64, u64>("ints").is_ok());
+}
+
+#[test]
+fn test_with_write_txn() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let res = env.with_write_txn(|txn| {
+        let db = txn.bind(&db);
+        try!(db.set(&"committed", &"yes"));
+        db.get::<String>(&"committed")
+    });
+    assert_eq!(res.unwrap(), "yes");
+
+    let res: core::MdbResult<()> = env.with_write_txn(|txn| {
+        try!(txn.bind(&db).set(&"aborted", &"yes"));
+        Err(MdbError::StateError("rollback".to_owned()))
+    });
+    assert!(res.is_err());
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.get::<&str>(&"committed").unwrap(), "yes");
+    assert!(db.get::<&str>(&"aborted").is_err());
+}
+
+#[test]
+fn test_bad_val_size() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let max = env.get_maxkeysize() as usize;
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let big = vec![1u8; max + 1];
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&plain);
+        match db.set(&"", &"value") {
+            Err(MdbError::BadValSize { what: KeyOrValue::Key, size: 0, .. }) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+        match db.set(&big, &"value") {
+            Err(MdbError::BadValSize { what: KeyOrValue::Key, size, max: m }) => {
+                assert_eq!(size, max + 1);
+                assert_eq!(m, max);
+            },
+            other => panic!("unexpected result {:?}", other),
+        }
+        // big values are fine without duplicates
+        db.set(&"key", &big).unwrap();
+
+        let db = txn.bind(&dups);
+        match db.set(&"key", &big) {
+            Err(MdbError::BadValSize { what: KeyOrValue::Value, size, .. }) => assert_eq!(size, max + 1),
+            other => panic!("unexpected result {:?}", other),
+        }
+        let mut cursor = db.new_cursor().unwrap();
+        match cursor.set(&"key", &big, 0) {
+            Err(MdbError::BadValSize { what: KeyOrValue::Value, .. }) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+    txn.abort();
+
+    // without checks LMDB error is reported as is
+    let env = EnvBuilder::new().check_sizes(false).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    match txn.bind(&db).set(&big, &"value") {
+        Err(MdbError::Other(code, _)) => assert_eq!(code, ffi::MDB_BAD_VALSIZE),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn test_concurrent_create_db() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let barrier = Arc::new(Barrier::new(16));
+
+    let threads: Vec<_> = (0..16).map(|_| {
+        let env = env.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            barrier.wait();
+            env.create_db("same", DbFlags::empty()).map(|h| format!("{:?}", h))
+        })
+    }).collect();
+
+    let handles: Vec<String> = threads.into_iter()
+        .map(|t| t.join().unwrap().unwrap())
+        .collect();
+    assert!(handles.iter().all(|h| *h == handles[0]), "handles differ: {:?}", handles);
+    assert!(env.is_db_cached("same"));
+}
+
+#[test]
+fn test_iter_from() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for key in &["b", "d", "f"] {
+        db.set(key, &"").unwrap();
+    }
+
+    let keys = |start: &str, inclusive: bool| -> Vec<String> {
+        db.iter_from(&start, inclusive).unwrap().map(|cv| cv.get_key()).collect()
+    };
+    assert_eq!(keys("d", true), vec!["d", "f"]);
+    assert_eq!(keys("d", false), vec!["f"]);
+    // missing start key behaves the same either way
+    assert_eq!(keys("c", true), vec!["d", "f"]);
+    assert_eq!(keys("c", false), vec!["d", "f"]);
+    assert_eq!(keys("a", false), vec!["b", "d", "f"]);
+    assert!(keys("f", false).is_empty());
+    assert!(keys("g", true).is_empty());
+}
+
+#[test]
+fn test_append_or_set() {
+    use core::AppendOutcome;
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        // big-endian keys sort numerically
+        let keys: Vec<u32> = vec![1, 2, 3, 5, 4, 6, 7, 9, 8, 10];
+        let db = txn.bind(&plain);
+        let mut fallbacks = 0;
+        for k in &keys {
+            let key = k.to_be_bytes().to_vec();
+            if db.append_or_set(&key, k).unwrap() == AppendOutcome::Set {
+                fallbacks += 1;
+            }
+        }
+        assert_eq!(fallbacks, 2);
+        let stored: Vec<u32> = db.iter().unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(stored, (1..11).collect::<Vec<u32>>());
+
+        let db = txn.bind(&dups);
+        let outcomes: Vec<AppendOutcome> = ["a", "c", "b", "d"].iter()
+            .map(|v| db.append_duplicate_or_set(&"key", v).unwrap())
+            .collect();
+        assert_eq!(outcomes, vec![AppendOutcome::Appended, AppendOutcome::Appended,
+                                  AppendOutcome::Set, AppendOutcome::Appended]);
+        let values: Vec<&str> = db.item_iter(&"key").unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec!["a", "b", "c", "d"]);
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_print_debug_info() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let _cursor = db.new_cursor().unwrap();
+        let _other = db.new_cursor().unwrap();
+        let info = txn.debug_info();
+        assert!(info.contains("state Normal, read-write"), "{}", info);
+        assert!(info.contains(": 2 open cursor(s)"), "{}", info);
+        assert!(!info.contains("parent"), "{}", info);
+        txn.print_debug_info();
+
+        let child = txn.new_child().unwrap();
+        let info = child.debug_info();
+        assert!(info.contains("parent: "), "{}", info);
+        assert!(info.contains("no open cursors"), "{}", info);
+    }
+    assert!(txn.debug_info().contains("no open cursors"));
+    txn.abort();
+
+    let mut reader = env.get_reader().unwrap();
+    reader.reset();
+    assert!(reader.debug_info().contains("read-only"));
+    reader.abort();
+    let info = reader.debug_info();
+    assert!(!info.contains("state Normal"), "{}", info);
+    reader.print_debug_info();
+}
+
+#[test]
+fn test_del_many() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&plain);
+        for key in &["a", "b", "c", "d", "e"] {
+            db.set(key, &"").unwrap();
+        }
+        let report = db.del_many(&["e", "x", "b", "a", "y"]).unwrap();
+        assert_eq!(report, core::DelManyReport { deleted: 3, missing: 2 });
+        let left: Vec<&str> = db.iter().unwrap().map(|cv| cv.get_key()).collect();
+        assert_eq!(left, vec!["c", "d"]);
+
+        let db = txn.bind(&dups);
+        for key in &["a", "b"] {
+            for value in &["1", "2", "3"] {
+                db.set(key, value).unwrap();
+            }
+        }
+        let report = db.del_many(&["a", "z"]).unwrap();
+        assert_eq!((report.deleted, report.missing), (1, 1));
+        assert!(db.get::<&str>(&"a").is_err());
+        assert_eq!(db.item_iter(&"b").unwrap().count(), 3);
+
+        // repeated keys are deleted and counted once
+        let db = txn.bind(&plain);
+        let report = db.del_many(&["c", "c", "x", "x"]).unwrap();
+        assert_eq!(report, core::DelManyReport { deleted: 1, missing: 1 });
+        let left: Vec<&str> = db.iter().unwrap().map(|cv| cv.get_key()).collect();
+        assert_eq!(left, vec!["d"]);
+    }
+}
+
+#[test]
+fn test_try_iter_keys() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"a", &"1").unwrap();
+        db.set(&(&b"b\xff"[..]), &"2").unwrap();
+        db.set(&"c", &(&b"\xfe"[..])).unwrap();
+
+        let keys: Vec<core::MdbResult<String>> = db.try_iter_keys().unwrap().collect();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[0].as_ref().unwrap(), "a");
+        match keys[1] {
+            Err(MdbError::Other(_, ref msg)) => assert!(msg.contains("conversion failed")),
+            ref other => panic!("unexpected item {:?}", other),
+        }
+        assert_eq!(keys[2].as_ref().unwrap(), "c");
+
+        let values: Vec<core::MdbResult<String>> = db.try_iter_values().unwrap().collect();
+        assert!(values[0].is_ok() && values[1].is_ok());
+        assert!(values[2].is_err());
+    }
+    txn.abort();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_copy_to_path_with_progress() {
+    use std::ops::ControlFlow;
+    use std::time::Duration;
+    use core::EnvCopyFlags;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        let db = txn.bind(&db);
+        for i in 0..1000u32 {
+            db.set(&i, &vec![0u8; 100]).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let backup = next_path();
+    fs::create_dir_all(&backup).unwrap();
+    let mut reports = Vec::new();
+    env.copy_to_path_with_progress(&backup, EnvCopyFlags::empty(), Duration::from_millis(1), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    }).unwrap();
+
+    let size = fs::metadata(backup.join("data.mdb")).unwrap().len();
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes_written, size);
+    assert!(last.expected_bytes.unwrap() >= size);
+
+    let copy = EnvBuilder::new().open(&backup, USER_DIR).unwrap();
+    let db = copy.get_default_db(DbFlags::empty()).unwrap();
+    let reader = copy.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).stat().unwrap().ms_entries, 1000);
+
+    // destination file must not exist yet
+    assert!(env.copy_to_path_with_progress(&backup, EnvCopyFlags::empty(), Duration::from_millis(1),
+                                           |_| ControlFlow::Continue(())).is_err());
+}
+
+#[test]
+fn test_checkpoint() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let mut txn = env.new_transaction().unwrap();
+    txn.bind(&db).set(&"a", &"1").unwrap();
+    let first = txn.checkpoint().unwrap();
+
+    // committed data is visible to readers while txn is still open
+    {
+        let reader = env.get_reader().unwrap();
+        assert_eq!(reader.bind(&db).get::<&str>(&"a").unwrap(), "1");
+    }
+
+    txn.bind(&db).set(&"b", &"2").unwrap();
+    let second = txn.checkpoint().unwrap();
+    assert_eq!(second, first + 1);
+    txn.abort();
+
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).get::<&str>(&"b").unwrap(), "2");
+
+    let txn = env.new_transaction().unwrap();
+    let mut child = txn.new_child().unwrap();
+    assert!(match child.checkpoint() { Err(MdbError::StateError(_)) => true, _ => false });
+}
+
+#[test]
+fn test_commit_and_continue() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    let first_id = env.info().unwrap().me_last_txnid;
+    let mut txn = env.new_transaction().unwrap();
+    for i in 0..10000u32 {
+        txn.bind(&db).set(&i, &(i * 2)).unwrap();
+        if (i + 1) % 1000 == 0 {
+            txn = txn.commit_and_continue().unwrap();
+        }
+    }
+    txn.abort();
+    assert_eq!(env.info().unwrap().me_last_txnid - first_id, 10);
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.stat().unwrap().ms_entries, 10000);
+    for i in 0..10000u32 {
+        assert_eq!(db.get::<u32>(&i).unwrap(), i * 2);
+    }
+}
+
+#[test]
+fn test_mdb_value_hash_eq() {
+    use std::collections::HashSet;
+    use traits::ToMdbValue;
+
+    let a = "same".to_owned();
+    let b = "same".to_owned();
+    let c = "other".to_owned();
+    let empty = Vec::<u8>::new();
+
+    let mut set = HashSet::new();
+    assert!(set.insert(a.to_mdb_value()));
+    assert!(!set.insert(b.to_mdb_value()));
+    assert!(set.insert(c.to_mdb_value()));
+    assert!(set.insert(empty.to_mdb_value()));
+    assert_eq!(set.len(), 3);
+    assert_eq!(a.to_mdb_value(), b.to_mdb_value());
+    assert!(a.to_mdb_value() != c.to_mdb_value());
+}
+
+#[test]
+fn test_read_only_mapped_view() {
+    let path = next_path();
+    {
+        let env = EnvBuilder::new().open(&path, USER_DIR).unwrap();
+        let db = env.get_default_db(DbFlags::empty()).unwrap();
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&"key", &"mapped value").unwrap();
+        txn.commit().unwrap();
+        assert!(env.read_only_mapped_view().is_err());
+    }
+
+    let env = EnvBuilder::new()
+        .flags(core::EnvCreateReadOnly)
+        .open(&path, USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let view = env.read_only_mapped_view().unwrap();
+
+    let value = view.get(&db, &"key").unwrap();
+    assert!(view.get(&db, &"missing").unwrap_err().is_not_found());
+    // reader used for lookup is gone, slice is still readable
+    let reader = env.get_reader().unwrap();
+    reader.bind(&db).get::<&str>(&"key").unwrap();
+    drop(reader);
+    assert_eq!(value, b"mapped value");
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn test_scan_ordered() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for k in &["delta", "alpha", "charlie", "bravo"] {
+            db.set(k, &k.len()).unwrap();
+        }
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    let map = db.scan_ordered::<String, usize>().unwrap();
+    let iterated: Vec<(String, usize)> = db.iter().unwrap().map(|cv| cv.get()).collect();
+    let scanned: Vec<(String, usize)> = map.into_iter().collect();
+    assert_eq!(scanned, iterated);
+    assert_eq!(scanned[0].0, "alpha");
+}
+
+#[test]
+fn test_cursor_iter_nth_last() {
+    let env = EnvBuilder::new().max_dbs(2).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let (plain, dups) = txn.bind_tuple((&plain, &dups));
+        for i in 0..10 {
+            let key = format!("k{}", i);
+            plain.set(&key, &format!("v{}", i)).unwrap();
+            for j in 0..3 {
+                dups.set(&key, &format!("v{}-{}", i, j)).unwrap();
+            }
+        }
+    }
+    txn.commit().unwrap();
+
+    // compares against values collected with plain `next`
+    macro_rules! check {
+        ($make:expr) => {{
+            let all: Vec<(String, String)> = $make.map(|cv| cv.get()).collect();
+            for n in 0..all.len() + 2 {
+                let got = $make.nth(n).map(|cv| cv.get::<String, String>());
+                assert_eq!(got.as_ref(), all.get(n));
+            }
+            let mut it = $make;
+            it.next();
+            assert_eq!(it.nth(1).map(|cv| cv.get::<String, String>()).as_ref(), all.get(2));
+            assert_eq!(it.last().map(|cv| cv.get::<String, String>()).as_ref(),
+                       if all.len() > 3 { all.last() } else { None });
+            assert_eq!($make.last().map(|cv| cv.get::<String, String>()).as_ref(), all.last());
+        }}
+    }
+
+    let reader = env.get_reader().unwrap();
+    for handle in &[&plain, &dups] {
+        let db = reader.bind(handle);
+
+        check!(db.iter().unwrap());
+        check!(db.keyrange_from(&"k7").unwrap());
+        check!(db.keyrange_to(&"k5").unwrap());
+        check!(db.keyrange_to(&"k55").unwrap());
+        check!(db.keyrange(&"k2", &"k5").unwrap());
+        check!(db.keyrange_from_to(&"k2", &"k5").unwrap());
+        check!(db.keyrange_from_to(&"k", &"z").unwrap());
+        check!(db.keyrange(&"k95", &"z").unwrap());
+        check!(db.keyrange_owned(b"k25".to_vec(), b"k55".to_vec()).unwrap());
+    }
+
+    let db = reader.bind(&dups);
+    check!(db.item_iter(&"k3").unwrap());
+    check!(db.item_iter_owned(b"k9".to_vec()).unwrap());
+}
+
+#[test]
+fn test_error_kind_conversions() {
+    use std::io::ErrorKind;
+
+    assert_eq!(ErrorKind::from(MdbError::NotFound), ErrorKind::NotFound);
+    assert_eq!(ErrorKind::from(MdbError::KeyExists), ErrorKind::AlreadyExists);
+    assert_eq!(ErrorKind::from(MdbError::Corrupted), ErrorKind::InvalidData);
+    assert_eq!(ErrorKind::from(MdbError::InvalidPath), ErrorKind::InvalidInput);
+    assert_eq!(ErrorKind::from(MdbError::TxnFull), ErrorKind::Other);
+    assert_eq!(ErrorKind::from(MdbError::new_with_code(::libc::EACCES)), ErrorKind::PermissionDenied);
+
+    assert!(match MdbError::from(ErrorKind::NotFound) { MdbError::NotFound => true, _ => false });
+    assert!(match MdbError::from(ErrorKind::AlreadyExists) { MdbError::KeyExists => true, _ => false });
+    assert!(match MdbError::from(ErrorKind::InvalidData) { MdbError::Corrupted => true, _ => false });
+    assert!(match MdbError::from(ErrorKind::UnexpectedEof) { MdbError::Other(0, _) => true, _ => false });
+
+    for kind in &[ErrorKind::NotFound, ErrorKind::AlreadyExists, ErrorKind::InvalidData,
+                  ErrorKind::PermissionDenied, ErrorKind::InvalidInput, ErrorKind::Interrupted] {
+        assert_eq!(ErrorKind::from(MdbError::from(*kind)), *kind);
+    }
+
+    for err in &[MdbError::NotFound, MdbError::KeyExists, MdbError::InvalidPath, MdbError::TxnFull] {
+        let io_err = ::std::io::Error::from(err.clone());
+        assert_eq!(io_err.kind(), ErrorKind::from(err.clone()));
+        assert_eq!(io_err.to_string(), err.to_string());
+        assert_eq!(io_err.get_ref().and_then(|e| e.downcast_ref::<MdbError>()), Some(err));
+    }
+    assert_eq!(MdbError::from(::std::io::Error::from(MdbError::NotFound).kind()), MdbError::NotFound);
+    assert_eq!(MdbError::from(::std::io::Error::from(MdbError::KeyExists).kind()), MdbError::KeyExists);
+    assert_eq!(MdbError::TxnFull.to_string(), "txn full");
+}
+
+#[test]
+fn test_iter_writes_during_iteration() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    for k in &["b", "d", "f"] {
+        db.set(k, &"v").unwrap();
+    }
+
+    // inserts ahead are visible, inserts behind aren't,
+    // overwriting current key doesn't repeat it
+    let mut seen = Vec::new();
+    for cv in db.iter().unwrap() {
+        let key: String = cv.get_key();
+        if key == "b" {
+            db.set(&"a", &"v").unwrap();
+            db.set(&"c", &"v").unwrap();
+            db.set(&"z", &"v").unwrap();
+        }
+        db.set(&key, &"updated").unwrap();
+        seen.push(key);
+    }
+    assert_eq!(seen, vec!["b", "c", "d", "f", "z"]);
+
+    // deleting current key doesn't skip anything, including
+    // keys on the following pages
+    for i in 0..1000u32 {
+        db.set(&format!("key{:04}", i), &i).unwrap();
+    }
+    let mut count = 0;
+    let mut iter = db.keyrange(&"key0000", &"key9999").unwrap();
+    while let Some(cv) = iter.next() {
+        let key: String = cv.get_key();
+        assert_eq!(key, format!("key{:04}", count));
+        db.del(&key).unwrap();
+        count += 1;
+    }
+    assert!(iter.take_error().is_none());
+    assert_eq!(count, 1000);
+    assert!(db.keyrange(&"key0000", &"key9999").unwrap().next().is_none());
+}
+
+#[test]
+fn test_iter_with_capacity_hint() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for i in 0..100u32 {
+        db.set(&i, &i).unwrap();
+    }
+
+    let count = db.stat().unwrap().ms_entries;
+    let mut iter = db.iter().unwrap().with_capacity_hint(count);
+    assert_eq!(iter.size_hint(), (100, Some(100)));
+    iter.next();
+    assert_eq!(iter.size_hint(), (99, Some(99)));
+
+    let values: Vec<u32> = db.iter().unwrap().with_capacity_hint(count)
+        .map(|cv| cv.get_value()).collect();
+    assert!(values.capacity() >= 100);
+    assert_eq!(values, (0..100).collect::<Vec<u32>>());
+}
+
+#[test]
+fn test_child_prefixes() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    let mut keys = vec!["etc/hosts".to_owned(), "home/a.txt".to_owned(), "home/c/x/y".to_owned(),
+                        "home/c/z".to_owned(), "home/d".to_owned(), "home/d/e".to_owned(),
+                        "var".to_owned()];
+    for i in 0..500 {
+        keys.push(format!("home/b/{:03}", i));
+    }
+    for key in &keys {
+        db.set(key, &"").unwrap();
+    }
+
+    let mut children = db.child_prefixes(b"home/", b'/').unwrap();
+    let listed: Vec<Vec<u8>> = children.by_ref().map(|c| c.unwrap()).collect();
+    assert_eq!(listed, vec![b"a.txt".to_vec(), b"b".to_vec(), b"c".to_vec(), b"d".to_vec()]);
+    assert!(children.visited() < 10, "visited {} keys", children.visited());
+
+    let top: Vec<Vec<u8>> = db.child_prefixes(b"", b'/').unwrap().map(|c| c.unwrap()).collect();
+    assert_eq!(top, vec![b"etc".to_vec(), b"home".to_vec(), b"var".to_vec()]);
+
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_after_prefix(b"home/b/").unwrap();
+    assert_eq!(cursor.get_key::<&str>().unwrap(), "home/c/x/y");
+    assert_eq!(cursor.to_after_prefix(b"\xff\xff"), Err(MdbError::NotFound));
+    assert_eq!(cursor.to_after_prefix(b"var"), Err(MdbError::NotFound));
+
+    db.set(&b"k\xff".to_vec(), &"").unwrap();
+    db.set(&b"l".to_vec(), &"").unwrap();
+    cursor.to_after_prefix(b"k\xff").unwrap();
+    assert_eq!(cursor.get_key::<&[u8]>().unwrap(), b"l");
+}
+
+#[test]
+fn test_cursor_skip() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for i in 0..10u8 {
+        db.set(&i, &"a").unwrap();
+        db.set(&i, &"b").unwrap();
+    }
+
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_first().unwrap();
+    assert_eq!(cursor.skip(0).unwrap(), 0);
+    assert_eq!(cursor.get_key::<u8>().unwrap(), 0);
+    // duplicates aren't counted
+    assert_eq!(cursor.skip(3).unwrap(), 3);
+    assert_eq!(cursor.get_key::<u8>().unwrap(), 3);
+    assert_eq!(cursor.skip(100).unwrap(), 6);
+    assert_eq!(cursor.get_key::<u8>().unwrap(), 9);
+    assert_eq!(cursor.skip(1).unwrap(), 0);
+}
+
+#[test]
+fn test_pop_min_item() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for key in &["a", "b"] {
+        for value in &["3", "1", "2"] {
+            db.set(key, value).unwrap();
+        }
+    }
+
+    let mut popped = Vec::new();
+    while let Some(value) = db.pop_min_item::<_, String>(&"a").unwrap() {
+        popped.push(value);
+    }
+    assert_eq!(popped, vec!["1", "2", "3"]);
+    assert!(db.pop_min_item::<_, String>(&"a").unwrap().is_none());
+    assert_eq!(db.item_iter(&"b").unwrap().count(), 3);
+
+    // deleting shifts the following sibling into place of popped value
+    db.set(&"c", &"aaaa").unwrap();
+    db.set(&"c", &"bbbb").unwrap();
+    assert_eq!(db.pop_min_item(&"c").unwrap(), Some("aaaa".to_owned()));
+    assert_eq!(db.pop_min_item(&"c").unwrap(), Some("bbbb".to_owned()));
+
+    for value in &[30u32, 10, 20] {
+        db.set(&"d", value).unwrap();
+    }
+    assert_eq!(db.pop_min_item::<_, u32>(&"d").unwrap(), Some(10));
+    assert_eq!(db.pop_min_item::<_, u32>(&"d").unwrap(), Some(20));
+}
+
+#[test]
+fn test_validate_at_open() {
+    let path = next_path();
+    let env = EnvBuilder::new().open(&path, USER_DIR).unwrap();
+    assert_eq!(env.open_stats(), core::OpenStats::default());
+    drop(env);
+
+    let env = EnvBuilder::new().validate_at_open(true).open(&path, USER_DIR).unwrap();
+    let stats = env.clone().open_stats();
+    assert!(stats.validated);
+    assert_eq!(stats.dead_readers, 0);
+}
+
+#[test]
+fn test_copy_with_flags() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for i in 0..1000u32 {
+            db.set(&i, &format!("value {}", i)).unwrap();
+        }
+        for i in 0..900u32 {
+            db.del(&i).unwrap();
+        }
+    }
+    txn.commit().unwrap();
+
+    let plain = next_path();
+    let copy2 = next_path();
+    let compact = next_path();
+    for p in &[&plain, &copy2, &compact] {
+        fs::create_dir_all(p).unwrap();
+    }
+    env.copy_to_path(&plain).unwrap();
+    env.copy_to_path_with_flags(&copy2, core::EnvCopyFlags::empty()).unwrap();
+    env.copy_to_path_with_flags(&compact, core::EnvCopyCompact).unwrap();
+
+    let plain_data = fs::read(plain.join("data.mdb")).unwrap();
+    assert_eq!(plain_data, fs::read(copy2.join("data.mdb")).unwrap());
+    assert!(fs::read(compact.join("data.mdb")).unwrap().len() <= plain_data.len());
+
+    let env = EnvBuilder::new().open(&compact, USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind(&db);
+    assert_eq!(db.stat().unwrap().ms_entries, 100);
+    assert_eq!(db.get::<String>(&950u32).unwrap(), "value 950");
+}
+
+#[test]
+fn test_database_writer() {
+    use std::fmt::Write;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    let mut w = db.writer(&"greeting");
+    write!(w, "Hello {}", "world").unwrap();
+    assert!(db.get::<String>(&"greeting").is_err());
+    w.flush().unwrap();
+    assert_eq!(db.get::<String>(&"greeting").unwrap(), "Hello world");
+
+    writeln!(w, "!").unwrap();
+    w.flush().unwrap();
+    assert_eq!(db.get::<String>(&"greeting").unwrap(), "Hello world!\n");
+}
+
+#[test]
+fn test_scan_and_merge() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let left = env.create_db("left", DbFlags::empty()).unwrap();
+    let right = env.create_db("right", DbFlags::empty()).unwrap();
+    let merged = env.create_db("merged", DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let left = txn.bind(&left);
+        let right = txn.bind(&right);
+        let merged = txn.bind(&merged);
+        for &(k, v) in &[(1u32, 10u32), (3, 30), (5, 50)] {
+            left.set(&k, &v).unwrap();
+        }
+        for &(k, v) in &[(2u32, 2u32), (3, 3), (6, 6)] {
+            right.set(&k, &v).unwrap();
+        }
+
+        let written = left.scan_and_merge::<u32, u32, _, _>(&right, |a, b| a + b, &merged).unwrap();
+        assert_eq!(written, 5);
+        let items: Vec<(u32, u32)> = merged.iter_typed().unwrap().collect();
+        assert_eq!(items, vec![(1, 10), (2, 2), (3, 33), (5, 50), (6, 6)]);
+    }
+}
+
+#[test]
+fn test_scan_and_merge_follows_db_ordering() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let left = env.create_db("left", DbFlags::empty()).unwrap();
+    let right = env.create_db("right", DbFlags::empty()).unwrap();
+    let merged = env.create_db("merged", DbFlags::empty()).unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let left = txn.bind(&left);
+        let right = txn.bind(&right);
+        let merged = txn.bind(&merged);
+        // native endian u32 keys are compared bytewise, so 256 comes
+        // before 1 on little endian and `u32::cmp` disagrees with db
+        for &(k, v) in &[(1u32, 1u32), (256, 256), (512, 512)] {
+            left.set(&k, &v).unwrap();
+        }
+        for &(k, v) in &[(1u32, 10u32), (2, 2), (256, 2560), (65536, 65536)] {
+            right.set(&k, &v).unwrap();
+        }
+
+        let written = left.scan_and_merge::<u32, u32, _, _>(&right, |a, b| a + b, &merged).unwrap();
+        assert_eq!(written, 5);
+        let mut items: Vec<(u32, u32)> = merged.iter_typed().unwrap().collect();
+        items.sort();
+        assert_eq!(items, vec![(1, 11), (2, 2), (256, 2816), (512, 512), (65536, 65536)]);
+    }
+}
+
+#[test]
+fn test_lock_implementation_smoke() {
+    // opens and uses env with whichever lock implementation
+    // `robust-mutex` or `posix-sem` features selected
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+    // writers from several threads contend for the write lock
+    let threads: Vec<_> = (0..4u32).map(|i| {
+        let env = env.clone();
+        thread::spawn(move || {
+            for j in 0..25u32 {
+                let txn = env.new_transaction().unwrap();
+                txn.bind(&db).set(&(i * 100 + j), &j).unwrap();
+                txn.commit().unwrap();
+            }
+        })
+    }).collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).stat().unwrap().ms_entries, 100);
+}
+
+#[test]
+fn test_num_opened_databases() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    assert_eq!(env.num_opened_databases(), 0);
+
+    for name in &["a", "b", "c"] {
+        env.create_db(name, DbFlags::empty()).unwrap();
+    }
+    // reopening is served from cache
+    env.get_db("a", DbFlags::empty()).unwrap();
+    assert_eq!(env.num_opened_databases(), 3);
+
+    env.invalidate_db_cache("b");
+    assert_eq!(env.num_opened_databases(), 2);
+}
+
+#[test]
+fn test_item_chunks_rev() {
+    let env = EnvBuilder::new().map_size(64 * 1024 * 1024).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups | core::DbDupFixed | core::DbAllowIntDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    let count = 100000u32;
+    for i in 0..count {
+        db.set(&"key", &i).unwrap();
+    }
+    db.set(&"single", &7u32).unwrap();
+
+    let values = |chunk: &[u8]| -> Vec<u32> {
+        chunk.chunks(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect()
+    };
+
+    let forward: Vec<Vec<u32>> = db.item_chunks(&"key").unwrap().map(|c| values(c.unwrap())).collect();
+    let backward: Vec<Vec<u32>> = db.item_chunks_rev(&"key").unwrap().map(|c| values(c.unwrap())).collect();
+    assert!(forward.len() > 1);
+    assert_eq!(forward.iter().map(|c| c.len()).sum::<usize>(), count as usize);
+
+    let mut reversed_forward = forward.clone();
+    reversed_forward.reverse();
+    assert_eq!(backward, reversed_forward);
+    assert_eq!(*backward[0].last().unwrap(), count - 1);
+
+    // key with a single item and missing key
+    let single: Vec<Vec<u32>> = db.item_chunks_rev(&"single").unwrap().map(|c| values(c.unwrap())).collect();
+    assert_eq!(single, vec![vec![7]]);
+    assert_eq!(db.item_chunks(&"single").unwrap().map(|c| values(c.unwrap())).collect::<Vec<_>>(), vec![vec![7]]);
+    assert_eq!(db.item_chunks(&"missing").unwrap().count(), 0);
+}
+
+#[test]
+fn test_flags_default() {
+    use core::{EnvFlags, EnvCreateFlags, EnvCopyFlags};
+
+    assert_eq!(DbFlags::default(), DbFlags::empty());
+    assert_eq!(EnvFlags::default(), EnvFlags::empty());
+    assert_eq!(EnvCreateFlags::default(), EnvCreateFlags::empty());
+    assert_eq!(EnvCopyFlags::default(), EnvCopyFlags::empty());
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    assert!(env.get_default_db(Default::default()).is_ok());
+}
+
+#[test]
+fn test_version() {
+    let version = ::version();
+    // MDB_PREV_MULTIPLE is used by item_chunks_rev
+    assert!(version >= (0, 9, 19), "{:?}", version);
+    if cfg!(feature = "vendored") && option_env!("LMDB_SYS_USE_SYSTEM") != Some("1") {
+        assert_eq!(version, (ffi::MDB_VERSION_MAJOR, ffi::MDB_VERSION_MINOR, ffi::MDB_VERSION_PATCH));
+    }
+
+    assert_eq!(MdbError::new_with_code(ffi::MDB_PROBLEM), MdbError::Problem);
+    assert_eq!(MdbError::Problem.with_context("commit"),
+               MdbError::Other(ffi::MDB_PROBLEM, "commit: unexpected problem, txn should abort".to_owned()));
+
+    match MdbError::new_with_code(ffi::MDB_BAD_DBI) {
+        MdbError::Other(code, msg) => {
+            assert_eq!(code, ffi::MDB_BAD_DBI);
+            assert!(msg.starts_with("MDB_BAD_DBI"), "{}", msg);
+        },
+        e => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn test_cursor_del_items() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    for value in &["1", "2", "3", "4", "5"] {
+        db.set(&"key", value).unwrap();
+    }
+    db.set(&"other", &"2").unwrap();
+
+    let mut cursor = db.new_cursor().unwrap();
+    cursor.to_key(&"key").unwrap();
+    assert_eq!(cursor.del_items(&["2", "4", "missing", "5"]).unwrap(), 3);
+
+    let left: Vec<String> = db.item_iter(&"key").unwrap().map(|cv| cv.get_value()).collect();
+    assert_eq!(left, vec!["1", "3"]);
+    assert_eq!(db.get::<&str>(&"other").unwrap(), "2");
+}
+
+#[test]
+fn test_typed_db() {
+    // test_single_values and test_keyrange with types fixed once
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let handle = env.get_default_db(DbFlags::empty()).unwrap().typed::<String, String>();
+    let ints = env.create_db("ints", core::DbIntKey).unwrap().typed::<u64, u64>();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind_typed(&handle);
+        let key = "key1".to_owned();
+
+        assert_eq!(db.get(&key).unwrap(), None);
+        db.set(&key, &"value1".to_owned()).unwrap();
+        assert_eq!(db.get(&key).unwrap(), Some("value1".to_owned()));
+        db.set(&key, &"value2".to_owned()).unwrap();
+        assert_eq!(db.get(&key).unwrap(), Some("value2".to_owned()));
+        db.del(&key).unwrap();
+        assert_eq!(db.get(&key).unwrap(), None);
+    }
+
+    let keys: Vec<u64> = vec![1, 2, 3, 4, 5, 6];
+    let values: Vec<u64> = vec![10, 11, 12, 13, 14, 15];
+    {
+        let db = txn.bind_typed(&ints);
+        for (k, v) in keys.iter().zip(values.iter()) {
+            db.set(k, v).unwrap();
+        }
+    }
+    txn.commit().unwrap();
+
+    let reader = env.get_reader().unwrap();
+    let db = reader.bind_typed(&ints);
+    let res: Vec<u64> = db.range(&keys[1], &keys[3]).unwrap().map(|(_, v)| v).collect();
+    assert_eq!(res, &values[1..4]);
+    assert_eq!(db.iter().unwrap().count(), keys.len());
+    assert_eq!(db.get(&7).unwrap(), None);
+
+    assert_eq!(db.keys().unwrap().collect::<Vec<u64>>(), keys);
+    assert_eq!(db.values().unwrap().collect::<Vec<u64>>(), values);
+    let from: Vec<u64> = db.iter_from(&keys[3], false).unwrap().map(|(k, _)| k).collect();
+    assert_eq!(from, &keys[4..]);
+    let to: Vec<u64> = db.iter_to(&keys[2]).unwrap().map(|(k, _)| k).collect();
+    assert_eq!(to, &keys[..2]);
+}
+
+#[test]
+fn test_get_lte() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+
+    for handle in &[&plain, &dups] {
+        let db = txn.bind(handle);
+        for &(k, v) in &[("b", "1"), ("d", "2"), ("f", "3")] {
+            db.set(&k, &v).unwrap();
+        }
+        if handle.name() == "dups" {
+            db.set(&"b", &"0").unwrap();
+            db.set(&"d", &"9").unwrap();
+        }
+        let first_b = if handle.name() == "dups" { "0" } else { "1" };
+
+        // exact match
+        assert_eq!(db.get_lte::<&str, &str>(&"d").unwrap(), Some(("d", "2")));
+        // between keys
+        assert_eq!(db.get_lte::<&str, &str>(&"c").unwrap(), Some(("b", first_b)));
+        assert_eq!(db.get_lte::<&str, &str>(&"e").unwrap(), Some(("d", "2")));
+        // past the last key
+        assert_eq!(db.get_lte::<&str, &str>(&"z").unwrap(), Some(("f", "3")));
+        // before the first key
+        assert_eq!(db.get_lte::<&str, &str>(&"a").unwrap(), None);
+    }
+}
+
+#[test]
+fn test_multimap() {
+    use std::collections::{BTreeMap, BTreeSet};
+    use multimap::MultiMap;
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    assert!(MultiMap::<u32, u32>::new(&plain).is_err());
+    let default = env.get_default_db(DbFlags::empty()).unwrap();
+    match MultiMap::<u32, u32>::new(&default) {
+        Err(MdbError::StateError(msg)) => assert!(msg.contains("default database has"), "{}", msg),
+        other => panic!("expected StateError, got {:?}", other.map(|_| ())),
+    }
+
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let fixed = env.create_db("fixed", core::DbAllowDups | core::DbDupFixed).unwrap();
+    for handle in &[dups, fixed] {
+        let map = MultiMap::<u32, u32>::new(handle).unwrap();
+        let mut reference: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        let txn = env.new_transaction().unwrap();
+        let db = map.bind(&txn);
+
+        let mut seed = 12345u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            seed >> 8
+        };
+        for _ in 0..5000 {
+            let key = next() % 8;
+            let value = next() % 2000;
+            match next() % 10 {
+                0..=5 => {
+                    db.add(&key, &value).unwrap();
+                    reference.entry(key).or_insert_with(BTreeSet::new).insert(value);
+                },
+                6..=8 => {
+                    let expected = reference.get_mut(&key).map(|s| s.remove(&value)).unwrap_or(false);
+                    assert_eq!(db.remove(&key, &value).unwrap(), expected);
+                },
+                _ => {
+                    let expected = reference.remove(&key).map(|s| s.len() as u64).unwrap_or(0);
+                    assert_eq!(db.remove_all(&key).unwrap(), expected);
+                },
+            }
+            assert_eq!(db.contains(&key, &value).unwrap(),
+                       reference.get(&key).map(|s| s.contains(&value)).unwrap_or(false));
+        }
+
+        for key in 0..8 {
+            let expected = reference.get(&key).cloned().unwrap_or_default();
+            assert_eq!(db.count(&key).unwrap(), expected.len() as u64);
+            let values: Vec<u32> = db.values(&key).unwrap().collect();
+            assert_eq!(values.len(), expected.len());
+            assert_eq!(values.into_iter().collect::<BTreeSet<_>>(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_readonly_refresh() {
+    use std::sync::mpsc;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&"counter", &0u32).unwrap();
+        txn.commit().unwrap();
+    }
+
+    let (committed_tx, committed_rx) = mpsc::channel();
+    let (ack_tx, ack_rx) = mpsc::channel::<()>();
+    let writer = {
+        let env = env.clone();
+        thread::spawn(move || {
+            for i in 1..20u32 {
+                let txn = env.new_transaction().unwrap();
+                txn.bind(&db).set(&"counter", &i).unwrap();
+                txn.commit().unwrap();
+                committed_tx.send(i).unwrap();
+                ack_rx.recv().unwrap();
+            }
+        })
+    };
+
+    let mut reader = env.get_reader().unwrap();
+    for i in committed_rx {
+        // snapshot is kept until refresh
+        assert_eq!(reader.bind(&db).get::<u32>(&"counter").unwrap(), i - 1);
+        reader.refresh().unwrap();
+        assert_eq!(reader.bind(&db).get::<u32>(&"counter").unwrap(), i);
+        ack_tx.send(()).unwrap();
+    }
+    writer.join().unwrap();
+}
+
+#[test]
+fn test_database_blob_writer() {
+    use std::io::{self, Write};
+
+    let env = EnvBuilder::new().map_size(16 * 1024 * 1024).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+
+    let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let mut w = db.blob_writer(&"blob");
+    assert_eq!(io::copy(&mut &data[..], &mut w).unwrap(), data.len() as u64);
+    assert!(db.get::<&[u8]>(&"blob").is_err());
+    w.flush().unwrap();
+    assert_eq!(db.get::<&[u8]>(&"blob").unwrap(), &data[..]);
+
+    let long_key = vec![b'k'; 1024];
+    let mut w = db.blob_writer(&long_key);
+    w.write_all(b"value").unwrap();
+    assert_eq!(w.flush().unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_optimistic_update() {
+    use std::cell::Cell;
+    use traits::ToMdbValue;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let set_counter = |value: u32| {
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&"counter", &value).unwrap();
+        txn.commit().unwrap();
+    };
+    set_counter(1);
+
+    // a concurrent writer sneaks in between phases of the first attempt
+    let attempts = Cell::new(0);
+    let res = env.optimistic_update(|reader| {
+        attempts.set(attempts.get() + 1);
+        let value = reader.get(&db, &"counter").unwrap().unwrap();
+        if attempts.get() == 1 {
+            set_counter(100);
+        }
+        Ok(u32::from_mdb_value(&value.to_mdb_value()))
+    }, |txn, value| {
+        txn.bind(&db).set(&"counter", &(value + 1)).unwrap();
+        Ok(value + 1)
+    }).unwrap();
+    assert_eq!(attempts.get(), 2);
+    assert_eq!(res, 101);
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).get::<u32>(&"counter").unwrap(), 101);
+    drop(reader);
+
+    // unrelated commits don't cause retries
+    attempts.set(0);
+    env.optimistic_update(|reader| {
+        attempts.set(attempts.get() + 1);
+        assert!(reader.get(&db, &"missing").unwrap().is_none());
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&"other", &"value").unwrap();
+        txn.commit().unwrap();
+        Ok(())
+    }, |txn, _| txn.bind(&db).set(&"missing", &"found")).unwrap();
+    assert_eq!(attempts.get(), 1);
+
+    // gives up when every attempt conflicts
+    let res = env.optimistic_update(|reader| {
+        let value = reader.get(&db, &"counter").unwrap().unwrap();
+        set_counter(u32::from_mdb_value(&value.to_mdb_value()) + 1);
+        Ok(())
+    }, |_, _| Ok(()));
+    match res {
+        Err(MdbError::StateError(_)) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_database_blob_reader() {
+    use std::io::Read;
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let blob: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        db.set(&"blob", &blob).unwrap();
+
+        let mut reader = db.blob_reader(&"blob").unwrap();
+        let mut assembled = Vec::new();
+        let mut chunk = [0u8; 333];
+        loop {
+            let n = reader.read(&mut chunk).unwrap();
+            if n == 0 {
+                break;
+            }
+            assembled.extend_from_slice(&chunk[..n]);
+        }
+        assert_eq!(reader.remaining(), 0);
+        assert_eq!(assembled, blob);
+
+        assert!(db.blob_reader(&"missing").is_err());
+    }
+}
+
+#[test]
+fn test_migrations() {
+    use core::{MdbResult, Transaction};
+    use migrations::{Migration, Migrator};
+
+    fn create_users(txn: &Transaction) -> MdbResult<()> {
+        let db = try!(txn.open_db("users", DbFlags::empty()));
+        db.set(&"alice", &"1")
+    }
+    fn add_bob(txn: &Transaction) -> MdbResult<()> {
+        let db = try!(txn.open_db("users", DbFlags::empty()));
+        db.set(&"bob", &"2")
+    }
+    fn broken(txn: &Transaction) -> MdbResult<()> {
+        let db = try!(txn.open_db("users", DbFlags::empty()));
+        try!(db.set(&"carol", &"3"));
+        Err(MdbError::StateError("broken".to_owned()))
+    }
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let migrator = Migrator::new()
+        .add(Migration { version: 1, name: "create users", run: create_users })
+        .add(Migration { version: 2, name: "add bob", run: add_bob })
+        .add(Migration { version: 3, name: "broken", run: broken });
+
+    // partial failure keeps the last successful version
+    assert!(migrator.run(&env).is_err());
+    assert_eq!(migrator.current_version(&env).unwrap(), 2);
+    {
+        let users = env.create_db("users", DbFlags::empty()).unwrap();
+        let reader = env.get_reader().unwrap();
+        let db = reader.bind(&users);
+        assert_eq!(db.get::<&str>(&"bob").unwrap(), "2");
+        assert!(db.get::<&str>(&"carol").is_err());
+    }
+
+    let migrator = Migrator::new()
+        .add(Migration { version: 1, name: "create users", run: create_users })
+        .add(Migration { version: 2, name: "add bob", run: add_bob })
+        .add(Migration { version: 3, name: "fixed", run: add_bob });
+    assert_eq!(migrator.run(&env).unwrap(), 3);
+    // second run has nothing to do
+    assert_eq!(migrator.run(&env).unwrap(), 3);
+    assert_eq!(migrator.current_version(&env).unwrap(), 3);
+
+    // older code refuses to touch newer schema
+    let old = Migrator::new()
+        .add(Migration { version: 1, name: "create users", run: create_users });
+    match old.run(&env) {
+        Err(MdbError::StateError(_)) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_ttl_database() {
+    use std::time::{Duration, UNIX_EPOCH};
+    use ttl::TtlDatabase;
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let data = env.create_db("data", DbFlags::empty()).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let expiry = env.create_db("expiry", core::DbAllowDups).unwrap();
+    assert!(TtlDatabase::new(&data, &plain).is_err());
+    let db = TtlDatabase::new(&data, &expiry).unwrap();
+
+    let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    let txn = env.new_transaction().unwrap();
+    db.set_with_ttl(&"a", &"first", at(100), &txn).unwrap();
+    db.set_with_ttl(&"b", &"second", at(200), &txn).unwrap();
+    db.set_with_ttl(&"c", &"third", at(100), &txn).unwrap();
+    // overwrite moves "c" to a later deadline
+    db.set_with_ttl(&"c", &"third again", at(300), &txn).unwrap();
+    db.set(&"d", &"fresh", Duration::from_secs(3600), &txn).unwrap();
+    assert_eq!(db.get::<_, &str, _>(&"c", &txn.bind(&data)).unwrap(), Some("third again"));
+    assert_eq!(db.get_with_expiry::<_, &str, _>(&"b", &txn.bind(&data)).unwrap(), Some(("second", at(200))));
+
+    assert_eq!(db.sweep_expired(at(99), &txn).unwrap(), 0);
+    assert_eq!(db.sweep_expired(at(150), &txn).unwrap(), 1);
+    assert_eq!(db.get::<_, &str, _>(&"a", &txn.bind(&data)).unwrap(), None);
+    assert_eq!(db.get::<_, &str, _>(&"c", &txn.bind(&data)).unwrap(), Some("third again"));
+
+    assert_eq!(db.sweep_expired(at(300), &txn).unwrap(), 2);
+    assert_eq!(db.get::<_, &str, _>(&"b", &txn.bind(&data)).unwrap(), None);
+    assert_eq!(db.get::<_, &str, _>(&"c", &txn.bind(&data)).unwrap(), None);
+    assert_eq!(db.get::<_, &str, _>(&"d", &txn.bind(&data)).unwrap(), Some("fresh"));
+
+    // only the entry which wasn't expired is left in both databases
+    assert_eq!(txn.bind(&data).stat().unwrap().ms_entries, 1);
+    assert_eq!(txn.bind(&expiry).stat().unwrap().ms_entries, 1);
+    db.del(&"d", &txn).unwrap();
+    assert_eq!(txn.bind(&expiry).stat().unwrap().ms_entries, 0);
+}
+
+#[test]
+fn test_ttl_db() {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU64;
+    use std::time::{Duration, UNIX_EPOCH};
+    use ttl::TtlDb;
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let data = env.create_db("data", DbFlags::empty()).unwrap();
+    let expiry = env.create_db("expiry", core::DbAllowDups).unwrap();
+
+    let secs = Arc::new(AtomicU64::new(1000));
+    let clock_secs = secs.clone();
+    let db = TtlDb::new(&data, &expiry).unwrap()
+        .with_clock(Arc::new(move || UNIX_EPOCH + Duration::from_secs(clock_secs.load(Ordering::SeqCst))))
+        .hide_expired(true);
+
+    {
+        let txn = env.new_transaction().unwrap();
+        for i in 0..10u32 {
+            db.put_with_ttl(&i, &i, Duration::from_secs(10 + i as u64 % 2 * 100), &txn).unwrap();
+        }
+        // overwrite extends ttl of 0, its old index item must go away
+        db.put_with_ttl(&0u32, &100u32, Duration::from_secs(500), &txn).unwrap();
+        txn.commit().unwrap();
+    }
+    let index_len = || {
+        let reader = env.get_reader().unwrap();
+        let n = reader.bind(&expiry).stat().unwrap().ms_entries;
+        n
+    };
+    assert_eq!(index_len(), 10);
+
+    secs.store(1050, Ordering::SeqCst);
+    {
+        // expired but not swept entries are hidden
+        let reader = env.get_reader().unwrap();
+        let bound = reader.bind(&data);
+        assert_eq!(db.get::<_, u32, _>(&2u32, &bound).unwrap(), None);
+        assert_eq!(db.get::<_, u32, _>(&3u32, &bound).unwrap(), Some(3));
+        assert_eq!(db.get::<_, u32, _>(&0u32, &bound).unwrap(), Some(100));
+        assert_eq!(db.database().get::<_, u32, _>(&2u32, &bound).unwrap(), Some(2));
+        // index isn't a data database
+        assert!(db.get::<_, u32, _>(&3u32, &reader.bind(&expiry)).is_err());
+    }
+
+    // 2, 4, 6, 8 are expired, swept in batches of 3
+    let now = db.now();
+    assert_eq!(db.sweep(&env, now, 3).unwrap(), 3);
+    assert_eq!(db.sweep(&env, now, 3).unwrap(), 1);
+    assert_eq!(db.sweep(&env, now, 3).unwrap(), 0);
+    assert_eq!(index_len(), 6);
+
+    secs.store(2000, Ordering::SeqCst);
+    assert_eq!(db.sweep(&env, db.now(), 100).unwrap(), 6);
+    assert_eq!(index_len(), 0);
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&data).stat().unwrap().ms_entries, 0);
+
+    // deadline past representable time is an error, not a panic
+    let txn = env.new_transaction().unwrap();
+    match db.put_with_ttl(&1u32, &1u32, Duration::from_secs(u64::MAX), &txn) {
+        Err(MdbError::StateError(_)) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+    match db.database().set(&1u32, &1u32, Duration::from_secs(u64::MAX), &txn) {
+        Err(MdbError::StateError(_)) => (),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_error_equality() {
+    use core::KeyOrValue;
+
+    let errors = vec![
+        MdbError::NotFound, MdbError::KeyExists, MdbError::TxnFull, MdbError::CursorFull,
+        MdbError::PageFull, MdbError::Corrupted, MdbError::Panic, MdbError::Problem, MdbError::InvalidPath,
+        MdbError::StateError("a".to_owned()), MdbError::CacheError,
+        MdbError::BadValSize { what: KeyOrValue::Key, size: 0, max: 511 },
+        MdbError::Other(ffi::MDB_MAP_FULL, "full".to_owned()),
+    ];
+    for (i, a) in errors.iter().enumerate() {
+        for (j, b) in errors.iter().enumerate() {
+            assert_eq!(a == b, i == j, "{:?} vs {:?}", a, b);
+        }
+        assert_eq!(a.clone(), *a);
+    }
+
+    assert!(MdbError::StateError("a".to_owned()) != MdbError::StateError("b".to_owned()));
+    assert!(MdbError::Other(1, "a".to_owned()) != MdbError::Other(2, "a".to_owned()));
+    assert!(MdbError::Other(1, "a".to_owned()) != MdbError::Other(1, "b".to_owned()));
+    assert!(MdbError::BadValSize { what: KeyOrValue::Key, size: 0, max: 511 } !=
+            MdbError::BadValSize { what: KeyOrValue::Value, size: 0, max: 511 });
+
+    assert!(MdbError::NotFound.is_not_found());
+    assert!(MdbError::NotFound.with_context("get").is_not_found());
+    assert!(!MdbError::KeyExists.is_not_found());
+    assert!(MdbError::KeyExists.is_key_exists());
+    assert!(MdbError::KeyExists.with_context("put").is_key_exists());
+    assert!(!MdbError::NotFound.is_key_exists());
+    assert!(MdbError::new_with_code(ffi::MDB_MAP_FULL).is_map_full());
+    assert!(!MdbError::PageFull.is_map_full());
+
+    let env = EnvBuilder::new().map_size(1 << 20).open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    let db = txn.bind(&db);
+    assert!(db.get::<&str>(&"missing").unwrap_err().is_not_found());
+    let value = vec![0u8; 64 * 1024];
+    let err = (0..100u32).map(|i| db.set(&i, &value)).find(|res| res.is_err()).unwrap().unwrap_err();
+    assert!(err.is_map_full(), "{:?}", err);
+}
+
+#[test]
+fn test_count_and_count_dups() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let plain = txn.bind(&plain);
+        let dups = txn.bind(&dups);
+        assert_eq!(plain.count().unwrap(), 0);
+
+        for i in 0..25u32 {
+            plain.set(&i, &i).unwrap();
+        }
+        for i in 0..7u32 {
+            dups.set(&"seven", &i).unwrap();
+        }
+        dups.set(&"one", &0u32).unwrap();
+
+        assert_eq!(plain.count().unwrap(), 25);
+        assert!(plain.count_dups(&3u32).is_err());
+        assert_eq!(dups.count().unwrap(), 8);
+        assert_eq!(dups.count_dups(&"seven").unwrap(), 7);
+        assert_eq!(dups.count_dups(&"one").unwrap(), 1);
+        assert_eq!(dups.count_dups(&"missing"), Err(MdbError::NotFound));
+    }
+}
+
+#[test]
+fn test_item_iter_from() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups | core::DbAllowIntDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for value in &[10u32, 20, 30, 40] {
+            db.set(&"key", value).unwrap();
+        }
+        db.set(&"next", &5u32).unwrap();
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_gte_item(&"key", &25u32).unwrap();
+        assert_eq!(cursor.get_value::<u32>().unwrap(), 30);
+
+        let values: Vec<u32> = db.item_iter_from(&"key", &25u32).unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec![30, 40]);
+        let values: Vec<u32> = db.item_iter_from(&"key", &20u32).unwrap().map(|cv| cv.get_value()).collect();
+        assert_eq!(values, vec![20, 30, 40]);
+        assert_eq!(db.item_iter_from(&"key", &41u32).unwrap().count(), 0);
+        assert_eq!(db.item_iter_from(&"missing", &0u32).unwrap().count(), 0);
+
+        let iter = db.item_iter_from(&"key", &25u32).unwrap();
+        assert_eq!(iter.size_hint(), (0, Some(4)));
+        assert_eq!(iter.last().unwrap().get_value::<u32>(), 40);
+    }
+}
+
+#[test]
+fn test_exists() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let plain = txn.bind(&plain);
+        plain.set(&"key", &"value").unwrap();
+        assert!(plain.exists(&"key").unwrap());
+        assert!(!plain.exists(&"missing").unwrap());
+        assert!(plain.exists_item(&"key", &"value").is_err());
+
+        let dups = txn.bind(&dups);
+        dups.set(&"key", &"a").unwrap();
+        dups.set(&"key", &"c").unwrap();
+        assert!(dups.exists(&"key").unwrap());
+        assert!(dups.exists_item(&"key", &"a").unwrap());
+        assert!(dups.exists_item(&"key", &"c").unwrap());
+        assert!(!dups.exists_item(&"key", &"b").unwrap());
+        assert!(!dups.exists_item(&"missing", &"a").unwrap());
+    }
+}
+
+#[test]
+fn test_cursor_put_multiple() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let fixed = env.create_db("fixed", core::DbAllowDups | core::DbDupFixed | core::DbAllowIntDups).unwrap();
+    let plain = env.create_db("plain", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&fixed);
+        // spans several pages
+        let values: Vec<u32> = (0..10_000u32).rev().collect();
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.put_multiple(&"key", &values).unwrap();
+        cursor.put_multiple(&"key", &[20_000u32, 5]).unwrap();
+        cursor.put_multiple(&"empty", &[] as &[u32]).unwrap();
+
+        assert_eq!(db.count_dups(&"key").unwrap(), 10_001);
+        let stored: Vec<u32> = db.item_iter(&"key").unwrap().map(|cv| cv.get_value()).collect();
+        let mut expected: Vec<u32> = (0..10_000u32).collect();
+        expected.push(20_000);
+        assert_eq!(stored, expected);
+        assert!(!db.exists(&"empty").unwrap());
+
+        let db = txn.bind(&plain);
+        let mut cursor = db.new_cursor().unwrap();
+        assert!(cursor.put_multiple(&"key", &[1u32, 2]).is_err());
+    }
+}
+
+#[test]
+fn test_len_and_key_count() {
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let plain = env.create_db("plain", DbFlags::empty()).unwrap();
+    let dups = env.create_db("dups", core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let plain = txn.bind(&plain);
+        assert_eq!(plain.len().unwrap(), 0);
+        assert!(plain.is_empty().unwrap());
+        assert_eq!(plain.key_count().unwrap(), 0);
+        for i in 0..42u32 {
+            plain.set(&i, &i).unwrap();
+        }
+        assert_eq!(plain.len().unwrap(), 42);
+        assert!(!plain.is_empty().unwrap());
+        assert_eq!(plain.key_count().unwrap(), 42);
+
+        let dups = txn.bind(&dups);
+        for key in 0..10u32 {
+            for value in 0..key + 1 {
+                dups.set(&key, &value).unwrap();
+            }
+        }
+        assert_eq!(dups.len().unwrap(), 55);
+        assert_eq!(dups.count().unwrap(), 55);
+        assert_eq!(dups.key_count().unwrap(), 10);
+    }
+}
+
+#[test]
+fn test_with_transaction_and_reader() {
+    let path = next_path();
+    {
+        let env = EnvBuilder::new().map_size(1 << 20).open(&path, USER_DIR).unwrap();
+        let db = env.get_default_db(DbFlags::empty()).unwrap();
+
+        let res = env.with_transaction(|txn| {
+            try!(txn.bind(&db).set(&"committed", &"yes"));
+            Ok(1)
+        });
+        assert_eq!(res, Ok(1));
+
+        let res: core::MdbResult<()> = env.with_transaction(|txn| {
+            try!(txn.bind(&db).set(&"aborted", &"yes"));
+            Err(MdbError::StateError("closure failed".to_owned()))
+        });
+        assert_eq!(res, Err(MdbError::StateError("closure failed".to_owned())));
+
+        // failed put poisons transaction, so commit fails and closure
+        // result is discarded
+        let res = env.with_transaction(|txn| {
+            let db = txn.bind(&db);
+            let value = vec![0u8; 64 * 1024];
+            let full = (0..100u32).map(|i| db.set(&i, &value)).any(|res| res.is_err());
+            assert!(full);
+            Ok(42)
+        });
+        assert!(res.is_err());
+
+        let res = env.with_reader(|reader| {
+            let db = reader.bind(&db);
+            assert!(!try!(db.exists(&"aborted")));
+            assert!(!try!(db.exists(&0u32)));
+            db.get::<String>(&"committed")
+        });
+        assert_eq!(res, Ok("yes".to_owned()));
+        assert_eq!(env.with_reader(|reader| reader.bind(&db).get::<String>(&"missing")),
+                   Err(MdbError::NotFound));
+    }
+
+    let ro_env = EnvBuilder::new().flags(core::EnvCreateReadOnly).open(&path, USER_DIR).unwrap();
+    let mut called = false;
+    assert!(ro_env.with_transaction(|_| { called = true; Ok(()) }).is_err());
+    assert!(!called);
+}
+
+#[test]
+fn test_item_iter_len_preallocates() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        let expected = ["a", "b", "c", "d"];
+        for v in &expected {
+            db.set(&"key", v).unwrap();
+        }
+
+        let iter = db.item_iter(&"key").unwrap();
+        assert_eq!(iter.len(), expected.len());
+        let mut values = Vec::with_capacity(iter.len());
+        values.extend(iter.map(|cv| cv.get_value::<String>()));
+        assert_eq!(values, expected);
+
+        let zipped = db.item_iter(&"key").unwrap().zip(expected.iter());
+        assert_eq!(zipped.len(), expected.len());
+        for (cv, v) in zipped {
+            assert_eq!(cv.get_value::<&str>(), *v);
+        }
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_copy_to_path_with_progress_compact() {
+    use std::ops::ControlFlow;
+    use std::time::Duration;
+    use core::{EnvCopyCompact, EnvCopyFlags};
+
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    {
+        let txn = env.new_transaction().unwrap();
+        let db = txn.bind(&db);
+        for i in 0..1000u32 {
+            db.set(&i, &vec![0u8; 100]).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+    {
+        let txn = env.new_transaction().unwrap();
+        let db = txn.bind(&db);
+        for i in 10..1000u32 {
+            db.del(&i).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let plain = next_path();
+    fs::create_dir_all(&plain).unwrap();
+    env.copy_to_path_with_progress(&plain, EnvCopyFlags::empty(), Duration::from_millis(1),
+                                   |_| ControlFlow::Continue(())).unwrap();
+
+    let compact = next_path();
+    fs::create_dir_all(&compact).unwrap();
+    let mut reports = Vec::new();
+    env.copy_to_path_with_progress(&compact, EnvCopyCompact, Duration::from_millis(1), |p| {
+        reports.push(p);
+        ControlFlow::Continue(())
+    }).unwrap();
+
+    let size = fs::metadata(compact.join("data.mdb")).unwrap().len();
+    assert!(size < fs::metadata(plain.join("data.mdb")).unwrap().len());
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes_written, size);
+    assert!(last.expected_bytes.is_none());
+
+    let copy = EnvBuilder::new().open(&compact, USER_DIR).unwrap();
+    let db = copy.get_default_db(DbFlags::empty()).unwrap();
+    let reader = copy.get_reader().unwrap();
+    assert_eq!(reader.bind(&db).stat().unwrap().ms_entries, 10);
+}
+
+#[test]
+fn test_cursor_get_multiple() {
+    let env = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let db = env.get_default_db(core::DbAllowDups | core::DbDupFixed).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.bind(&db);
+        for i in 0..2000u32 {
+            db.set(&"key", &i).unwrap();
+        }
+
+        let mut cursor = db.new_cursor().unwrap();
+        cursor.to_key(&"key").unwrap();
+        let mut total = cursor.get_multiple().unwrap().len();
+        // page borrow ends here, so cursor can be moved again
+        loop {
+            match cursor.next_multiple() {
+                Ok(page) => total += page.len(),
+                Err(MdbError::NotFound) => break,
+                Err(e) => panic!("{}", e),
+            }
+        }
+        assert_eq!(total, 2000 * 4);
+        assert!(cursor.prev_multiple().unwrap().len() > 0);
+    }
+    txn.abort();
+}
+
+#[test]
+fn test_get_mapped() {
+    let path = next_path();
+    {
+        let env = EnvBuilder::new().open(&path, USER_DIR).unwrap();
+        let db = env.get_default_db(DbFlags::empty()).unwrap();
+        let txn = env.new_transaction().unwrap();
+        txn.bind(&db).set(&"key", &"mapped value").unwrap();
+        txn.commit().unwrap();
+    }
+
+    let env = EnvBuilder::new()
+        .flags(core::EnvCreateReadOnly)
+        .open(&path, USER_DIR).unwrap();
+    let db = env.get_default_db(DbFlags::empty()).unwrap();
+    let value = {
+        let reader = env.get_reader().unwrap();
+        // clone shares the mapping
+        assert!(unsafe { reader.bind(&db).get_mapped(&env.clone(), &"key").is_ok() });
+        unsafe { reader.bind(&db).get_mapped(&env, &"key").unwrap() }
+    };
+    assert_eq!(value, b"mapped value");
+
+    let other = EnvBuilder::new().open(&next_path(), USER_DIR).unwrap();
+    let reader = env.get_reader().unwrap();
+    let res = unsafe { reader.bind(&db).get_mapped(&other, &"key") };
+    assert!(res.is_err());
+}
+
+#[test]
+fn test_migrations_run_once() {
+    use core::{MdbResult, Transaction};
+    use migrations::{Migration, Migrator};
+
+    static FIRST: AtomicUsize = ATOMIC_USIZE_INIT;
+    static SECOND: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn first(txn: &Transaction) -> MdbResult<()> {
+        FIRST.fetch_add(1, Ordering::SeqCst);
+        let db = try!(txn.open_db("counters", DbFlags::empty()));
+        db.set(&"first", &1u32)
+    }
+    fn second(txn: &Transaction) -> MdbResult<()> {
+        SECOND.fetch_add(1, Ordering::SeqCst);
+        // slow migration keeps the other runner waiting for write lock
+        thread::sleep(::std::time::Duration::from_millis(20));
+        let db = try!(txn.open_db("counters", DbFlags::empty()));
+        db.set(&"second", &2u32)
+    }
+
+    let env = EnvBuilder::new().max_dbs(5).open(&next_path(), USER_DIR).unwrap();
+    let migrator = Migrator::new()
+        .add(Migration { version: 1, name: "first", run: first })
+        .add(Migration { version: 2, name: "second", run: second });
+
+    // both runners see version 0 before either starts migrating
+    let barrier = Arc::new(Barrier::new(2));
+    let runners: Vec<_> = (0..2).map(|_| {
+        let env = env.clone();
+        let migrator = migrator.clone();
+        let barrier = barrier.clone();
+        thread::spawn(move || {
+            assert_eq!(migrator.current_version(&env).unwrap(), 0);
+            barrier.wait();
+            migrator.run(&env).unwrap()
+        })
+    }).collect();
+    for runner in runners {
+        assert_eq!(runner.join().unwrap(), 2);
+    }
+
+    assert_eq!(FIRST.load(Ordering::SeqCst), 1);
+    assert_eq!(SECOND.load(Ordering::SeqCst), 1);
+    assert_eq!(migrator.run(&env).unwrap(), 2);
+    assert_eq!(FIRST.load(Ordering::SeqCst) + SECOND.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_open_db_caches_on_commit() {
+    let env = EnvBuilder::new()
+        .max_dbs(5)
+        .open(&next_path(), USER_DIR)
+        .unwrap();
+
+    let txn = env.new_transaction().unwrap();
+    txn.open_db("aborted", DbFlags::empty()).unwrap();
+    txn.abort();
+    assert!(!env.is_db_cached("aborted"));
+    assert!(env.get_db("aborted", DbFlags::empty()).is_err());
+
+    let txn = env.new_transaction().unwrap();
+    {
+        let child = txn.new_child().unwrap();
+        child.open_db("child aborted", DbFlags::empty()).unwrap();
+        child.abort();
+    }
+    {
+        let child = txn.new_child().unwrap();
+        child.open_db("child committed", DbFlags::empty()).unwrap().set(&"key", &"child").unwrap();
+        child.commit().unwrap();
+    }
+    txn.open_db("root", DbFlags::empty()).unwrap().set(&"key", &"root").unwrap();
+    // not visible until top level transaction is committed
+    assert!(!env.is_db_cached("child committed"));
+    assert!(!env.is_db_cached("root"));
+    txn.commit().unwrap();
+
+    assert!(!env.is_db_cached("child aborted"));
+    assert!(env.is_db_cached("child committed"));
+    assert!(env.is_db_cached("root"));
+
+    // cached handle is reused
+    let handle = env.get_db("root", DbFlags::empty()).unwrap();
+    let txn = env.new_transaction().unwrap();
+    {
+        let db = txn.open_db("root", DbFlags::empty()).unwrap();
+        assert_eq!(db.get::<&str>(&"key").unwrap(), "root");
+        db.set(&"key", &"updated").unwrap();
+    }
+    txn.commit().unwrap();
+    let reader = env.get_reader().unwrap();
+    assert_eq!(reader.bind(&handle).get::<&str>(&"key").unwrap(), "updated");
+}
+
 /*
 #[test]
 fn test_compilation_of_moved_items() {
@@ -0,0 +1,108 @@
+//! Helpers for bulk operations spanning multiple transactions
+
+use core::{DbHandle, Environment, MdbError, MdbResult, Transaction};
+use traits::ToMdbValue;
+
+/// Options for [import](fn.import.html)
+pub struct ImportOptions {
+    /// Count of rows written in a single transaction
+    pub txn_chunk_size: usize,
+    /// Use `append` instead of `set`, requires input sorted by key
+    pub use_append: bool,
+    /// Called after each chunk commit with total count of committed rows
+    pub progress: Option<Box<FnMut(u64)>>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> ImportOptions {
+        ImportOptions {
+            txn_chunk_size: 1000,
+            use_append: false,
+            progress: None,
+        }
+    }
+}
+
+/// Result of [import](fn.import.html)
+#[derive(Debug)]
+pub struct ImportReport {
+    /// Count of rows durably committed
+    pub committed: u64,
+    /// Error which stopped import, if any. Rows of the chunk which
+    /// failed are not committed
+    pub error: Option<MdbError>,
+}
+
+/// Imports rows from iterator into database, committing every
+/// `txn_chunk_size` rows to keep transactions reasonably small.
+///
+/// Import stops on the first error, either yielded by iterator or
+/// returned by database. It is reported in `ImportReport` together
+/// with count of already committed rows, so the caller can resume.
+pub fn import<I, K, V>(env: &Environment, db: &DbHandle, iter: I, opts: ImportOptions) -> MdbResult<ImportReport>
+    where I: IntoIterator<Item=MdbResult<(K, V)>>, K: ToMdbValue, V: ToMdbValue
+{
+    if opts.txn_chunk_size == 0 {
+        return Err(MdbError::StateError("import chunk size must be positive".to_owned()));
+    }
+
+    let mut opts = opts;
+    let mut iter = iter.into_iter();
+    let mut report = ImportReport {
+        committed: 0,
+        error: None,
+    };
+
+    loop {
+        let res = env.new_transaction().and_then(|txn| {
+            let written = try!(import_chunk(&txn, db, &mut iter, &opts));
+            if written > 0 {
+                try!(txn.commit());
+            }
+            Ok(written)
+        });
+
+        match res {
+            Err(e) => {
+                report.error = Some(e);
+                break;
+            },
+            Ok(written) => {
+                if written > 0 {
+                    report.committed += written as u64;
+                    if let Some(ref mut progress) = opts.progress {
+                        progress(report.committed);
+                    }
+                }
+                if written < opts.txn_chunk_size {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Writes up to a chunk of rows, returns count of written rows
+fn import_chunk<It, K, V>(txn: &Transaction, db: &DbHandle, iter: &mut It, opts: &ImportOptions) -> MdbResult<usize>
+    where It: Iterator<Item=MdbResult<(K, V)>>, K: ToMdbValue, V: ToMdbValue
+{
+    let db = txn.bind(db);
+    let mut written = 0;
+    while written < opts.txn_chunk_size {
+        match iter.next() {
+            None => break,
+            Some(row) => {
+                let (k, v) = try!(row);
+                if opts.use_append {
+                    try!(db.append(&k, &v));
+                } else {
+                    try!(db.set(&k, &v));
+                }
+                written += 1;
+            }
+        }
+    }
+    Ok(written)
+}
@@ -34,6 +34,22 @@ pub trait FromMdbValue {
     fn from_mdb_value(value: &MdbValue) -> Self;
 }
 
+/// `FromMdbValueOwned` marks types which copy data out of the
+/// memory slice, so they stay valid after the database is
+/// modified, unlike `&str` or `&[u8]`:
+///
+/// ```compile_fail
+/// # use lmdb_rs::core::EnvBuilder;
+/// # let env = EnvBuilder::new().open("unused", 0o777).unwrap();
+/// # let handle = env.get_default_db(lmdb_rs::DbFlags::empty()).unwrap();
+/// let txn = env.new_transaction().unwrap();
+/// let db = txn.bind(&handle);
+/// let old: Option<&str> = db.swap(&"key", &"new").unwrap();
+/// ```
+
+pub trait FromMdbValueOwned: FromMdbValue {
+}
+
 impl ToMdbValue for Vec<u8> {
     fn to_mdb_value<'a>(&'a self) -> MdbValue<'a> {
         unsafe {
@@ -88,7 +104,7 @@ impl FromMdbValue for String {
     fn from_mdb_value(value: &MdbValue) -> String {
         unsafe {
             let ptr = mem::transmute(value.get_ref());
-            let data: Vec<u8> = slice::from_raw_parts(ptr, value.get_size()).to_vec();
+            let data: Vec<u8> = slice::from_raw_parts(ptr, value.len()).to_vec();
             String::from_utf8(data).unwrap()
         }
     }
@@ -98,7 +114,7 @@ impl FromMdbValue for Vec<u8> {
     fn from_mdb_value(value: &MdbValue) -> Vec<u8> {
         unsafe {
             let ptr = mem::transmute(value.get_ref());
-            slice::from_raw_parts(ptr, value.get_size()).to_vec()
+            slice::from_raw_parts(ptr, value.len()).to_vec()
         }
     }
 }
@@ -108,10 +124,14 @@ impl FromMdbValue for () {
     }
 }
 
+impl FromMdbValueOwned for String {}
+impl FromMdbValueOwned for Vec<u8> {}
+impl FromMdbValueOwned for () {}
+
 impl<'b> FromMdbValue for &'b str {
     fn from_mdb_value(value: &MdbValue) -> &'b str {
         unsafe {
-            std::mem::transmute(slice::from_raw_parts(value.get_ref(), value.get_size()))
+            std::mem::transmute(slice::from_raw_parts(value.get_ref(), value.len()))
         }
     }
 }
@@ -119,7 +139,7 @@ impl<'b> FromMdbValue for &'b str {
 impl<'b> FromMdbValue for &'b [u8] {
     fn from_mdb_value(value: &MdbValue) -> &'b [u8] {
         unsafe {
-            std::mem::transmute(slice::from_raw_parts(value.get_ref(), value.get_size()))
+            std::mem::transmute(slice::from_raw_parts(value.get_ref(), value.len()))
         }
     }
 }
@@ -135,12 +155,15 @@ macro_rules! mdb_for_primitive {
         impl FromMdbValue for $t {
             fn from_mdb_value(value: &MdbValue) -> $t {
                 unsafe {
-                    let t: *mut $t = mem::transmute(value.get_ref());
-                    *t
+                    // data isn't guaranteed to be aligned in db pages
+                    let t: *const $t = mem::transmute(value.get_ref());
+                    std::ptr::read_unaligned(t)
                 }
             }
         }
 
+        impl FromMdbValueOwned for $t {}
+
         )
 }
 
@@ -152,6 +175,7 @@ mdb_for_primitive!(u32);
 mdb_for_primitive!(i32);
 mdb_for_primitive!(u64);
 mdb_for_primitive!(i64);
+mdb_for_primitive!(usize);
 mdb_for_primitive!(f32);
 mdb_for_primitive!(f64);
 mdb_for_primitive!(bool);
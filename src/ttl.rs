@@ -0,0 +1,254 @@
+//! Entries with expiration time (`TtlDatabase`, `TtlDb`)
+//!
+//! Besides data database an expiry index is kept: its keys are
+//! big-endian expiration timestamps (milliseconds since Unix epoch),
+//! so default key ordering puts the earliest deadline first, and its
+//! values are keys of data database. Index database must be opened
+//! with `DbAllowDups`, note that LMDB limits size of duplicate
+//! values, so keys of data database have to fit `max_key_size`.
+//!
+//! Data values are stored with expiration timestamp prepended, which
+//! allows to find and remove stale index item on overwrite.
+//!
+//! `TtlDatabase` takes all times explicitly and works within
+//! caller's transactions, `TtlDb` adds a clock and sweeps in batches.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use core::{describe_db, Database, DbAllowDups, DbHandle, Environment, MdbError, MdbResult, MdbValue, NotFound, StateError, Transaction};
+use traits::{FromMdbValue, ToMdbValue};
+
+const STAMP_SIZE: usize = 8;
+
+fn to_stamp(time: SystemTime) -> [u8; STAMP_SIZE] {
+    // times before epoch are expired anyway
+    let millis = time.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    millis.to_be_bytes()
+}
+
+fn expires_after(now: SystemTime, ttl: Duration) -> MdbResult<SystemTime> {
+    now.checked_add(ttl).ok_or_else(|| StateError(format!("ttl {:?} is out of time range", ttl)))
+}
+
+fn split_stamp(data: &[u8]) -> MdbResult<(&[u8], &[u8])> {
+    if data.len() < STAMP_SIZE {
+        return Err(MdbError::StateError(format!("unexpected ttl value size {}", data.len())));
+    }
+    Ok(data.split_at(STAMP_SIZE))
+}
+
+/// Database with entries which are removed by `sweep_expired` once
+/// their expiration time has passed
+#[derive(Clone, Debug)]
+pub struct TtlDatabase {
+    data: DbHandle,
+    expiry: DbHandle,
+}
+
+impl TtlDatabase {
+    /// Creates TTL database stored in `data` with expiry index in
+    /// `expiry`, fails with `StateError` if `expiry` isn't opened
+    /// with `DbAllowDups`
+    pub fn new(data: &DbHandle, expiry: &DbHandle) -> MdbResult<TtlDatabase> {
+        if !expiry.flags().contains(DbAllowDups) {
+            return Err(MdbError::StateError(format!("expiry index requires DUPSORT flag, {} has {}",
+                                                    describe_db(expiry.name()), expiry.flags())));
+        }
+        Ok(TtlDatabase {
+            data: *data,
+            expiry: *expiry,
+        })
+    }
+
+    /// Sets value for key which expires after `ttl` from now, fails
+    /// with `StateError` if expiration time can't be represented
+    pub fn set<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V, ttl: Duration, txn: &Transaction) -> MdbResult<()> {
+        self.set_with_ttl(key, value, try!(expires_after(SystemTime::now(), ttl)), txn)
+    }
+
+    /// Sets value for key which expires at `expires_at`, replacing
+    /// previous value and its expiration time
+    pub fn set_with_ttl<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V, expires_at: SystemTime,
+                                                      txn: &Transaction) -> MdbResult<()> {
+        let data = txn.bind(&self.data);
+        let expiry = txn.bind(&self.expiry);
+        let key = key.to_mdb_value();
+
+        match data.get::<&[u8]>(&key) {
+            Ok(old) => {
+                let (stamp, _) = try!(split_stamp(old));
+                match expiry.del_item(&stamp, &key) {
+                    Ok(_) | Err(NotFound) => (),
+                    Err(e) => return Err(e),
+                }
+            },
+            Err(NotFound) => (),
+            Err(e) => return Err(e),
+        }
+
+        let stamp = to_stamp(expires_at);
+        let value = value.to_mdb_value();
+        let mut buf = Vec::with_capacity(STAMP_SIZE + value.len());
+        buf.extend_from_slice(&stamp);
+        buf.extend_from_slice(value.as_slice());
+        try!(data.set(&key, &buf));
+        expiry.set(&&stamp[..], &key)
+    }
+
+    /// Retrieves a value by key from data database bound to either
+    /// kind of transaction, `None` if key is missing. Entries which
+    /// are expired but not swept yet are still returned.
+    pub fn get<'t, K: ToMdbValue, V: FromMdbValue + 't, M>(&self, key: &K, data: &'t Database<'t, M>) -> MdbResult<Option<V>> {
+        self.get_with_expiry(key, data).map(|res| res.map(|(value, _)| value))
+    }
+
+    /// Retrieves a value by key along with its expiration time, fails
+    /// with `StateError` if `data` isn't bound to data database
+    pub fn get_with_expiry<'t, K: ToMdbValue, V: FromMdbValue + 't, M>(&self, key: &K, data: &'t Database<'t, M>)
+                                                                     -> MdbResult<Option<(V, SystemTime)>> {
+        if !data.is_bound_to(&self.data) {
+            return Err(StateError(format!("expected data {}, got {}", describe_db(self.data.name()), describe_db(data.name()))));
+        }
+        let stored = match data.get::<&'t [u8]>(key) {
+            Ok(stored) => stored,
+            Err(NotFound) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let (stamp, value) = try!(split_stamp(stored));
+        let mut millis = [0u8; STAMP_SIZE];
+        millis.copy_from_slice(stamp);
+        let expires_at = UNIX_EPOCH + Duration::from_millis(u64::from_be_bytes(millis));
+        let value = unsafe { MdbValue::new(value.as_ptr() as *const _, value.len()) };
+        Ok(Some((V::from_mdb_value(&value), expires_at)))
+    }
+
+    /// Deletes value for key along with its index item
+    pub fn del<K: ToMdbValue>(&self, key: &K, txn: &Transaction) -> MdbResult<()> {
+        let data = txn.bind(&self.data);
+        let key = key.to_mdb_value();
+        let stored = try!(data.get::<&[u8]>(&key));
+        let (stamp, _) = try!(split_stamp(stored));
+        match txn.bind(&self.expiry).del_item(&stamp, &key) {
+            Ok(_) | Err(NotFound) => (),
+            Err(e) => return Err(e),
+        }
+        data.del(&key)
+    }
+
+    /// Deletes all entries which expire at or before `now`, returns
+    /// count of deleted entries
+    pub fn sweep_expired(&self, now: SystemTime, txn: &Transaction) -> MdbResult<usize> {
+        self.sweep_limited(now, usize::MAX, txn)
+    }
+
+    fn sweep_limited(&self, now: SystemTime, limit: usize, txn: &Transaction) -> MdbResult<usize> {
+        let data = txn.bind(&self.data);
+        let expiry = txn.bind(&self.expiry);
+        let now = to_stamp(now);
+        let mut cursor = try!(expiry.new_cursor());
+        let mut count = 0;
+        while count < limit {
+            match cursor.to_first() {
+                Ok(_) => (),
+                Err(NotFound) => break,
+                Err(e) => return Err(e),
+            }
+            let (stamp, key) = try!(cursor.get::<&[u8], Vec<u8>>());
+            if stamp > &now[..] {
+                break;
+            }
+            try!(cursor.del_item());
+            match data.del(&key) {
+                Ok(_) => count += 1,
+                Err(NotFound) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// Source of current time for `TtlDb`
+pub type Clock = Arc<Fn() -> SystemTime + Send + Sync>;
+
+/// `TtlDatabase` with a clock, optionally hides expired entries
+/// which weren't swept yet
+#[derive(Clone)]
+pub struct TtlDb {
+    db: TtlDatabase,
+    clock: Clock,
+    hide_expired: bool,
+}
+
+impl TtlDb {
+    /// Creates TTL database using system clock, see
+    /// [TtlDatabase::new](struct.TtlDatabase.html#method.new)
+    pub fn new(data: &DbHandle, expiry: &DbHandle) -> MdbResult<TtlDb> {
+        TtlDatabase::new(data, expiry).map(|db| TtlDb {
+            db: db,
+            clock: Arc::new(SystemTime::now),
+            hide_expired: false,
+        })
+    }
+
+    /// Replaces clock used to compute expiration times
+    pub fn with_clock(mut self, clock: Clock) -> TtlDb {
+        self.clock = clock;
+        self
+    }
+
+    /// If set `get` reports entries which are expired but not swept
+    /// yet as missing
+    pub fn hide_expired(mut self, hide: bool) -> TtlDb {
+        self.hide_expired = hide;
+        self
+    }
+
+    /// Returns underlying database
+    pub fn database(&self) -> &TtlDatabase {
+        &self.db
+    }
+
+    /// Returns current time according to clock
+    pub fn now(&self) -> SystemTime {
+        (self.clock)()
+    }
+
+    /// Sets value for key which expires after `ttl`, fails with
+    /// `StateError` if expiration time can't be represented
+    pub fn put_with_ttl<K: ToMdbValue, V: ToMdbValue>(&self, key: &K, value: &V, ttl: Duration,
+                                                      txn: &Transaction) -> MdbResult<()> {
+        self.db.set_with_ttl(key, value, try!(expires_after(self.now(), ttl)), txn)
+    }
+
+    /// Retrieves a value by key from bound data database, `None` if
+    /// key is missing
+    pub fn get<'t, K: ToMdbValue, V: FromMdbValue + 't, M>(&self, key: &K, data: &'t Database<'t, M>) -> MdbResult<Option<V>> {
+        match try!(self.db.get_with_expiry(key, data)) {
+            Some((_, expires_at)) if self.hide_expired && expires_at <= self.now() => Ok(None),
+            res => Ok(res.map(|(value, _)| value)),
+        }
+    }
+
+    /// Deletes up to `limit` entries expired at `now` in a single
+    /// write transaction, returns count of deleted entries. Call
+    /// again while it returns `limit` to sweep everything.
+    pub fn sweep(&self, env: &Environment, now: SystemTime, limit: usize) -> MdbResult<usize> {
+        let txn = try!(env.new_transaction());
+        let count = try!(self.db.sweep_limited(now, limit, &txn));
+        try!(txn.commit());
+        Ok(count)
+    }
+}
+
+// Fn doesn't implement Debug
+impl fmt::Debug for TtlDb {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TtlDb")
+            .field("db", &self.db)
+            .field("hide_expired", &self.hide_expired)
+            .finish()
+    }
+}
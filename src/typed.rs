@@ -0,0 +1,159 @@
+//! Databases with fixed key and value types (`TypedDb`)
+//!
+//! `Database` methods take any `ToMdbValue` and return whatever
+//! `FromMdbValue` is asked for, so reading a `String` where `Vec<u8>`
+//! was stored compiles just fine. `TypedDatabase` fixes both types
+//! once, mismatches become compile errors:
+//!
+//! ```compile_fail
+//! # use lmdb_rs::core::EnvBuilder;
+//! # let env = EnvBuilder::new().open("unused", 0o777).unwrap();
+//! let handle = env.get_default_db(lmdb_rs::DbFlags::empty()).unwrap().typed::<String, u64>();
+//! let txn = env.new_transaction().unwrap();
+//! let db = txn.bind_typed(&handle);
+//! db.set(&"key".to_owned(), &"not a number".to_owned()).unwrap();
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use core::{CursorFromKeyIter, CursorIter, CursorOwnedKeyRangeIter, CursorToKeyIter, Database, DbHandle};
+use core::{MdbResult, NotFound};
+use core::{ReadOnly, ReadWrite, ReadonlyTransaction, Transaction, TypedIter};
+use traits::{FromMdbValue, ToMdbValue};
+
+/// Handle of database with keys of type `K` and values of type `V`,
+/// see [typed](../core/struct.DbHandle.html#method.typed)
+pub struct TypedDb<K, V> {
+    handle: DbHandle,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V> TypedDb<K, V> {
+    /// Returns underlying untyped handle
+    pub fn handle(&self) -> &DbHandle {
+        &self.handle
+    }
+}
+
+// derive would require K and V to be Clone and Debug
+impl<K, V> Clone for TypedDb<K, V> {
+    fn clone(&self) -> TypedDb<K, V> {
+        TypedDb {
+            handle: self.handle,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for TypedDb<K, V> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("TypedDb").field("handle", &self.handle).finish()
+    }
+}
+
+impl DbHandle {
+    /// Fixes key and value types of database, bind result with
+    /// `bind_typed` of a transaction
+    pub fn typed<K, V>(&self) -> TypedDb<K, V>
+        where K: ToMdbValue + FromMdbValue, V: ToMdbValue + FromMdbValue
+    {
+        TypedDb {
+            handle: *self,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Binds typed database to this transaction
+    pub fn bind_typed<K, V>(&self, db: &TypedDb<K, V>) -> TypedDatabase<'_, K, V> {
+        TypedDatabase {
+            db: self.bind(&db.handle),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a> ReadonlyTransaction<'a> {
+    /// Binds typed database to this transaction
+    pub fn bind_typed<K, V>(&self, db: &TypedDb<K, V>) -> TypedDatabase<'_, K, V, ReadOnly> {
+        TypedDatabase {
+            db: self.bind(&db.handle),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Database bound to a transaction which accepts only `K` keys
+/// and `V` values
+#[derive(Debug)]
+pub struct TypedDatabase<'a, K, V, M = ReadWrite> {
+    db: Database<'a, M>,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<'a, K, V, M> TypedDatabase<'a, K, V, M>
+    where K: ToMdbValue + FromMdbValue + 'a, V: ToMdbValue + FromMdbValue + 'a
+{
+    /// Returns underlying untyped database
+    pub fn inner(&self) -> &Database<'a, M> {
+        &self.db
+    }
+
+    /// Retrieves a value by key, `None` if key is missing
+    pub fn get(&'a self, key: &K) -> MdbResult<Option<V>> {
+        match self.db.get(key) {
+            Ok(value) => Ok(Some(value)),
+            Err(NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns an iterator over all items in key order
+    pub fn iter(&'a self) -> MdbResult<TypedIter<'a, CursorIter, K, V>> {
+        self.db.iter_typed()
+    }
+
+    /// Returns an iterator over all keys in key order
+    pub fn keys(&'a self) -> MdbResult<impl Iterator<Item=K> + 'a> {
+        self.db.iter_keys()
+    }
+
+    /// Returns an iterator over all values in key order
+    pub fn values(&'a self) -> MdbResult<impl Iterator<Item=V> + 'a> {
+        self.db.iter_values()
+    }
+
+    /// Returns an iterator over items with keys `from <= key <= to`
+    pub fn range(&self, from: &K, to: &K) -> MdbResult<TypedIter<'_, CursorOwnedKeyRangeIter, K, V>> {
+        let from = from.to_mdb_value().as_slice().to_vec();
+        let to = to.to_mdb_value().as_slice().to_vec();
+        self.db.keyrange_owned(from, to).map(|it| it.typed())
+    }
+
+    /// Returns an iterator over items with keys starting from `start`,
+    /// which is included only if `inclusive` is true
+    pub fn iter_from<'c>(&'c self, start: &'c K, inclusive: bool) -> MdbResult<TypedIter<'c, CursorFromKeyIter<'c>, K, V>> {
+        self.db.iter_from(start, inclusive).map(|it| it.typed())
+    }
+
+    /// Returns an iterator over items with keys less than `end`
+    pub fn iter_to<'c>(&'c self, end: &'c K) -> MdbResult<TypedIter<'c, CursorToKeyIter<'c>, K, V>> {
+        self.db.keyrange_to(end).map(|it| it.typed())
+    }
+}
+
+impl<'a, K, V> TypedDatabase<'a, K, V, ReadWrite>
+    where K: ToMdbValue + FromMdbValue, V: ToMdbValue + FromMdbValue
+{
+    /// Sets value for key
+    pub fn set(&self, key: &K, value: &V) -> MdbResult<()> {
+        self.db.set(key, value)
+    }
+
+    /// Deletes value for key
+    pub fn del(&self, key: &K) -> MdbResult<()> {
+        self.db.del(key)
+    }
+}